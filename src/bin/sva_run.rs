@@ -0,0 +1,102 @@
+#![warn(clippy::all, rust_2018_idioms)]
+//! Headless runner for projects exported from sva_ui
+//!
+//! Usage: `sva_run <project.json>... [--cycles N]`
+//!
+//! Runs every vm one instruction per cycle until all of them finish or cycle limit is reached,
+//! then prints registers, ports, stacks and ram contents as json to stdout.
+//! Every project is run on its own, with more than one project a list of results is printed
+use std::fs;
+use std::process::ExitCode;
+
+use serde_json::Value;
+
+use sva_ui::project::{self, document::Document};
+
+const DEFAULT_CYCLES: usize = 10_000;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut paths: Vec<String> = Vec::new();
+    let mut max_cycles = DEFAULT_CYCLES;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--cycles" | "-n" => match args_iter.next().map(|v| v.parse::<usize>()) {
+                Some(Ok(cycles)) if cycles > 0 => max_cycles = cycles,
+                _ => {
+                    eprintln!("--cycles expects a positive number");
+                    return ExitCode::from(2);
+                }
+            },
+            "--help" | "-h" => {
+                print_usage();
+                return ExitCode::SUCCESS;
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    if paths.is_empty() {
+        print_usage();
+        return ExitCode::from(2);
+    }
+
+    let mut results = Vec::new();
+    for path in paths.iter() {
+        match run_project(path, max_cycles) {
+            Ok(result) => results.push(result),
+            Err(err) => {
+                eprintln!("{}", err);
+                return ExitCode::from(1);
+            }
+        }
+    }
+
+    let output = if results.len() == 1 {
+        results.remove(0)
+    } else {
+        Value::Array(results)
+    };
+    match serde_json::to_string_pretty(&output) {
+        Ok(json) => {
+            println!("{}", json);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Could not serialize state: {}", err);
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Runs project from file, returns its final state
+fn run_project(path: &str, max_cycles: usize) -> Result<Value, String> {
+    let data =
+        fs::read_to_string(path).map_err(|err| format!("Could not open file {}: {}", path, err))?;
+    let project =
+        project::load(&data).map_err(|err| format!("Invalid project {}: {}", path, err))?;
+    let mut document = Document::from_project(project);
+
+    let mut cycles = 0;
+    while cycles < max_cycles && !document.all_vms_finished() {
+        document.step_all();
+        cycles += 1;
+    }
+
+    let mut output = document.state_to_json();
+    output["file"] = serde_json::json!(path);
+    output["cycles"] = serde_json::json!(cycles);
+    output["finished"] = serde_json::json!(document.all_vms_finished());
+    Ok(output)
+}
+
+fn print_usage() {
+    eprintln!("Usage: sva_run <project.json>... [--cycles N]");
+    eprintln!(
+        "Runs every exported project for N cycles (default {}) or until every vm finishes",
+        DEFAULT_CYCLES
+    );
+}
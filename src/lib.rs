@@ -21,6 +21,8 @@ i18n!("locales");
 
 //-------------------------------------------------------
 
+pub mod model;
+pub mod project;
 pub mod storage;
 pub mod ui;
 
@@ -5,6 +5,7 @@ use std::fs::File;
 use std::io::Write;
 
 use chrono::{DateTime, Utc};
+use sva_ui::storage::recovery::RecoveryManager;
 
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
@@ -30,6 +31,12 @@ fn main() -> eframe::Result<()> {
         let painc_path = format!("SVA_panic_log{}.txt", formatted_datetime);
         save_to_file(painc_path, &message);
 
+        // Save last auto-saved project next to panic log
+        if let Some(project) = RecoveryManager::last_snapshot() {
+            let project_path = format!("SVA_panic_project{}.json", formatted_datetime);
+            save_to_file(project_path, &project);
+        }
+
         // Save logs to file
         //let logs_path = format!("SVA_logs{}.txt", formatted_datetime);
 
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use super::condition::Condition;
+
+/// Breakpoint on line of source code, optionally with condition like `acc == 5`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Breakpoint {
+    /// Line of code, counted from 0
+    pub line: usize,
+    /// Condition as typed by user, empty means always stop
+    condition: String,
+    /// Condition parsed once after it was set, not saved
+    #[serde(skip)]
+    parsed: Option<Result<Condition, String>>,
+}
+
+impl Breakpoint {
+    pub fn new(line: usize) -> Self {
+        Self::with_condition(line, String::new())
+    }
+
+    pub fn with_condition(line: usize, condition: String) -> Self {
+        Self {
+            line,
+            condition,
+            parsed: None,
+        }
+    }
+
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
+
+    pub fn set_condition(&mut self, condition: String) {
+        self.condition = condition;
+        self.parsed = None;
+    }
+
+    /// Condition parsed on first use after it was set, None when it's empty
+    fn parsed(&mut self) -> Option<&Result<Condition, String>> {
+        if self.condition.trim().is_empty() {
+            return None;
+        }
+        Some(
+            self.parsed
+                .get_or_insert_with(|| Condition::parse(&self.condition)),
+        )
+    }
+
+    /// Returns error message if condition can't be parsed
+    pub fn condition_error(&mut self) -> Option<String> {
+        self.parsed()?.as_ref().err().cloned()
+    }
+
+    /// Checks if execution should stop, invalid condition never stops
+    pub fn should_stop(&mut self, lookup: impl Fn(&str) -> Option<i32>) -> bool {
+        match self.parsed() {
+            None => true,
+            Some(Ok(condition)) => condition.evaluate(lookup).unwrap_or(false),
+            Some(Err(_err)) => false,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+/// What clock writes to its port every period
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockMode {
+    /// 1, cleared by program that noticed it
+    #[default]
+    Pulse,
+    /// Number of periods since clock was reset
+    Counter,
+}
+
+/// Unit of clock period
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockUnit {
+    #[default]
+    Ms,
+    /// Ticks of lockstep scheduler
+    Ticks,
+}
+
+/// Writes pulse or counter to its port every period
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Clock {
+    pub mode: ClockMode,
+    pub period: u64,
+    pub unit: ClockUnit,
+    pub running: bool,
+    count: i32,
+    /// Time or tick at which current period started
+    #[serde(skip)]
+    period_start: Option<f64>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            mode: ClockMode::Pulse,
+            period: 1000,
+            unit: ClockUnit::Ms,
+            running: true,
+            count: 0,
+            period_start: None,
+        }
+    }
+}
+
+impl Clock {
+    pub const PORTS: [&'static str; 1] = ["out"];
+    const OUT: usize = 0;
+
+    pub fn new(mode: ClockMode, period: u64, unit: ClockUnit, running: bool) -> Self {
+        Self {
+            mode,
+            period: period.max(1),
+            unit,
+            running,
+            ..Default::default()
+        }
+    }
+
+    pub fn count(&self) -> i32 {
+        self.count
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.period_start = None;
+    }
+
+    /// Changes unit of period, current period starts again
+    pub fn set_unit(&mut self, unit: ClockUnit) {
+        if self.unit != unit {
+            self.unit = unit;
+            self.period_start = None;
+        }
+    }
+
+    /// Counts periods that passed, time is in seconds and tick is tick of scheduler,
+    /// either of them is None when it didn't change. Returns values to be written to ports
+    pub fn advance(&mut self, time: Option<f64>, tick: Option<u64>) -> Vec<(usize, i32)> {
+        if !self.running {
+            self.period_start = None;
+            return Vec::new();
+        }
+        let (now, period) = match self.unit {
+            ClockUnit::Ms => match time {
+                Some(time) => (time, self.period as f64 / 1000.0),
+                None => return Vec::new(),
+            },
+            ClockUnit::Ticks => match tick {
+                Some(tick) => (tick as f64, self.period as f64),
+                None => return Vec::new(),
+            },
+        };
+        let start = match self.period_start {
+            // scheduler was reset
+            Some(start) if start <= now => start,
+            _ => {
+                self.period_start = Some(now);
+                return Vec::new();
+            }
+        };
+        let periods = ((now - start) / period).floor();
+        if periods < 1.0 {
+            return Vec::new();
+        }
+        self.period_start = Some(start + periods * period);
+        self.count = self.count.wrapping_add(periods as i32);
+        match self.mode {
+            ClockMode::Pulse => vec![(Clock::OUT, 1)],
+            ClockMode::Counter => vec![(Clock::OUT, self.count)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter_counts_ticks() {
+        let mut clock = Clock::new(ClockMode::Counter, 2, ClockUnit::Ticks, true);
+        assert_eq!(clock.advance(None, Some(0)), vec![]);
+        assert_eq!(clock.advance(None, Some(1)), vec![]);
+        assert_eq!(clock.advance(None, Some(2)), vec![(Clock::OUT, 1)]);
+        // time doesn't move clock counting ticks
+        assert_eq!(clock.advance(Some(100.0), None), vec![]);
+        assert_eq!(clock.advance(None, Some(6)), vec![(Clock::OUT, 3)]);
+        assert_eq!(clock.count(), 3);
+    }
+
+    #[test]
+    fn pulse_is_written_once_for_periods_that_passed() {
+        let mut clock = Clock::new(ClockMode::Pulse, 500, ClockUnit::Ms, true);
+        assert_eq!(clock.advance(Some(1.0), None), vec![]);
+        assert_eq!(clock.advance(Some(1.4), None), vec![]);
+        assert_eq!(clock.advance(Some(2.1), None), vec![(Clock::OUT, 1)]);
+        assert_eq!(clock.count(), 2);
+    }
+
+    #[test]
+    fn period_starts_again_after_stop_and_scheduler_reset() {
+        let mut clock = Clock::new(ClockMode::Counter, 2, ClockUnit::Ticks, true);
+        clock.advance(None, Some(10));
+        clock.running = false;
+        assert_eq!(clock.advance(None, Some(20)), vec![]);
+        clock.running = true;
+        assert_eq!(clock.advance(None, Some(21)), vec![]);
+        assert_eq!(clock.advance(None, Some(23)), vec![(Clock::OUT, 1)]);
+
+        // ticks went back, so scheduler was reset
+        assert_eq!(clock.advance(None, Some(0)), vec![]);
+        assert_eq!(clock.advance(None, Some(2)), vec![(Clock::OUT, 2)]);
+        clock.reset();
+        assert_eq!(clock.count(), 0);
+    }
+}
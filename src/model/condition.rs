@@ -0,0 +1,204 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CompareOp {
+    Equal,
+    NotEqual,
+    Lesser,
+    LesserOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl CompareOp {
+    /// Longer operators first so `<=` isn't matched as `<`
+    const ALL: [(&'static str, CompareOp); 6] = [
+        ("==", CompareOp::Equal),
+        ("!=", CompareOp::NotEqual),
+        ("<=", CompareOp::LesserOrEqual),
+        (">=", CompareOp::GreaterOrEqual),
+        ("<", CompareOp::Lesser),
+        (">", CompareOp::Greater),
+    ];
+
+    pub fn compare(&self, left: i32, right: i32) -> bool {
+        match self {
+            CompareOp::Equal => left == right,
+            CompareOp::NotEqual => left != right,
+            CompareOp::Lesser => left < right,
+            CompareOp::LesserOrEqual => left <= right,
+            CompareOp::Greater => left > right,
+            CompareOp::GreaterOrEqual => left >= right,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        CompareOp::ALL
+            .iter()
+            .find(|(_, op)| op == self)
+            .map(|(symbol, _)| *symbol)
+            .unwrap_or("==")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Operand {
+    /// Name of register, port or other value, resolved when condition is evaluated
+    Name(String),
+    Value(i32),
+}
+
+impl Operand {
+    fn parse(text: &str) -> Result<Operand, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(t!("condition.error.missing_operand"));
+        }
+        if let Some(value) = parse_value(text) {
+            return Ok(Operand::Value(value));
+        }
+        if text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Ok(Operand::Name(text.to_lowercase()));
+        }
+        Err(format!("{}: {}", t!("condition.error.bad_operand"), text))
+    }
+
+    fn resolve(&self, lookup: &impl Fn(&str) -> Option<i32>) -> Result<i32, String> {
+        match self {
+            Operand::Value(value) => Ok(*value),
+            Operand::Name(name) => lookup(name)
+                .ok_or_else(|| format!("{}: {}", t!("condition.error.unknown_name"), name)),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Name(name) => write!(f, "{}", name),
+            Operand::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Comparison of two operands, e.g. `acc == 5` or `r0 > r1`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Condition {
+    left: Operand,
+    op: CompareOp,
+    right: Operand,
+}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Condition, String> {
+        for (symbol, op) in CompareOp::ALL {
+            if let Some((left, right)) = text.split_once(symbol) {
+                return Ok(Condition {
+                    left: Operand::parse(left)?,
+                    op,
+                    right: Operand::parse(right)?,
+                });
+            }
+        }
+        Err(t!("condition.error.missing_operator"))
+    }
+
+    /// Evaluates condition, `lookup` returns value for name of register/port
+    pub fn evaluate(&self, lookup: impl Fn(&str) -> Option<i32>) -> Result<bool, String> {
+        let left = self.left.resolve(&lookup)?;
+        let right = self.right.resolve(&lookup)?;
+        Ok(self.op.compare(left, right))
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.left, self.op.symbol(), self.right)
+    }
+}
+
+/// Parses value written in decimal, binary (0b), hex (0x) or as char ('a')
+pub fn parse_value(text: &str) -> Option<i32> {
+    let text = text.trim();
+    if let Some(binary) = text.strip_prefix("0b") {
+        return i32::from_str_radix(binary, 2).ok();
+    }
+    if let Some(hex) = text.strip_prefix("0x") {
+        return i32::from_str_radix(hex, 16).ok();
+    }
+    if text.len() == 3 && text.starts_with('\'') && text.ends_with('\'') {
+        return text.chars().nth(1).map(|c| c as i32);
+    }
+    text.parse::<i32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_values_in_every_notation() {
+        assert_eq!(parse_value("42"), Some(42));
+        assert_eq!(parse_value(" -7 "), Some(-7));
+        assert_eq!(parse_value("0b101"), Some(5));
+        assert_eq!(parse_value("0x1F"), Some(31));
+        assert_eq!(parse_value("'a'"), Some(97));
+        assert_eq!(parse_value("0b2"), None);
+        assert_eq!(parse_value("acc"), None);
+        assert_eq!(parse_value("''"), None);
+    }
+
+    #[test]
+    fn parses_condition_with_longest_operator() {
+        let condition = Condition::parse("acc <= 0x10").unwrap();
+        assert_eq!(
+            condition,
+            Condition {
+                left: Operand::Name("acc".to_owned()),
+                op: CompareOp::LesserOrEqual,
+                right: Operand::Value(16),
+            }
+        );
+        assert_eq!(condition.to_string(), "acc <= 16");
+
+        let condition = Condition::parse("R0!=r1").unwrap();
+        assert_eq!(condition.op, CompareOp::NotEqual);
+        assert_eq!(condition.left, Operand::Name("r0".to_owned()));
+        assert_eq!(condition.right, Operand::Name("r1".to_owned()));
+    }
+
+    #[test]
+    fn rejects_bad_conditions() {
+        assert!(Condition::parse("acc").is_err());
+        assert!(Condition::parse("== 5").is_err());
+        assert!(Condition::parse("acc > ").is_err());
+        assert!(Condition::parse("acc + 1 > 5").is_err());
+    }
+
+    #[test]
+    fn evaluates_condition_with_lookup() {
+        let lookup = |name: &str| match name {
+            "acc" => Some(5),
+            "r0" => Some(-1),
+            _ => None,
+        };
+        assert_eq!(
+            Condition::parse("acc == 5").unwrap().evaluate(lookup),
+            Ok(true)
+        );
+        assert_eq!(
+            Condition::parse("r0 > acc").unwrap().evaluate(lookup),
+            Ok(false)
+        );
+        assert_eq!(
+            Condition::parse("-1 >= r0").unwrap().evaluate(lookup),
+            Ok(true)
+        );
+        assert!(Condition::parse("r9 == 0")
+            .unwrap()
+            .evaluate(lookup)
+            .is_err());
+    }
+}
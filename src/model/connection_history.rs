@@ -0,0 +1,61 @@
+use std::collections::{HashMap, VecDeque};
+
+/// How many value changes are kept per connection
+pub const CONNECTION_HISTORY_CAPACITY: usize = 100;
+
+/// Value that appeared on connection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueChange {
+    pub value: i32,
+    /// Seconds since app start
+    pub time: f64,
+    /// Lockstep tick, when value was written by scheduler
+    pub tick: Option<u64>,
+}
+
+impl ValueChange {
+    /// Tick number in lockstep mode, time otherwise
+    pub fn when(&self) -> String {
+        match self.tick {
+            Some(tick) => format!("{} {}", t!("scheduler.tick"), tick),
+            None => format!("{:.2} s", self.time),
+        }
+    }
+}
+
+/// Recent values of every connection, only changes are stored
+#[derive(Default)]
+pub struct ConnectionHistory {
+    changes: HashMap<usize, VecDeque<ValueChange>>,
+}
+
+impl ConnectionHistory {
+    /// Stores value unless it is the same as last one
+    pub fn record(&mut self, conn_id: usize, change: ValueChange) {
+        let changes = self.changes.entry(conn_id).or_default();
+        if changes.back().map(|last| last.value) == Some(change.value) {
+            return;
+        }
+        if changes.len() == CONNECTION_HISTORY_CAPACITY {
+            changes.pop_front();
+        }
+        changes.push_back(change);
+    }
+
+    /// Changes of connection, newest first
+    pub fn changes(&self, conn_id: usize) -> Vec<ValueChange> {
+        self.changes
+            .get(&conn_id)
+            .map(|changes| changes.iter().rev().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forgets history of connections that no longer exist
+    pub fn retain(&mut self, keep: impl Fn(usize) -> bool) {
+        self.changes.retain(|id, _| keep(*id));
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::condition::parse_value;
+use super::value_format::ValueFormat;
+
+/// How many printed values are kept
+const CONSOLE_CAPACITY: usize = 4096;
+
+/// Text console, values written to output port are printed and text typed by user
+/// is fed to input port one value at a time.
+/// Port holding 0 is empty, whoever reads value from port writes 0 back to it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Console {
+    pub format: ValueFormat,
+    /// Values printed so far
+    output: Vec<i32>,
+    /// Values waiting to be fed to input port
+    #[serde(skip)]
+    input: VecDeque<i32>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self {
+            format: ValueFormat::Unicode,
+            output: Vec::new(),
+            input: VecDeque::new(),
+        }
+    }
+}
+
+impl Console {
+    pub const PORTS: [&'static str; 2] = ["output", "input"];
+    const OUTPUT: usize = 0;
+    const INPUT: usize = 1;
+
+    pub fn with_format(format: ValueFormat) -> Self {
+        Self {
+            format,
+            ..Default::default()
+        }
+    }
+
+    pub fn output(&self) -> &[i32] {
+        &self.output
+    }
+
+    /// Output as characters, or as numbers separated by spaces in other formats
+    pub fn output_text(&self) -> String {
+        match self.format {
+            ValueFormat::Unicode => self
+                .output
+                .iter()
+                .map(|value| {
+                    u32::try_from(*value)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER)
+                })
+                .collect(),
+            format => self
+                .output
+                .iter()
+                .map(|value| format.format_value(*value))
+                .collect::<Vec<String>>()
+                .join(" "),
+        }
+    }
+
+    pub fn clear_output(&mut self) {
+        self.output.clear();
+    }
+
+    pub fn queued_input(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Queues typed text, in unicode format as characters followed by new line,
+    /// otherwise as values separated by whitespace. Returns value that couldn't be parsed
+    pub fn queue_input(&mut self, text: &str) -> Result<(), String> {
+        let values: Vec<i32> = match self.format {
+            ValueFormat::Unicode => text.chars().chain(['\n']).map(|c| c as i32).collect(),
+            _ => text
+                .split_whitespace()
+                .map(|word| parse_value(word).ok_or_else(|| word.to_string()))
+                .collect::<Result<Vec<i32>, String>>()?,
+        };
+        // 0 means that port is empty so it can't be sent
+        self.input
+            .extend(values.into_iter().filter(|value| *value != 0));
+        Ok(())
+    }
+
+    /// Takes value from output port and puts next queued value on empty input port,
+    /// returns values to be written to ports
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        let mut writes = Vec::new();
+        if let Some(Some(value)) = values.get(Console::OUTPUT) {
+            if *value != 0 {
+                if self.output.len() == CONSOLE_CAPACITY {
+                    self.output.remove(0);
+                }
+                self.output.push(*value);
+                writes.push((Console::OUTPUT, 0));
+            }
+        }
+        if let Some(Some(0)) = values.get(Console::INPUT) {
+            if let Some(value) = self.input.pop_front() {
+                writes.push((Console::INPUT, value));
+            }
+        }
+        writes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_is_printed_and_port_emptied() {
+        let mut console = Console::default();
+        assert_eq!(
+            console.update(&[Some(72), None]),
+            vec![(Console::OUTPUT, 0)]
+        );
+        assert_eq!(console.update(&[Some(0), None]), vec![]);
+        console.update(&[Some(105), None]);
+        assert_eq!(console.output_text(), "Hi");
+
+        console.format = ValueFormat::Hex;
+        assert_eq!(console.output_text(), "0x48 0x69");
+        console.clear_output();
+        assert!(console.output().is_empty());
+    }
+
+    #[test]
+    fn input_is_fed_to_empty_port() {
+        let mut console = Console::default();
+        console.queue_input("a").unwrap();
+        assert_eq!(console.queued_input(), 2);
+
+        // program didn't take previous value yet
+        assert_eq!(console.update(&[None, Some(5)]), vec![]);
+        assert_eq!(console.update(&[None, Some(0)]), vec![(Console::INPUT, 97)]);
+        assert_eq!(console.update(&[None, Some(0)]), vec![(Console::INPUT, 10)]);
+        assert_eq!(console.update(&[None, Some(0)]), vec![]);
+    }
+
+    #[test]
+    fn numbers_are_queued_in_other_formats() {
+        let mut console = Console::with_format(ValueFormat::Dec);
+        assert_eq!(console.queue_input("0x10 x"), Err("x".to_string()));
+        assert_eq!(console.queued_input(), 0);
+        // 0 would leave port empty, so it's dropped
+        console.queue_input("16 0 -3").unwrap();
+        assert_eq!(console.queued_input(), 2);
+        assert_eq!(console.update(&[None, Some(0)]), vec![(Console::INPUT, 16)]);
+    }
+}
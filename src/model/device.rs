@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use simple_virtual_assembler::components::connection::Connection;
+use simple_virtual_assembler::components::port::Port;
+use simple_virtual_assembler::vm::virtual_machine::VirtualMachine;
+
+use super::clock::{Clock, ClockUnit};
+use super::console::Console;
+use super::keypad::Keypad;
+use super::led_bar::LedBar;
+use super::pixel_display::PixelDisplay;
+use super::push_buttons::PushButtons;
+use super::rom::Rom;
+use super::seven_segment::SevenSegment;
+use super::switches::Switches;
+
+/// What device does, together with its state
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum DeviceKind {
+    Console(Console),
+    Display(PixelDisplay),
+    Clock(Clock),
+    LedBar(LedBar),
+    SevenSegment(SevenSegment),
+    Switches(Switches),
+    Buttons(PushButtons),
+    Keypad(Keypad),
+    Rom(Rom),
+}
+
+impl DeviceKind {
+    /// Name used in project files and default names of devices
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DeviceKind::Console(_) => "console",
+            DeviceKind::Display(_) => "display",
+            DeviceKind::Clock(_) => "clock",
+            DeviceKind::LedBar(_) => "leds",
+            DeviceKind::SevenSegment(_) => "segments",
+            DeviceKind::Switches(_) => "switches",
+            DeviceKind::Buttons(_) => "buttons",
+            DeviceKind::Keypad(_) => "keypad",
+            DeviceKind::Rom(_) => "rom",
+        }
+    }
+
+    pub fn port_names(&self) -> &'static [&'static str] {
+        match self {
+            DeviceKind::Console(_) => &Console::PORTS,
+            DeviceKind::Display(_) => &PixelDisplay::PORTS,
+            DeviceKind::Clock(_) => &Clock::PORTS,
+            DeviceKind::LedBar(_) => &LedBar::PORTS,
+            DeviceKind::SevenSegment(_) => &SevenSegment::PORTS,
+            DeviceKind::Switches(_) => &Switches::PORTS,
+            DeviceKind::Buttons(_) => &PushButtons::PORTS,
+            DeviceKind::Keypad(_) => &Keypad::PORTS,
+            DeviceKind::Rom(_) => &Rom::PORTS,
+        }
+    }
+
+    /// Reacts to values on ports, None for ports that aren't connected,
+    /// returns values to be written to ports
+    fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        match self {
+            DeviceKind::Console(console) => console.update(values),
+            DeviceKind::Display(display) => display.update(values),
+            DeviceKind::Clock(_) => Vec::new(),
+            DeviceKind::LedBar(leds) => leds.update(values),
+            DeviceKind::SevenSegment(segments) => segments.update(values),
+            DeviceKind::Switches(switches) => switches.update(values),
+            DeviceKind::Buttons(buttons) => buttons.update(values),
+            DeviceKind::Keypad(keypad) => keypad.update(values),
+            DeviceKind::Rom(rom) => rom.update(values),
+        }
+    }
+
+    /// Counts time in seconds or ticks of scheduler, returns values to be written to ports
+    fn advance(&mut self, time: Option<f64>, tick: Option<u64>) -> Vec<(usize, i32)> {
+        match self {
+            DeviceKind::Clock(clock) => clock.advance(time, tick),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Does device change on its own, so ui has to be repainted
+    pub fn is_running(&self) -> bool {
+        match self {
+            DeviceKind::Clock(clock) => clock.running && clock.unit == ClockUnit::Ms,
+            _ => false,
+        }
+    }
+}
+
+/// Component other than vm or ram, e.g. console or display, connected to circuit through its ports.
+/// Ports of device are ports of vm hidden inside it, so device can have at most as many ports as vm
+#[derive(Serialize, Deserialize)]
+pub struct Device {
+    id: usize,
+    name: String,
+    /// Vm providing ports of device, it never executes any program
+    ports: VirtualMachine,
+    pub kind: DeviceKind,
+}
+
+impl Device {
+    pub fn new(id: usize, kind: DeviceKind) -> Self {
+        Self {
+            id,
+            name: format!("{}:{}", kind.type_name(), id),
+            ports: VirtualMachine::new(),
+            kind,
+        }
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn port_count(&self) -> usize {
+        self.kind.port_names().len()
+    }
+
+    pub fn port(&self, index: usize) -> Port {
+        self.ports
+            .get_ports()
+            .into_iter()
+            .nth(index)
+            .unwrap_or(Port::new(0))
+    }
+
+    /// Connects port and stores its id in connection
+    pub fn connect(&mut self, index: usize, conn: &mut Connection, port_id: String) {
+        self.ports.connect_with_id(index, conn, port_id);
+    }
+
+    /// Connects port to connection already holding its id
+    pub fn reconnect(&mut self, index: usize, conn: &mut Connection) {
+        self.ports.connect(index, conn);
+    }
+
+    pub fn disconnect(&mut self, index: usize) {
+        self.ports.disconnect(index);
+    }
+
+    /// Values on ports, None for ports that aren't connected
+    pub fn port_values(&self) -> Vec<Option<i32>> {
+        let ports = self.ports.get_ports();
+        let values = self.ports.get_state_for_display().4;
+        (0..self.port_count())
+            .map(|index| ports[index].get_conn_id().map(|_| values[index]))
+            .collect()
+    }
+
+    /// Lets device react to values on its ports, returns values to be written to ports
+    pub fn update(&mut self) -> Vec<(usize, i32)> {
+        let values = self.port_values();
+        self.kind.update(&values)
+    }
+
+    /// Lets device count time, returns values to be written to ports
+    pub fn advance(&mut self, time: Option<f64>, tick: Option<u64>) -> Vec<(usize, i32)> {
+        self.kind.advance(time, tick)
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::VecDeque;
+
+use serde_json::Value;
+use simple_virtual_assembler::vm::flag::Flag;
+use simple_virtual_assembler::vm::virtual_machine::VmStatus;
+
+/// How many executed instructions can be undone per vm
+pub const HISTORY_CAPACITY: usize = 256;
+
+/// Value of ram cell before it was overwritten
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RamWrite {
+    pub ram_id: usize,
+    pub index: usize,
+    pub old_value: i32,
+}
+
+/// Registers of vm, program and ports are left out
+pub struct Registers {
+    pub acc: i32,
+    pub pc: usize,
+    pub flag: Flag,
+    pub r: [i32; 4],
+    pub status: VmStatus,
+    /// None for vm without stack
+    pub stack: Option<Vec<i32>>,
+}
+
+impl Registers {
+    /// Writes registers over fields of serialized vm, fails if vm has no such field
+    pub fn write_to(&self, vm: &mut Value) -> Result<(), String> {
+        let fields = [
+            ("acc", serde_json::json!(self.acc)),
+            ("pc", serde_json::json!(self.pc)),
+            ("flag", serde_json::json!(self.flag)),
+            ("r", serde_json::json!(self.r)),
+            ("status", serde_json::json!(self.status)),
+        ];
+        for (name, value) in fields {
+            *vm.get_mut(name).ok_or(name)? = value;
+        }
+        if let Some(stack) = &self.stack {
+            let serialized = vm.get_mut("stack").ok_or("stack")?;
+            let current = current_stack_values(serialized).ok_or("stack")?;
+            replace_array(serialized, &current, serde_json::json!(stack));
+        }
+        Ok(())
+    }
+}
+
+/// Values of serialized stack, which is either array or struct holding one
+fn current_stack_values(stack: &Value) -> Option<Value> {
+    match stack {
+        Value::Array(_) => Some(stack.clone()),
+        Value::Object(fields) => fields.values().find_map(current_stack_values),
+        _ => None,
+    }
+}
+
+fn replace_array(value: &mut Value, current: &Value, new: Value) -> bool {
+    if value == current {
+        *value = new;
+        return true;
+    }
+    match value {
+        Value::Object(fields) => fields
+            .values_mut()
+            .any(|field| replace_array(field, current, new.clone())),
+        _ => false,
+    }
+}
+
+/// State of vm and things it touched, taken before executing an instruction
+pub struct Snapshot {
+    pub registers: Registers,
+    /// Values of connections on vm ports, as (connection id, value)
+    pub connection_values: Vec<(usize, i32)>,
+    /// Ram cells changed after instruction was executed
+    pub ram_writes: Vec<RamWrite>,
+}
+
+/// Bounded history of executed instructions, oldest snapshots are dropped
+pub struct History {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(HISTORY_CAPACITY),
+        }
+    }
+}
+
+impl History {
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() >= HISTORY_CAPACITY {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut Snapshot> {
+        self.snapshots.back_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Snapshots from newest to oldest
+    pub fn iter(&self) -> impl Iterator<Item = &Snapshot> {
+        self.snapshots.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stack_is_replaced_inside_struct_holding_it() {
+        let mut stack = json!({ "size": 4, "data": [1, 2] });
+        let current = current_stack_values(&stack).unwrap();
+        assert_eq!(current, json!([1, 2]));
+
+        assert!(replace_array(&mut stack, &current, json!([1])));
+        assert_eq!(stack, json!({ "size": 4, "data": [1] }));
+    }
+
+    #[test]
+    fn plain_stack_is_replaced() {
+        let mut stack = json!([3]);
+        let current = current_stack_values(&stack).unwrap();
+        assert!(replace_array(&mut stack, &current, json!([])));
+        assert_eq!(stack, json!([]));
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Numeric keypad, pressed keys are fed to its port one at a time as character codes,
+/// like input of console. Port holding 0 is empty, program should write 0 after reading key
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Keypad {
+    /// Keys waiting to be fed to port
+    #[serde(skip)]
+    pressed: VecDeque<i32>,
+}
+
+impl Keypad {
+    pub const PORTS: [&'static str; 1] = ["key"];
+    const KEY: usize = 0;
+
+    pub fn queued_keys(&self) -> usize {
+        self.pressed.len()
+    }
+
+    pub fn press(&mut self, key: char) {
+        self.pressed.push_back(key as i32);
+    }
+
+    /// Drops keys that weren't fed to port yet
+    pub fn clear(&mut self) {
+        self.pressed.clear();
+    }
+
+    /// Puts next pressed key on empty port, returns values to be written to ports
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        if let Some(Some(0)) = values.get(Keypad::KEY) {
+            if let Some(key) = self.pressed.pop_front() {
+                return vec![(Keypad::KEY, key)];
+            }
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_fed_to_empty_port() {
+        let mut keypad = Keypad::default();
+        keypad.press('5');
+        keypad.press('#');
+        assert_eq!(keypad.update(&[None]), vec![]);
+        assert_eq!(keypad.update(&[Some(0)]), vec![(Keypad::KEY, '5' as i32)]);
+        // key stays on port until program takes it
+        assert_eq!(keypad.update(&[Some('5' as i32)]), vec![]);
+        assert_eq!(keypad.update(&[Some(0)]), vec![(Keypad::KEY, '#' as i32)]);
+        assert_eq!(keypad.queued_keys(), 0);
+        assert_eq!(keypad.update(&[Some(0)]), vec![]);
+    }
+
+    #[test]
+    fn cleared_keys_are_dropped() {
+        let mut keypad = Keypad::default();
+        keypad.press('1');
+        keypad.clear();
+        assert_eq!(keypad.update(&[Some(0)]), vec![]);
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// Row of leds showing bits of value on its port, most significant bit on the left
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LedBar {
+    bits: usize,
+    #[serde(skip)]
+    value: i32,
+}
+
+impl Default for LedBar {
+    fn default() -> Self {
+        Self::new(8)
+    }
+}
+
+impl LedBar {
+    pub const PORTS: [&'static str; 1] = ["in"];
+    const IN: usize = 0;
+
+    pub fn new(bits: usize) -> Self {
+        Self {
+            bits: bits.clamp(1, 32),
+            value: 0,
+        }
+    }
+
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    pub fn set_bits(&mut self, bits: usize) {
+        self.bits = bits.clamp(1, 32);
+    }
+
+    /// Value last read from port
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    /// Is bit lit, bit 0 is least significant
+    pub fn is_lit(&self, bit: usize) -> bool {
+        bit < self.bits && ((self.value as u32) >> bit) & 1 == 1
+    }
+
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        self.value = values.get(LedBar::IN).copied().flatten().unwrap_or(0);
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leds_show_bits_of_port_value() {
+        let mut leds = LedBar::new(8);
+        // value stays on port, it isn't taken by leds
+        assert_eq!(leds.update(&[Some(0b1010_1011)]), vec![]);
+        let lit: Vec<bool> = (0..8).map(|bit| leds.is_lit(bit)).collect();
+        assert_eq!(lit, [true, true, false, true, false, true, false, true]);
+        assert!(!leds.is_lit(8));
+
+        leds.update(&[Some(-1)]);
+        assert!(leds.is_lit(7));
+        leds.update(&[None]);
+        assert_eq!(leds.value(), 0);
+    }
+
+    #[test]
+    fn bit_count_is_clamped() {
+        let mut leds = LedBar::new(0);
+        assert_eq!(leds.bits(), 1);
+        leds.set_bits(40);
+        assert_eq!(leds.bits(), 32);
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use simple_virtual_assembler::components::ram::Ram;
+
+use super::port_driver::PortDriver;
+
+/// Number of cells of new ram
+pub const DEFAULT_RAM_SIZE: usize = 512;
+pub const MIN_RAM_SIZE: usize = 16;
+pub const MAX_RAM_SIZE: usize = 65536;
+
+/// Ram of circuit with its name, shown by `RamWidow`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Memory {
+    id: usize,
+    name: String,
+    pub ram: Ram,
+}
+
+impl Memory {
+    pub fn new(id: usize) -> Self {
+        Self::with_size(id, DEFAULT_RAM_SIZE)
+    }
+
+    pub fn with_size(id: usize, size: usize) -> Self {
+        Self {
+            ram: Ram::new()
+                .with_id(id)
+                .with_size(size.clamp(MIN_RAM_SIZE, MAX_RAM_SIZE)),
+            id,
+            name: format!("ram:{}", id),
+        }
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn size(&self) -> usize {
+        self.ram.get_data_ref().len()
+    }
+
+    /// Changes number of cells, values that still fit are kept.
+    /// Ports are left disconnected, `Circuit::resize_ram` connects them again
+    pub fn resize(&mut self, size: usize) {
+        let data = self.get_data();
+        self.ram = Ram::new()
+            .with_id(self.id)
+            .with_size(size.clamp(MIN_RAM_SIZE, MAX_RAM_SIZE));
+        for (index, value) in data.into_iter().enumerate().take(self.size()) {
+            self.ram.set_value(index, value);
+        }
+    }
+
+    pub fn get_value(&self, index: usize) -> Option<i32> {
+        self.ram.get_data_ref().get(index).copied()
+    }
+
+    pub fn get_data(&self) -> Vec<i32> {
+        self.ram.get_data_ref().clone()
+    }
+
+    pub fn zero_data(&mut self) {
+        self.ram.zero_data();
+    }
+
+    /// Cell pointed by index port, the only one refresh can write value of data port to.
+    /// None when data port is disconnected, disconnected index port stays at 0
+    pub fn addressed_index(&self) -> Option<usize> {
+        PortDriver::read(&self.ram.get_data_port())?;
+        let index = PortDriver::read(&self.ram.get_index_port()).unwrap_or(0);
+        usize::try_from(index)
+            .ok()
+            .filter(|index| *index < self.size())
+    }
+
+    pub fn refresh(&mut self) {
+        self.ram.refresh();
+    }
+
+    pub fn set_value_at_index(&mut self, index: usize, value: i32) {
+        if index < self.size() {
+            self.ram.set_value(index, value);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! State of circuit components and logic driving them
+//!
+//! Nothing here depends on egui, windows showing components live in `ui`
+pub mod breakpoint;
+pub mod clock;
+pub mod condition;
+pub mod connection_history;
+pub mod console;
+pub mod device;
+pub mod history;
+pub mod keypad;
+pub mod led_bar;
+pub mod memory;
+pub mod pixel_display;
+pub mod port_driver;
+pub mod push_buttons;
+pub mod rom;
+pub mod scheduler;
+pub mod seven_segment;
+pub mod source_map;
+pub mod switches;
+pub mod value_format;
+pub mod vm;
+pub mod watchpoint;
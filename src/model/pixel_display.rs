@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+/// Largest width and height of display
+pub const MAX_DISPLAY_SIZE: usize = 128;
+
+/// Display with pixels set through its ports, pixel at `x`, `y` gets colour `color`
+/// when non zero value is written to `write` port, which is then set back to 0.
+/// Colours are `0xRRGGBB`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PixelDisplay {
+    width: usize,
+    height: usize,
+    /// Colours of pixels, row by row
+    pixels: Vec<i32>,
+}
+
+impl Default for PixelDisplay {
+    fn default() -> Self {
+        Self::new(32, 32)
+    }
+}
+
+impl PixelDisplay {
+    pub const PORTS: [&'static str; 4] = ["x", "y", "color", "write"];
+    const X: usize = 0;
+    const Y: usize = 1;
+    const COLOR: usize = 2;
+    const WRITE: usize = 3;
+
+    pub fn new(width: usize, height: usize) -> Self {
+        let width = width.clamp(1, MAX_DISPLAY_SIZE);
+        let height = height.clamp(1, MAX_DISPLAY_SIZE);
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<i32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels.get(y * self.width + x).copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = 0);
+    }
+
+    /// Changes size, pixels that still fit stay where they were
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut resized = PixelDisplay::new(width, height);
+        for y in 0..resized.height.min(self.height) {
+            for x in 0..resized.width.min(self.width) {
+                resized.pixels[y * resized.width + x] = self.pixels[y * self.width + x];
+            }
+        }
+        *self = resized;
+    }
+
+    /// Sets pixel when `write` port holds non zero value, returns values to be written to ports
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        let value = |port: usize| values.get(port).copied().flatten().unwrap_or(0);
+        if value(PixelDisplay::WRITE) == 0 {
+            return Vec::new();
+        }
+        let x = usize::try_from(value(PixelDisplay::X)).ok();
+        let y = usize::try_from(value(PixelDisplay::Y)).ok();
+        if let (Some(x), Some(y)) = (x, y) {
+            if x < self.width && y < self.height {
+                self.pixels[y * self.width + x] = value(PixelDisplay::COLOR);
+            }
+        }
+        vec![(PixelDisplay::WRITE, 0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_is_set_on_write() {
+        let mut display = PixelDisplay::new(8, 8);
+        assert_eq!(
+            display.update(&[Some(3), Some(4), Some(255), Some(0)]),
+            vec![]
+        );
+        assert_eq!(display.pixel(3, 4), Some(0));
+
+        // write port is emptied so next pixel can be drawn
+        assert_eq!(
+            display.update(&[Some(3), Some(4), Some(255), Some(1)]),
+            vec![(PixelDisplay::WRITE, 0)]
+        );
+        assert_eq!(display.pixel(3, 4), Some(255));
+        assert_eq!(display.pixel(4, 3), Some(0));
+        assert_eq!(display.pixel(8, 0), None);
+    }
+
+    #[test]
+    fn writes_outside_display_are_ignored() {
+        let mut display = PixelDisplay::new(2, 2);
+        for (x, y) in [(-1, 0), (0, 2), (5, 5)] {
+            assert_eq!(
+                display.update(&[Some(x), Some(y), Some(7), Some(1)]),
+                vec![(PixelDisplay::WRITE, 0)]
+            );
+        }
+        assert!((0..2).all(|y| (0..2).all(|x| display.pixel(x, y) == Some(0))));
+    }
+
+    #[test]
+    fn resize_keeps_pixels_that_fit() {
+        let mut display = PixelDisplay::new(4, 4);
+        display.update(&[Some(1), Some(1), Some(9), Some(1)]);
+        display.update(&[Some(3), Some(0), Some(5), Some(1)]);
+
+        display.resize(2, 6);
+        assert_eq!((display.width(), display.height()), (2, 6));
+        assert_eq!(display.pixel(1, 1), Some(9));
+        assert_eq!(display.pixel(3, 0), None);
+        display.resize(0, MAX_DISPLAY_SIZE + 1);
+        assert_eq!((display.width(), display.height()), (1, MAX_DISPLAY_SIZE));
+    }
+}
@@ -0,0 +1,28 @@
+use simple_virtual_assembler::components::port::Port;
+
+/// Reads and writes values of connected ports, all ports connected to the same
+/// connection share one value, so writing to any of them sets value of connection
+pub struct PortDriver {}
+
+impl PortDriver {
+    /// Sets value of connection port is connected to, returns false for disconnected port
+    pub fn write(port: &Port, value: i32) -> bool {
+        match port {
+            Port::Connected(shared, _) => {
+                *shared.lock().unwrap_or_else(|err| err.into_inner()) = value;
+                true
+            }
+            Port::Disconnected(_) => false,
+        }
+    }
+
+    /// Value of connection port is connected to, None for disconnected port
+    pub fn read(port: &Port) -> Option<i32> {
+        match port {
+            Port::Connected(shared, _) => {
+                Some(*shared.lock().unwrap_or_else(|err| err.into_inner()))
+            }
+            Port::Disconnected(_) => None,
+        }
+    }
+}
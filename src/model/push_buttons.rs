@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+/// Momentary push buttons, bits of buttons held down are written to its port,
+/// button 0 is least significant
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PushButtons {
+    count: usize,
+    #[serde(skip)]
+    pressed: u32,
+    /// Value last written to port, None when it has to be written again
+    #[serde(skip)]
+    written: Option<i32>,
+}
+
+impl Default for PushButtons {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl PushButtons {
+    pub const PORTS: [&'static str; 1] = ["out"];
+    const OUT: usize = 0;
+
+    pub fn new(count: usize) -> Self {
+        Self {
+            count: count.clamp(1, 16),
+            pressed: 0,
+            written: None,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Changes number of buttons, all of them are released
+    pub fn set_count(&mut self, count: usize) {
+        self.count = count.clamp(1, 16);
+        self.pressed = 0;
+    }
+
+    pub fn is_pressed(&self, button: usize) -> bool {
+        button < self.count && (self.pressed >> button) & 1 == 1
+    }
+
+    pub fn set_pressed(&mut self, button: usize, pressed: bool) {
+        if button >= self.count {
+            return;
+        }
+        if pressed {
+            self.pressed |= 1 << button;
+        } else {
+            self.pressed &= !(1 << button);
+        }
+    }
+
+    /// Writes buttons held down when they changed or port was connected again,
+    /// returns values to be written to ports
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        if values.get(PushButtons::OUT).copied().flatten().is_none() {
+            self.written = None;
+            return Vec::new();
+        }
+        let value = self.pressed as i32;
+        if self.written == Some(value) {
+            return Vec::new();
+        }
+        self.written = Some(value);
+        vec![(PushButtons::OUT, value)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn held_buttons_are_written_on_change() {
+        let mut buttons = PushButtons::new(4);
+        assert_eq!(buttons.update(&[Some(7)]), vec![(PushButtons::OUT, 0)]);
+        buttons.set_pressed(1, true);
+        buttons.set_pressed(3, true);
+        assert_eq!(buttons.update(&[Some(0)]), vec![(PushButtons::OUT, 10)]);
+        assert_eq!(buttons.update(&[Some(10)]), vec![]);
+        buttons.set_pressed(1, false);
+        assert_eq!(buttons.update(&[Some(10)]), vec![(PushButtons::OUT, 8)]);
+
+        buttons.set_pressed(4, true);
+        assert!(!buttons.is_pressed(4));
+        buttons.set_count(2);
+        assert!(!buttons.is_pressed(3));
+    }
+}
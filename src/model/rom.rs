@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use super::value_format::ValueFormat;
+
+/// Largest number of values of rom
+pub const MAX_ROM_SIZE: usize = 65536;
+
+/// Read only memory with contents loaded from file, ports are laid out like ports of ram.
+/// In read mode value at index is put on data port, in write mode rom does nothing,
+/// so program can't change its contents
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Rom {
+    pub format: ValueFormat,
+    data: Vec<i32>,
+}
+
+impl Rom {
+    pub const PORTS: [&'static str; 3] = ["index", "data", "mode"];
+    const INDEX: usize = 0;
+    const DATA: usize = 1;
+    const MODE: usize = 2;
+
+    pub fn new(data: Vec<i32>, format: ValueFormat) -> Self {
+        let mut rom = Self { format, data };
+        rom.data.truncate(MAX_ROM_SIZE);
+        rom
+    }
+
+    pub fn data(&self) -> &[i32] {
+        &self.data
+    }
+
+    pub fn set_data(&mut self, mut data: Vec<i32>) {
+        data.truncate(MAX_ROM_SIZE);
+        self.data = data;
+    }
+
+    /// Value at index, 0 outside of rom
+    pub fn value(&self, index: i32) -> i32 {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.data.get(index))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Puts value at index on data port unless mode port holds 0,
+    /// returns values to be written to ports
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        let port = |port: usize| values.get(port).copied().flatten();
+        if port(Rom::MODE) == Some(0) {
+            return Vec::new();
+        }
+        let Some(data) = port(Rom::DATA) else {
+            return Vec::new();
+        };
+        let value = self.value(port(Rom::INDEX).unwrap_or(0));
+        if data == value {
+            return Vec::new();
+        }
+        vec![(Rom::DATA, value)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_at_index_is_put_on_data_port() {
+        let mut rom = Rom::new(vec![10, 20, 97, -4], Default::default());
+        assert_eq!(rom.update(&[Some(2), Some(0), None]), vec![(Rom::DATA, 97)]);
+        assert_eq!(rom.update(&[Some(2), Some(97), None]), vec![]);
+        // outside of rom
+        assert_eq!(
+            rom.update(&[Some(-1), Some(97), None]),
+            vec![(Rom::DATA, 0)]
+        );
+        assert_eq!(rom.update(&[Some(1), None, None]), vec![]);
+    }
+
+    #[test]
+    fn writes_are_ignored() {
+        let mut rom = Rom::new(vec![10, 20], Default::default());
+        // write is overwritten with value from rom
+        assert_eq!(
+            rom.update(&[Some(1), Some(99), Some(1)]),
+            vec![(Rom::DATA, 20)]
+        );
+        assert_eq!(rom.update(&[Some(1), Some(99), Some(0)]), vec![]);
+        assert_eq!(rom.data(), [10, 20]);
+    }
+
+    #[test]
+    fn data_is_cut_to_max_size() {
+        let mut rom = Rom::new(vec![1; MAX_ROM_SIZE + 1], Default::default());
+        assert_eq!(rom.data().len(), MAX_ROM_SIZE);
+        rom.set_data(vec![2; MAX_ROM_SIZE + 5]);
+        assert_eq!(rom.data().len(), MAX_ROM_SIZE);
+        assert_eq!(rom.value(MAX_ROM_SIZE as i32), 0);
+    }
+}
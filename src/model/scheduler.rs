@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+/// Most ticks executed in one frame, keeps ui responsive when tick interval is very short
+pub const MAX_TICKS_PER_FRAME: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum SchedulerStatus {
+    #[default]
+    Stopped,
+    Running,
+    Paused,
+}
+
+/// Lockstep scheduler, when enabled vms don't run on their own threads,
+/// instead every vm executes one instruction per global tick in fixed order
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scheduler {
+    /// Is lockstep mode turned on
+    enabled: bool,
+    /// Time between ticks in ms
+    tick_ms: u64,
+    #[serde(skip)]
+    status: SchedulerStatus,
+    /// Number of ticks executed since start
+    #[serde(skip)]
+    tick: u64,
+    /// Time of last tick in seconds, from egui input
+    #[serde(skip)]
+    last_tick_time: Option<f64>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tick_ms: 100,
+            status: SchedulerStatus::Stopped,
+            tick: 0,
+            last_tick_time: None,
+        }
+    }
+}
+
+impl Scheduler {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.status = SchedulerStatus::Stopped;
+        self.last_tick_time = None;
+    }
+
+    pub fn get_status(&self) -> SchedulerStatus {
+        self.status
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == SchedulerStatus::Running
+    }
+
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn get_tick_ms(&self) -> u64 {
+        self.tick_ms
+    }
+
+    pub fn tick_ms_mut(&mut self) -> &mut u64 {
+        &mut self.tick_ms
+    }
+
+    pub fn start(&mut self) {
+        self.status = SchedulerStatus::Running;
+        self.last_tick_time = None;
+    }
+
+    pub fn pause(&mut self) {
+        if self.status == SchedulerStatus::Running {
+            self.status = SchedulerStatus::Paused;
+        }
+    }
+
+    /// Stops scheduler and resets tick counter
+    pub fn stop(&mut self) {
+        self.status = SchedulerStatus::Stopped;
+        self.tick = 0;
+        self.last_tick_time = None;
+    }
+
+    /// Counts manually executed tick
+    pub fn advance(&mut self) {
+        self.tick += 1;
+        if self.status == SchedulerStatus::Stopped {
+            self.status = SchedulerStatus::Paused;
+        }
+    }
+
+    /// Returns how many ticks should be executed now, `now` is time in seconds
+    pub fn ticks_due(&mut self, now: f64) -> u64 {
+        if !self.enabled || self.status != SchedulerStatus::Running {
+            return 0;
+        }
+        ticks_since(&mut self.last_tick_time, now, self.tick_ms)
+    }
+}
+
+/// Returns how many ticks of `tick_ms` passed since `last_tick_time` and moves it forward,
+/// first call returns one tick, at most `MAX_TICKS_PER_FRAME` are returned
+pub fn ticks_since(last_tick_time: &mut Option<f64>, now: f64, tick_ms: u64) -> u64 {
+    let Some(last) = *last_tick_time else {
+        *last_tick_time = Some(now);
+        return 1;
+    };
+    let interval = tick_ms.max(1) as f64 / 1000.0;
+    let due = ((now - last) / interval).floor() as u64;
+    if due == 0 {
+        return 0;
+    }
+    if due > MAX_TICKS_PER_FRAME {
+        *last_tick_time = Some(now);
+        return MAX_TICKS_PER_FRAME;
+    }
+    *last_tick_time = Some(last + due as f64 * interval);
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running_scheduler(tick_ms: u64) -> Scheduler {
+        let mut scheduler = Scheduler::default();
+        scheduler.set_enabled(true);
+        *scheduler.tick_ms_mut() = tick_ms;
+        scheduler.start();
+        scheduler
+    }
+
+    #[test]
+    fn ticks_due_are_capped_per_frame() {
+        let mut scheduler = running_scheduler(1);
+        assert_eq!(scheduler.ticks_due(0.0), 1);
+        assert_eq!(scheduler.ticks_due(10.0), MAX_TICKS_PER_FRAME);
+        // ticks over the cap are dropped, not carried to next frame
+        assert_eq!(scheduler.ticks_due(10.0), 0);
+        assert_eq!(scheduler.ticks_due(10.5), 500);
+    }
+
+    #[test]
+    fn ticks_are_counted_across_frames() {
+        let mut scheduler = running_scheduler(250);
+        assert_eq!(scheduler.ticks_due(1.0), 1);
+        assert_eq!(scheduler.ticks_due(1.2), 0);
+        assert_eq!(scheduler.ticks_due(1.5), 2);
+        // remainder of frame counts towards next tick
+        assert_eq!(scheduler.ticks_due(1.8), 1);
+        assert_eq!(scheduler.ticks_due(2.0), 1);
+    }
+
+    #[test]
+    fn only_running_scheduler_has_ticks_due() {
+        let mut scheduler = running_scheduler(250);
+        scheduler.pause();
+        assert_eq!(scheduler.ticks_due(1.0), 0);
+        scheduler.start();
+        scheduler.set_enabled(false);
+        assert_eq!(scheduler.ticks_due(1.0), 0);
+    }
+
+    #[test]
+    fn manual_ticks_are_counted_until_stop() {
+        let mut scheduler = running_scheduler(250);
+        scheduler.stop();
+        scheduler.advance();
+        scheduler.advance();
+        assert_eq!(scheduler.get_tick(), 2);
+        assert_eq!(scheduler.get_status(), SchedulerStatus::Paused);
+        scheduler.stop();
+        assert_eq!(scheduler.get_tick(), 0);
+        assert_eq!(scheduler.get_status(), SchedulerStatus::Stopped);
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+/// One to four seven segment digits showing value on its port in chosen base,
+/// negative values are shown as unsigned
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SevenSegment {
+    digits: usize,
+    base: u32,
+    #[serde(skip)]
+    value: i32,
+}
+
+impl Default for SevenSegment {
+    fn default() -> Self {
+        Self::new(4, 10)
+    }
+}
+
+impl SevenSegment {
+    pub const PORTS: [&'static str; 1] = ["in"];
+    const IN: usize = 0;
+
+    pub fn new(digits: usize, base: u32) -> Self {
+        Self {
+            digits: digits.clamp(1, 4),
+            base: base.clamp(2, 16),
+            value: 0,
+        }
+    }
+
+    pub fn digit_count(&self) -> usize {
+        self.digits
+    }
+
+    pub fn set_digit_count(&mut self, digits: usize) {
+        self.digits = digits.clamp(1, 4);
+    }
+
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+
+    pub fn set_base(&mut self, base: u32) {
+        self.base = base.clamp(2, 16);
+    }
+
+    /// Value last read from port
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    /// Shown digits, most significant first, higher digits of value are cut off
+    pub fn digits(&self) -> Vec<u32> {
+        let mut value = self.value as u32;
+        let mut digits = vec![0; self.digits];
+        for digit in digits.iter_mut().rev() {
+            *digit = value % self.base;
+            value /= self.base;
+        }
+        digits
+    }
+
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        self.value = values.get(SevenSegment::IN).copied().flatten().unwrap_or(0);
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits_show_port_value_in_base() {
+        let mut segments = SevenSegment::new(3, 16);
+        // value stays on port, it isn't taken by display
+        assert_eq!(segments.update(&[Some(0xAB)]), vec![]);
+        assert_eq!(segments.digits(), vec![0, 10, 11]);
+
+        // higher digits are cut off
+        segments.set_base(10);
+        segments.update(&[Some(1234)]);
+        assert_eq!(segments.digits(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn negative_values_are_shown_as_unsigned() {
+        let mut segments = SevenSegment::new(4, 16);
+        segments.update(&[Some(-1)]);
+        assert_eq!(segments.digits(), vec![15, 15, 15, 15]);
+    }
+}
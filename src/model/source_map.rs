@@ -0,0 +1,55 @@
+/// Maps index of instruction in assembled program to line of source code (counted from 0)
+///
+/// Assembler skips empty lines, comments and labels, so instruction at pc `n`
+/// comes from `n`-th line that contains an instruction
+pub fn instruction_lines(code: &str) -> Vec<usize> {
+    code.lines()
+        .enumerate()
+        .filter(|(_, line)| contains_instruction(line))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn contains_instruction(line: &str) -> bool {
+    let line = match line.split_once('#') {
+        Some((code, _comment)) => code,
+        None => line,
+    };
+    let line = line.trim();
+    if line.is_empty() {
+        return false;
+    }
+    // label, possibly followed by instruction in the same line
+    match line.split_once(':') {
+        Some((label, rest)) if !label.contains(char::is_whitespace) => !rest.trim().is_empty(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_empty_lines_and_comments() {
+        let code = "MOV 1 acc\n\n# comment\n   \nADD 2 # add two\n  #indented comment\nJMP 0";
+        assert_eq!(instruction_lines(code), vec![0, 4, 6]);
+    }
+
+    #[test]
+    fn skips_labels_without_instruction() {
+        let code = "start:\nMOV 1 acc\nloop: ADD 1\n  end:   # done\nJMP loop";
+        assert_eq!(instruction_lines(code), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn colon_after_whitespace_is_not_label() {
+        assert_eq!(instruction_lines("MOV 'a' p0\nMOV ':' p0"), vec![0, 1]);
+    }
+
+    #[test]
+    fn empty_code_has_no_instructions() {
+        assert!(instruction_lines("").is_empty());
+        assert!(instruction_lines("# only comment\n\nlabel:").is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+/// Row of toggle switches, each one is a bit of value written to its port,
+/// switch 0 is least significant
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Switches {
+    count: usize,
+    state: u32,
+    /// Value last written to port, None when it has to be written again
+    #[serde(skip)]
+    written: Option<i32>,
+}
+
+impl Default for Switches {
+    fn default() -> Self {
+        Self::new(8, 0)
+    }
+}
+
+impl Switches {
+    pub const PORTS: [&'static str; 1] = ["out"];
+    const OUT: usize = 0;
+
+    pub fn new(count: usize, state: u32) -> Self {
+        let count = count.clamp(1, 32);
+        Self {
+            count,
+            state: state & Switches::mask(count),
+            written: None,
+        }
+    }
+
+    /// Bits of switches that exist
+    fn mask(count: usize) -> u32 {
+        u32::MAX >> (32 - count)
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Changes number of switches, switches that were removed are turned off
+    pub fn set_count(&mut self, count: usize) {
+        self.count = count.clamp(1, 32);
+        self.state &= Switches::mask(self.count);
+    }
+
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    pub fn is_on(&self, switch: usize) -> bool {
+        switch < self.count && (self.state >> switch) & 1 == 1
+    }
+
+    pub fn toggle(&mut self, switch: usize) {
+        if switch < self.count {
+            self.state ^= 1 << switch;
+        }
+    }
+
+    /// Writes state when it changed or port was connected again,
+    /// returns values to be written to ports
+    pub fn update(&mut self, values: &[Option<i32>]) -> Vec<(usize, i32)> {
+        if values.get(Switches::OUT).copied().flatten().is_none() {
+            self.written = None;
+            return Vec::new();
+        }
+        let value = self.state as i32;
+        if self.written == Some(value) {
+            return Vec::new();
+        }
+        self.written = Some(value);
+        vec![(Switches::OUT, value)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_is_written_on_change() {
+        let mut switches = Switches::new(4, 0b0101);
+        assert_eq!(switches.update(&[Some(0)]), vec![(Switches::OUT, 5)]);
+        assert_eq!(switches.update(&[Some(5)]), vec![]);
+        switches.toggle(3);
+        assert!(switches.is_on(3));
+        assert_eq!(switches.update(&[Some(5)]), vec![(Switches::OUT, 13)]);
+    }
+
+    #[test]
+    fn state_is_written_again_after_reconnect() {
+        let mut switches = Switches::new(2, 0b11);
+        switches.update(&[Some(0)]);
+        assert_eq!(switches.update(&[None]), vec![]);
+        assert_eq!(switches.update(&[Some(0)]), vec![(Switches::OUT, 3)]);
+    }
+
+    #[test]
+    fn removed_switches_are_turned_off() {
+        let mut switches = Switches::new(4, 0b1111_1111);
+        assert_eq!(switches.state(), 0b1111);
+        switches.set_count(2);
+        assert_eq!(switches.state(), 0b11);
+        switches.toggle(5);
+        assert_eq!(switches.state(), 0b11);
+        assert_eq!(Switches::new(32, u32::MAX).state(), u32::MAX);
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// How values of ram cells, consoles and roms are shown,
+/// saved in lowercase with aliases for files saved before
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueFormat {
+    #[default]
+    #[serde(alias = "Dec")]
+    Dec,
+    #[serde(alias = "Bin")]
+    Bin,
+    #[serde(alias = "Hex")]
+    Hex,
+    #[serde(alias = "Unicode")]
+    Unicode,
+}
+
+impl ValueFormat {
+    pub fn format_value(&self, value: i32) -> String {
+        match self {
+            ValueFormat::Dec => format!("{}", value),
+            ValueFormat::Hex => format!("0x{:X}", value),
+            ValueFormat::Bin => format!("0b{:b}", value),
+            ValueFormat::Unicode => {
+                if value < 0 {
+                    t!("common.invalid_char")
+                } else {
+                    if let Some(char) = char::from_u32(value as u32) {
+                        return format!("\'{}\'", char);
+                    }
+                    t!("common.invalid_char")
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_is_saved_in_lowercase() {
+        let formats = [
+            (ValueFormat::Dec, "\"dec\""),
+            (ValueFormat::Bin, "\"bin\""),
+            (ValueFormat::Hex, "\"hex\""),
+            (ValueFormat::Unicode, "\"unicode\""),
+        ];
+        for (format, json) in formats {
+            assert_eq!(serde_json::to_string(&format).unwrap(), json);
+            assert_eq!(serde_json::from_str::<ValueFormat>(json).unwrap(), format);
+        }
+        assert_eq!(
+            serde_json::from_str::<ValueFormat>("\"Hex\"").unwrap(),
+            ValueFormat::Hex
+        );
+    }
+}
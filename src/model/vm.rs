@@ -0,0 +1,937 @@
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+use simple_virtual_assembler::assembler::assembler::Assembler;
+use simple_virtual_assembler::assembler::parsing_err::ParsingError;
+use simple_virtual_assembler::components::connection::Connection;
+use simple_virtual_assembler::components::port::Port;
+use simple_virtual_assembler::language::Language;
+use simple_virtual_assembler::vm::flag::Flag;
+use simple_virtual_assembler::vm::instruction::Instruction;
+use simple_virtual_assembler::vm::virtual_machine::{VirtualMachine, VmStatus};
+
+use crate::storage::toasts::ToastsManager;
+
+use super::breakpoint::Breakpoint;
+use super::history::{History, RamWrite, Registers, Snapshot};
+use super::port_driver::PortDriver;
+use super::scheduler::{ticks_since, MAX_TICKS_PER_FRAME};
+use super::source_map::instruction_lines;
+
+/// State of vm as returned by `get_state_for_display`: acc, pc, flag, r0-r3, p0-p5, status, delay
+pub type VmState = (i32, usize, Flag, [i32; 4], [i32; 6], VmStatus, u32);
+
+/// Extension added to saved source files without one
+pub const SOURCE_EXTENSION: &str = "sva";
+
+/// Number of values on stack of new vm
+pub const DEFAULT_STACK_SIZE: usize = 32;
+pub const MAX_STACK_SIZE: usize = 1024;
+
+fn default_stack_size() -> usize {
+    DEFAULT_STACK_SIZE
+}
+
+/// Settings chosen when adding vm, can be changed later in its settings window
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmSettings {
+    /// Empty name keeps current or default name
+    pub name: String,
+    pub stack: bool,
+    pub stack_size: usize,
+    pub delay_ms: u64,
+}
+
+impl Default for VmSettings {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            stack: true,
+            stack_size: DEFAULT_STACK_SIZE,
+            delay_ms: 1000,
+        }
+    }
+}
+
+/// Returns value of register or port by its name, used in breakpoint conditions
+pub fn register_value(state: &VmState, name: &str) -> Option<i32> {
+    let (acc, pc, _flag, r, p, _status, _delay) = state;
+    match name {
+        "acc" => Some(*acc),
+        "pc" => (*pc).try_into().ok(),
+        "r0" => Some(r[0]),
+        "r1" => Some(r[1]),
+        "r2" => Some(r[2]),
+        "r3" => Some(r[3]),
+        "p0" => Some(p[0]),
+        "p1" => Some(p[1]),
+        "p2" => Some(p[2]),
+        "p3" => Some(p[3]),
+        "p4" => Some(p[4]),
+        "p5" => Some(p[5]),
+        _ => None,
+    }
+}
+
+/// Vm of circuit with its code, breakpoints and source file, shown by `SVAWindow`
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Vm {
+    /// Id
+    id: usize,
+    /// Tile
+    name: String,
+
+    /// Simple virtual machine
+    pub vm: Arc<Mutex<VirtualMachine>>,
+    /// Assembler for simple virtual machine
+    assembler: Assembler,
+    /// Code before assembly
+    code: String,
+    /// ( Currently useless ) Program to be executed by vm
+    program: Vec<Instruction>,
+    /// Error assembling code to program
+    parsing_error: Option<ParsingError>,
+    /// ( Currently useless ) Parsing error message
+    parsing_error_msg: String,
+    /// Language
+    language: Language,
+
+    ///Delay ms
+    delay_ms: u64,
+
+    conn_ids: [Option<usize>; 4],
+
+    stack_present: bool,
+
+    /// Number of values stack can hold
+    #[serde(default = "default_stack_size")]
+    stack_size: usize,
+
+    #[serde(skip)]
+    vm_join_handle: Option<JoinHandle<()>>,
+
+    /// Vm is driven by global scheduler instead of its own thread
+    #[serde(skip)]
+    lockstep: bool,
+
+    #[serde(default)]
+    breakpoints: Vec<Breakpoint>,
+
+    /// Vm with breakpoints or watched by watchpoints is run by document one instruction
+    /// at a time instead of on its own thread, so they are checked at every instruction
+    #[serde(skip)]
+    checked_run: bool,
+
+    /// Document has watchpoints, so vm has to run checked
+    #[serde(skip)]
+    watched: bool,
+
+    /// Time of last instruction executed by checked run, in seconds
+    #[serde(skip)]
+    last_step_time: Option<f64>,
+
+    /// Line of code for each instruction of program
+    #[serde(skip)]
+    instruction_lines: Vec<usize>,
+
+    /// Pc at which vm was last stopped by breakpoint, so resuming doesn't stop it again
+    #[serde(skip)]
+    last_break_pc: Option<usize>,
+
+    /// Instructions executed by stepping, used to step back
+    #[serde(skip)]
+    history: History,
+
+    /// Source file code was opened from or saved to
+    #[serde(default)]
+    source_path: Option<PathBuf>,
+
+    /// Code is reloaded when linked source file changes
+    #[serde(default)]
+    reload_source: bool,
+
+    /// Modification time of linked source file when it was last read or written
+    #[serde(skip)]
+    source_modified: Option<SystemTime>,
+}
+
+/// Modification time of file, None when it can't be read
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: format!("{}", 0).to_owned(),
+            vm: Arc::new(Mutex::new(VirtualMachine::new())),
+            assembler: Assembler::new(),
+            code: String::new(),
+            program: Vec::new(),
+
+            parsing_error_msg: String::new(),
+            parsing_error: None,
+            language: Language::En,
+
+            delay_ms: 1000,
+
+            conn_ids: [None; 4],
+            stack_present: false,
+            stack_size: DEFAULT_STACK_SIZE,
+            vm_join_handle: None,
+            lockstep: false,
+            breakpoints: Vec::new(),
+            checked_run: false,
+            watched: false,
+            last_step_time: None,
+            instruction_lines: Vec::new(),
+            last_break_pc: None,
+            history: History::default(),
+            source_path: None,
+            reload_source: false,
+            source_modified: None,
+        }
+    }
+}
+
+impl Vm {
+    pub fn new(id: usize, stack_present: bool) -> Vm {
+        let mut s = Vm {
+            id,
+            name: format!("vm:{}", id),
+            stack_present,
+            ..Default::default()
+        };
+        s.build_vm();
+        s
+    }
+
+    /// Creates vm and assembler matching stack settings and loads code into vm
+    fn build_vm(&mut self) {
+        let mut vm = if self.stack_present {
+            VirtualMachine::new().with_stack(self.stack_size)
+        } else {
+            VirtualMachine::new()
+        };
+        vm.set_delay(self.delay_ms.try_into().unwrap());
+        self.vm = Arc::new(Mutex::new(vm));
+        self.assembler = if self.stack_present {
+            Assembler::new().with_stack()
+        } else {
+            Assembler::new()
+        };
+        self.assembler.set_language(self.language.clone());
+        if !self.code.is_empty() {
+            self.try_assemble_and_load();
+        }
+    }
+
+    pub fn settings(&self) -> VmSettings {
+        VmSettings {
+            name: self.name.clone(),
+            stack: self.stack_present,
+            stack_size: self.stack_size,
+            delay_ms: self.delay_ms,
+        }
+    }
+
+    /// Applies settings, when stack changed vm is halted and rebuilt, which leaves
+    /// its ports disconnected. Returns true when vm was rebuilt
+    pub fn apply_settings(&mut self, settings: &VmSettings) -> bool {
+        if !settings.name.trim().is_empty() {
+            self.name = settings.name.clone();
+        }
+        self.set_delay_ms(settings.delay_ms);
+        let stack_size = settings.stack_size.clamp(1, MAX_STACK_SIZE);
+        if settings.stack == self.stack_present && stack_size == self.stack_size {
+            return false;
+        }
+        self.stack_present = settings.stack;
+        self.stack_size = stack_size;
+        self.halt_vm();
+        // thread finishes on its own after halt
+        self.vm_join_handle = None;
+        self.build_vm();
+        true
+    }
+
+    pub fn handle_poison_error(&mut self) {
+        ToastsManager::show_info(format!("{} {} ", t!("error.restarting_vm"), self.id), 10);
+        self.build_vm();
+    }
+
+    /// Runs closure on locked vm, vm poisoned by panic on its thread is rebuilt first,
+    /// which leaves its ports disconnected
+    pub fn with_vm<T>(&mut self, f: impl FnOnce(&mut VirtualMachine) -> T) -> T {
+        if self.vm.is_poisoned() {
+            self.handle_poison_error();
+        }
+        let mut vm = self.vm.lock().unwrap_or_else(|err| err.into_inner());
+        f(&mut vm)
+    }
+
+    pub fn reset_vm(&mut self) {
+        self.history.clear();
+        let mut poison_err = false;
+        {
+            let vm_lock = self.vm.lock();
+            match vm_lock {
+                Ok(mut vm) => vm.clear_registers(),
+                Err(_err) => poison_err = true,
+            }
+        }
+        if poison_err {
+            self.handle_poison_error();
+        }
+    }
+
+    pub fn join_vm(&mut self) {
+        if let Some(join_handle) = self.vm_join_handle.take() {
+            join_handle.join().unwrap();
+        }
+    }
+
+    /// Reads status directly from vm, vm in checked run is running
+    pub fn read_status(&self) -> VmStatus {
+        if self.checked_run {
+            return VmStatus::Running;
+        }
+        match self.vm.lock() {
+            Ok(vm) => vm.get_state_for_display().5,
+            Err(_err) => VmStatus::Finished,
+        }
+    }
+
+    pub fn halt_vm(&mut self) {
+        self.checked_run = false;
+        VirtualMachine::halt(self.vm.clone());
+    }
+
+    /// Starts vm thread, or resumes it if vm was stopped, does nothing if code has errors.
+    /// Vm with breakpoints or watchpoints is started in checked run instead
+    pub fn start_vm(&mut self) {
+        if self.parsing_error.is_some() {
+            return;
+        }
+        if self.needs_checked_run() {
+            self.start_checked_run();
+            return;
+        }
+        // instructions executed on vm thread aren't recorded
+        self.history.clear();
+        let mut poison_err = false;
+        let mut vm_status = VmStatus::Initial;
+        {
+            match self.vm.lock() {
+                Ok(mut vm) => {
+                    vm.set_delay(self.delay_ms.try_into().unwrap());
+                    vm_status = vm.get_state_for_display().5;
+                }
+                Err(_) => poison_err = true,
+            }
+        }
+        if poison_err {
+            self.handle_poison_error();
+        }
+        match vm_status {
+            VmStatus::Initial => {
+                self.vm_join_handle = Some(VirtualMachine::start(self.vm.clone()));
+            }
+            VmStatus::Running => {}
+            VmStatus::Stopped => {
+                if self.vm_join_handle.is_some() {
+                    VirtualMachine::resume(self.vm.clone());
+                } else {
+                    self.vm_join_handle = Some(VirtualMachine::start(self.vm.clone()));
+                }
+            }
+            VmStatus::Finished => {
+                VirtualMachine::start(self.vm.clone());
+                //self.reset_vm();
+                //VirtualMachine::resume(self.vm.clone());
+            }
+        }
+    }
+
+    pub fn stop_vm(&mut self) {
+        self.checked_run = false;
+        VirtualMachine::stop(self.vm.clone());
+    }
+
+    pub fn resume_vm(&mut self) {
+        if self.needs_checked_run() {
+            self.start_checked_run();
+        } else if self.vm_join_handle.is_some() {
+            VirtualMachine::resume(self.vm.clone());
+        } else {
+            self.vm_join_handle = Some(VirtualMachine::start(self.vm.clone()));
+        }
+    }
+
+    /// Vm is finished when it was halted or its pc went past the end of the program
+    pub fn is_finished(&self) -> bool {
+        match self.vm.lock() {
+            Ok(vm) => {
+                vm.get_state_for_display().5 == VmStatus::Finished
+                    || vm.get_pc() >= vm.get_program().len()
+            }
+            Err(_err) => true,
+        }
+    }
+
+    /// Registers, ports and stack of vm as json, used by headless runner
+    pub fn state_to_json(&self) -> serde_json::Value {
+        let vm = match self.vm.lock() {
+            Ok(vm) => vm,
+            Err(err) => return serde_json::json!({ "id": self.id, "error": err.to_string() }),
+        };
+        let (acc, pc, flag, r, p, status, _delay) = vm.get_state_for_display();
+        let stack = if self.stack_present {
+            vm.get_stack()
+        } else {
+            Vec::new()
+        };
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "status": status,
+            "acc": acc,
+            "pc": pc,
+            "flag": flag.to_string(),
+            "r": r,
+            "p": p,
+            "stack": stack,
+        })
+    }
+
+    /// Registers, ports, status and delay of vm
+    pub fn state(&mut self) -> VmState {
+        self.with_vm(|vm| vm.get_state_for_display())
+    }
+
+    /// Values on stack, empty for vm without stack
+    pub fn stack_values(&mut self) -> Vec<i32> {
+        if !self.stack_present {
+            return Vec::new();
+        }
+        self.with_vm(|vm| vm.get_stack())
+    }
+
+    pub fn ports(&mut self) -> [Port; 6] {
+        self.with_vm(|vm| vm.get_ports())
+    }
+
+    /// Instruction that will be executed next
+    pub fn next_instruction(&mut self) -> Option<String> {
+        self.with_vm(|vm| vm.get_next_instruction())
+            .map(|instruction| instruction.to_string())
+    }
+
+    /// Switches vm between running on its own thread and being driven by global scheduler
+    pub fn set_lockstep(&mut self, lockstep: bool) {
+        if lockstep && (self.vm_join_handle.is_some() || self.checked_run) {
+            self.stop_vm();
+        }
+        self.lockstep = lockstep;
+    }
+
+    pub fn is_lockstep(&self) -> bool {
+        self.lockstep
+    }
+
+    /// Stops vm thread and lets document step vm, restarting it if it finished
+    fn start_checked_run(&mut self) {
+        if self.read_status() == VmStatus::Running {
+            VirtualMachine::stop(self.vm.clone());
+        }
+        let mut poison_err = false;
+        {
+            match self.vm.lock() {
+                Ok(mut vm) => {
+                    if vm.get_pc() >= vm.get_program().len() {
+                        vm.clear_registers();
+                    }
+                }
+                Err(_err) => poison_err = true,
+            }
+        }
+        if poison_err {
+            self.handle_poison_error();
+        }
+        self.checked_run = true;
+        self.last_step_time = None;
+    }
+
+    fn needs_checked_run(&self) -> bool {
+        (self.has_breakpoints() || self.watched) && !self.lockstep
+    }
+
+    /// Moves running vm between its own thread and checked run when breakpoints or
+    /// watchpoints were added or removed
+    fn update_run_mode(&mut self) {
+        if self.lockstep {
+            return;
+        }
+        if self.needs_checked_run() && !self.checked_run && self.read_status() == VmStatus::Running
+        {
+            self.start_checked_run();
+        } else if !self.needs_checked_run() && self.checked_run {
+            self.checked_run = false;
+            self.resume_vm();
+        }
+    }
+
+    /// Tells vm whether document has watchpoints, which have to be checked after
+    /// every instruction
+    pub fn set_watched(&mut self, watched: bool) {
+        if self.watched != watched {
+            self.watched = watched;
+            self.update_run_mode();
+        }
+    }
+
+    pub fn is_checked_run(&self) -> bool {
+        self.checked_run
+    }
+
+    /// Ends checked run of vm that finished its program
+    pub fn finish_checked_run(&mut self) {
+        self.checked_run = false;
+    }
+
+    /// Number of instructions checked run should execute now, one per vm delay,
+    /// `now` is time in seconds
+    pub fn steps_due(&mut self, now: f64) -> u64 {
+        if !self.checked_run {
+            return 0;
+        }
+        if self.delay_ms == 0 {
+            self.last_step_time = Some(now);
+            return MAX_TICKS_PER_FRAME;
+        }
+        ticks_since(&mut self.last_step_time, now, self.delay_ms)
+    }
+
+    /// Line of code from which instruction at given pc comes
+    pub fn source_line(&mut self, pc: usize) -> Option<usize> {
+        if self.instruction_lines.is_empty() && !self.code.is_empty() {
+            self.instruction_lines = instruction_lines(&self.code);
+        }
+        self.instruction_lines.get(pc).copied()
+    }
+
+    /// Line of code with instruction that will be executed next,
+    /// None before vm was started or stepped
+    pub fn current_line(&mut self, state: &VmState) -> Option<usize> {
+        let (_acc, pc, _flag, _r, _p, status, _delay) = *state;
+        let started = matches!(status, VmStatus::Running | VmStatus::Stopped) || pc > 0;
+        if !started || self.parsing_error.is_some() {
+            return None;
+        }
+        self.source_line(pc)
+    }
+
+    pub fn has_breakpoints(&self) -> bool {
+        !self.breakpoints.is_empty()
+    }
+
+    pub fn has_breakpoint(&self, line: usize) -> bool {
+        self.breakpoints.iter().any(|bp| bp.line == line)
+    }
+
+    pub fn toggle_breakpoint(&mut self, line: usize) {
+        if self.has_breakpoint(line) {
+            self.breakpoints.retain(|bp| bp.line != line);
+        } else {
+            self.breakpoints.push(Breakpoint::new(line));
+            self.breakpoints.sort_by_key(|bp| bp.line);
+        }
+        self.update_run_mode();
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) {
+        if index < self.breakpoints.len() {
+            self.breakpoints.remove(index);
+            self.update_run_mode();
+        }
+    }
+
+    /// Breakpoints ordered by line, used to edit their conditions
+    pub fn breakpoints_mut(&mut self) -> &mut [Breakpoint] {
+        &mut self.breakpoints
+    }
+
+    /// Checks if next instruction is on line with breakpoint whose condition is met,
+    /// returns the line
+    pub fn hit_breakpoint(&mut self) -> Option<usize> {
+        if self.breakpoints.is_empty() {
+            return None;
+        }
+        let state: VmState = match self.vm.lock() {
+            Ok(vm) => vm.get_state_for_display(),
+            Err(_err) => return None,
+        };
+        let pc = state.1;
+        if self.last_break_pc == Some(pc) {
+            return None;
+        }
+        self.last_break_pc = None;
+        let line = self.source_line(pc)?;
+        let hit = self
+            .breakpoints
+            .iter_mut()
+            .filter(|bp| bp.line == line)
+            .any(|bp| bp.should_stop(|name| register_value(&state, name)));
+        if !hit {
+            return None;
+        }
+        self.last_break_pc = Some(pc);
+        Some(line)
+    }
+
+    /// Current value of register or port watched by watchpoint
+    pub fn watched_register(&self, name: &str) -> Option<i32> {
+        match self.vm.lock() {
+            Ok(vm) => register_value(&vm.get_state_for_display(), name),
+            Err(_err) => None,
+        }
+    }
+
+    /// Number of values on stack, None for vm without stack
+    pub fn stack_depth(&self) -> Option<i32> {
+        if !self.stack_present {
+            return None;
+        }
+        match self.vm.lock() {
+            Ok(vm) => vm.get_stack().len().try_into().ok(),
+            Err(_err) => None,
+        }
+    }
+
+    pub fn has_stack(&self) -> bool {
+        self.stack_present
+    }
+
+    pub fn stack_size(&self) -> usize {
+        self.stack_size
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn get_code(&self) -> String {
+        self.code.clone()
+    }
+
+    /// Replaces code and assembles it
+    pub fn set_code(&mut self, code: String) {
+        self.code = code;
+        self.try_assemble_and_load();
+    }
+
+    pub fn parsing_error(&self) -> Option<&ParsingError> {
+        self.parsing_error.as_ref()
+    }
+
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source_path.as_deref()
+    }
+
+    pub fn reloads_source(&self) -> bool {
+        self.source_path.is_some() && self.reload_source
+    }
+
+    pub fn set_reload_source(&mut self, reload: bool) {
+        self.reload_source = reload;
+    }
+
+    /// Links vm to source file without reading it, used when loading project
+    pub fn link_source(&mut self, path: PathBuf, reload: bool) {
+        self.source_modified = modified_time(&path);
+        self.source_path = Some(path);
+        self.reload_source = reload;
+    }
+
+    pub fn unlink_source(&mut self) {
+        self.source_path = None;
+        self.reload_source = false;
+        self.source_modified = None;
+    }
+
+    /// Replaces code with content of file and links vm to it
+    pub fn open_source(&mut self, path: &Path) -> Result<(), String> {
+        let code = fs::read_to_string(path).map_err(|_err| t!("error.file.cant_open"))?;
+        self.set_code(code);
+        self.link_source(path.to_path_buf(), self.reload_source);
+        Ok(())
+    }
+
+    /// Writes code to file and links vm to it, `.sva` is added when path has no extension
+    pub fn save_source(&mut self, path: &Path) -> Result<(), String> {
+        let mut path = path.to_path_buf();
+        if path.extension().is_none() {
+            path.set_extension(SOURCE_EXTENSION);
+        }
+        fs::write(&path, &self.code).map_err(|_err| t!("error.file.cant_save"))?;
+        self.link_source(path, self.reload_source);
+        Ok(())
+    }
+
+    /// Reads linked source file again when it was modified since it was last read,
+    /// returns true when code was reloaded. Reloading is turned off when file can't be read
+    pub fn reload_source_if_changed(&mut self) -> Result<bool, String> {
+        if !self.reloads_source() {
+            return Ok(false);
+        }
+        let Some(path) = self.source_path.clone() else {
+            return Ok(false);
+        };
+        let modified = modified_time(&path);
+        if modified.is_some() && modified == self.source_modified {
+            return Ok(false);
+        }
+        match fs::read_to_string(&path) {
+            Ok(code) => {
+                self.source_modified = modified;
+                if code != self.code {
+                    self.set_code(code);
+                    return Ok(true);
+                }
+                Ok(false)
+            }
+            Err(_err) => {
+                self.reload_source = false;
+                Err(format!(
+                    "{}: {}",
+                    t!("sva_shell.error.cant_reload_source"),
+                    path.display()
+                ))
+            }
+        }
+    }
+
+    pub fn get_delay_ms(&self) -> u64 {
+        self.delay_ms
+    }
+
+    pub fn set_delay_ms(&mut self, delay_ms: u64) {
+        self.delay_ms = delay_ms;
+        let delay = self.delay_ms.try_into().unwrap_or(u32::MAX);
+        self.with_vm(|vm| vm.set_delay(delay));
+    }
+
+    pub fn get_breakpoints(&self) -> Vec<Breakpoint> {
+        self.breakpoints.clone()
+    }
+
+    pub fn set_breakpoints(&mut self, breakpoints: Vec<Breakpoint>) {
+        self.breakpoints = breakpoints;
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language.clone();
+        self.assembler.set_language(language);
+    }
+
+    /// Number of executed instructions that can be stepped back
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Tries Assembles code to instructions and loads them to vm
+    pub fn try_assemble_and_load(&mut self) {
+        let res = self.assembler.parse(&self.code);
+
+        match res {
+            Ok(program) => {
+                {
+                    match self.vm.lock() {
+                        Ok(mut vm) => {
+                            //CustomLogger::log("Loading program");
+                            vm.load_program(program);
+                        }
+                        Err(err) => ToastsManager::show_err(format!("{:?}", err), 10),
+                    }
+                }
+                self.instruction_lines = instruction_lines(&self.code);
+                self.history.clear();
+                self.parsing_error = None
+            }
+            Err(err) => self.parsing_error = Some(err),
+        }
+    }
+    /// Executes one instruction and records state from before it, so it can be undone
+    pub fn step_with_history(&mut self) {
+        if let Some(snapshot) = self.take_snapshot() {
+            self.history.push(snapshot);
+        }
+        self.step();
+    }
+
+    /// Adds ram cells changed by last recorded instruction
+    pub fn attach_ram_writes(&mut self, ram_writes: Vec<RamWrite>) {
+        if let Some(snapshot) = self.history.last_mut() {
+            snapshot.ram_writes = ram_writes;
+        }
+    }
+
+    fn take_snapshot(&self) -> Option<Snapshot> {
+        let vm = self.vm.lock().ok()?;
+        let (acc, pc, flag, r, p, status, _delay) = vm.get_state_for_display();
+        let connection_values = vm
+            .get_ports()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, port)| Some((port.get_conn_id()?, *p.get(index)?)))
+            .collect();
+        let stack = self.stack_present.then(|| vm.get_stack());
+        Some(Snapshot {
+            registers: Registers {
+                acc,
+                pc,
+                flag,
+                r,
+                status,
+                stack,
+            },
+            connection_values,
+            ram_writes: Vec::new(),
+        })
+    }
+
+    /// Copy of vm with registers from snapshot, program and ports stay as they are
+    fn restored_vm(&self, registers: &Registers) -> Result<VirtualMachine, String> {
+        let vm = self.vm.lock().map_err(|err| err.to_string())?;
+        let mut serialized = serde_json::to_value(&*vm).map_err(|err| err.to_string())?;
+        registers.write_to(&mut serialized)?;
+        serde_json::from_value(serialized).map_err(|err| err.to_string())
+    }
+
+    /// Restores vm and connections on its ports to state before last recorded instruction,
+    /// returns ram writes that have to be undone
+    pub fn step_back(&mut self, connections: &mut [Connection]) -> Option<Vec<RamWrite>> {
+        let snapshot = self.history.pop()?;
+        let mut restored = match self.restored_vm(&snapshot.registers) {
+            Ok(vm) => vm,
+            Err(err) => {
+                ToastsManager::show_err(format!("{} {}", t!("error.cant_step_back"), err), 10);
+                self.history.clear();
+                return None;
+            }
+        };
+        for (index, port) in restored.get_ports().into_iter().enumerate() {
+            let Some(conn_id) = port.get_conn_id() else {
+                continue;
+            };
+            if let Some(conn) = connections.iter_mut().find(|c| c.get_id() == Some(conn_id)) {
+                restored.connect(index, conn);
+            }
+        }
+        // reconnected ports share values with connections
+        for port in restored.get_ports() {
+            let Some(conn_id) = port.get_conn_id() else {
+                continue;
+            };
+            if let Some((_, value)) = snapshot
+                .connection_values
+                .iter()
+                .find(|(id, _)| *id == conn_id)
+            {
+                PortDriver::write(&port, *value);
+            }
+        }
+        let mut poison_err = false;
+        {
+            match self.vm.lock() {
+                Ok(mut vm) => *vm = restored,
+                Err(_err) => poison_err = true,
+            }
+        }
+        if poison_err {
+            self.handle_poison_error();
+            return None;
+        }
+        self.last_break_pc = None;
+        Some(snapshot.ram_writes)
+    }
+
+    /// Execute one instruction FIXME:
+    pub fn step(&mut self) {
+        let mut poison_err = false;
+        {
+            let vm_lock = self.vm.lock();
+
+            match vm_lock {
+                Ok(mut vm) => {
+                    if vm.get_pc() >= vm.get_program().len() {
+                        vm.clear_registers();
+                    }
+                }
+                Err(_err) => poison_err = true,
+            }
+        }
+        if poison_err {
+            self.handle_poison_error();
+        }
+        panic::catch_unwind(|| {
+            self.vm.lock().unwrap().execute();
+        })
+        .unwrap_or_else(|_err| {
+            self.handle_poison_error();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vm_source_is_opened_saved_and_reloaded() {
+        let dir = std::env::temp_dir().join(format!("sva_source_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut vm = Vm::new(0, false);
+
+        vm.set_code("MOV 7 acc".to_string());
+        vm.save_source(&dir.join("program")).unwrap();
+        let saved = dir.join("program.sva");
+        assert_eq!(vm.source_path(), Some(saved.as_path()));
+        assert_eq!(fs::read_to_string(&saved).unwrap(), "MOV 7 acc");
+
+        let opened = dir.join("other.sva");
+        fs::write(&opened, "MOV 3 r0").unwrap();
+        vm.open_source(&opened).unwrap();
+        assert_eq!(vm.get_code(), "MOV 3 r0");
+        assert!(!vm.reloads_source());
+        assert_eq!(vm.reload_source_if_changed(), Ok(false));
+
+        vm.link_source(opened.clone(), true);
+        assert_eq!(vm.reload_source_if_changed(), Ok(false));
+        fs::remove_file(&opened).unwrap();
+        assert!(vm.reload_source_if_changed().is_err());
+        assert!(!vm.reloads_source());
+        assert_eq!(vm.get_code(), "MOV 3 r0");
+        assert!(vm.open_source(&opened).is_err());
+
+        vm.unlink_source();
+        assert_eq!(vm.source_path(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
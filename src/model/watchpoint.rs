@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::condition::Condition;
+
+/// Value that is watched
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum WatchTarget {
+    /// Register or port of vm, by name e.g. `acc`, `r0`, `p3`
+    VmRegister {
+        vm_id: usize,
+        name: String,
+    },
+    /// Number of values on vm stack
+    VmStackDepth {
+        vm_id: usize,
+    },
+    RamCell {
+        ram_id: usize,
+        index: usize,
+    },
+}
+
+/// Stops execution when watched value changes, or when condition becomes true
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Watchpoint {
+    pub target: WatchTarget,
+    /// Condition on watched value called `value`, e.g. `value > 10`, empty means any change
+    condition: String,
+    pub enabled: bool,
+    #[serde(skip)]
+    last_value: Option<i32>,
+    /// Condition parsed once after it was set, not saved
+    #[serde(skip)]
+    parsed: Option<Result<Condition, String>>,
+}
+
+impl Watchpoint {
+    pub fn new(target: WatchTarget) -> Self {
+        Self {
+            target,
+            condition: String::new(),
+            enabled: true,
+            last_value: None,
+            parsed: None,
+        }
+    }
+
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
+
+    pub fn set_condition(&mut self, condition: String) {
+        self.condition = condition;
+        self.parsed = None;
+    }
+
+    /// Condition parsed on first use after it was set, None when it's empty
+    fn parsed(&mut self) -> Option<&Result<Condition, String>> {
+        if self.condition.trim().is_empty() {
+            return None;
+        }
+        Some(
+            self.parsed
+                .get_or_insert_with(|| Condition::parse(&self.condition)),
+        )
+    }
+
+    /// Returns error message if condition can't be parsed
+    pub fn condition_error(&mut self) -> Option<String> {
+        self.parsed()?.as_ref().err().cloned()
+    }
+
+    fn condition_holds(&mut self, value: i32) -> bool {
+        match self.parsed() {
+            Some(Ok(condition)) => condition
+                .evaluate(|name| (name == "value").then_some(value))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Updates watched value without checking it, used after value was changed by hand
+    pub fn sync(&mut self, value: Option<i32>) {
+        self.last_value = value;
+    }
+
+    /// Updates watched value, returns true if watchpoint was hit
+    pub fn check(&mut self, value: Option<i32>) -> bool {
+        let last_value = self.last_value;
+        self.last_value = value;
+        let Some(value) = value else {
+            return false;
+        };
+        if !self.enabled {
+            return false;
+        }
+        if self.condition.trim().is_empty() {
+            return last_value.is_some_and(|last| last != value);
+        }
+        // only hit when condition becomes true, not on every check while it holds
+        self.condition_holds(value) && !last_value.is_some_and(|last| self.condition_holds(last))
+    }
+}
+
+/// Shared list of watchpoints of all vms and rams of document
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WatchList {
+    watchpoints: Vec<Watchpoint>,
+}
+
+impl WatchList {
+    /// Adds watchpoint unless target is already watched
+    pub fn add(&mut self, target: WatchTarget) {
+        if !self.watchpoints.iter().any(|wp| wp.target == target) {
+            self.watchpoints.push(Watchpoint::new(target));
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.watchpoints.len() {
+            self.watchpoints.remove(index);
+        }
+    }
+
+    /// Removes watchpoints of vm or ram that no longer exists
+    pub fn retain(&mut self, keep: impl Fn(&WatchTarget) -> bool) {
+        self.watchpoints.retain(|wp| keep(&wp.target));
+    }
+
+    pub fn clear(&mut self) {
+        self.watchpoints.clear();
+    }
+
+    pub fn targets(&self) -> impl Iterator<Item = &WatchTarget> {
+        self.watchpoints.iter().map(|wp| &wp.target)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Watchpoint> {
+        self.watchpoints.iter_mut()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.watchpoints.is_empty()
+    }
+
+    /// Takes current values (in order of `targets`) without checking them, so changes
+    /// not made by executed instructions don't hit watchpoints
+    pub fn sync(&mut self, values: &[Option<i32>]) {
+        for (wp, value) in self.watchpoints.iter_mut().zip(values.iter()) {
+            wp.sync(*value);
+        }
+    }
+
+    /// Checks watchpoints against current values (in order of `targets`),
+    /// returns indexes of watchpoints that were hit
+    pub fn check(&mut self, values: &[Option<i32>]) -> Vec<usize> {
+        self.watchpoints
+            .iter_mut()
+            .zip(values.iter())
+            .enumerate()
+            .filter_map(|(index, (wp, value))| wp.check(*value).then_some(index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ram_cell() -> Watchpoint {
+        Watchpoint::new(WatchTarget::RamCell {
+            ram_id: 0,
+            index: 0,
+        })
+    }
+
+    #[test]
+    fn hits_on_change_but_not_after_sync() {
+        let mut wp = ram_cell();
+        assert!(!wp.check(Some(1)));
+        assert!(wp.check(Some(2)));
+        assert!(!wp.check(Some(2)));
+        wp.sync(Some(5));
+        assert!(!wp.check(Some(5)));
+    }
+
+    #[test]
+    fn hits_when_condition_becomes_true() {
+        let mut wp = ram_cell();
+        wp.set_condition("value > 10".to_owned());
+        assert!(!wp.check(Some(3)));
+        assert!(wp.check(Some(11)));
+        assert!(!wp.check(Some(12)));
+        wp.sync(Some(0));
+        wp.sync(Some(20));
+        assert!(!wp.check(Some(20)));
+    }
+
+    #[test]
+    fn invalid_condition_never_hits() {
+        let mut wp = ram_cell();
+        wp.set_condition("value >".to_owned());
+        assert!(wp.condition_error().is_some());
+        assert!(!wp.check(Some(1)));
+        assert!(!wp.check(Some(100)));
+    }
+}
@@ -0,0 +1,1134 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use simple_virtual_assembler::components::connection::Connection;
+use simple_virtual_assembler::components::port::Port;
+
+use crate::model::device::{Device, DeviceKind};
+use crate::model::history::RamWrite;
+use crate::model::memory::Memory;
+use crate::model::port_driver::PortDriver;
+use crate::model::vm::{Vm, VmSettings};
+
+use super::schema::{PortRef, RamPort, VM_PORTS};
+
+/// Reason why edit of circuit was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum CircuitError {
+    UnknownVm(usize),
+    UnknownRam(usize),
+    UnknownDevice(usize),
+    UnknownConnection(usize),
+    PortOutOfRange(usize),
+    PortAlreadyConnected(PortRef),
+    PortNotConnected(PortRef),
+    /// Wire would start and end on the same port
+    WireToItself(PortRef),
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::UnknownVm(id) => write!(f, "{}: {}", t!("project.error.unknown_vm"), id),
+            CircuitError::UnknownRam(id) => {
+                write!(f, "{}: {}", t!("project.error.unknown_ram"), id)
+            }
+            CircuitError::UnknownDevice(id) => {
+                write!(f, "{}: {}", t!("project.error.unknown_device_id"), id)
+            }
+            CircuitError::UnknownConnection(id) => {
+                write!(f, "{}: {}", t!("circuit.error.unknown_connection"), id)
+            }
+            CircuitError::PortOutOfRange(port) => {
+                write!(f, "{}: {}", t!("project.error.out_of_range"), port)
+            }
+            CircuitError::PortAlreadyConnected(_) => {
+                write!(f, "{}", t!("toast_info.can_connect_connected_port"))
+            }
+            CircuitError::PortNotConnected(port) => {
+                write!(
+                    f,
+                    "{}: {}",
+                    t!("circuit.error.port_not_connected"),
+                    port.port_id()
+                )
+            }
+            CircuitError::WireToItself(port) => {
+                write!(
+                    f,
+                    "{}: {}",
+                    t!("circuit.error.wire_to_itself"),
+                    port.port_id()
+                )
+            }
+        }
+    }
+}
+
+/// Vms, rams, devices and connections between them, edited only through its methods
+/// so ports of components and port ids stored in connections stay in sync
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Circuit {
+    vms: Vec<Vm>,
+    rams: Vec<Memory>,
+    devices: Vec<Device>,
+    connections: Vec<Connection>,
+    connection_names: HashMap<usize, String>,
+    next_conn_id: usize,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self {
+            vms: Vec::new(),
+            rams: Vec::new(),
+            devices: Vec::new(),
+            connections: Vec::new(),
+            connection_names: HashMap::new(),
+            next_conn_id: 1,
+        }
+    }
+}
+
+impl Circuit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // --------------------components--------------------
+
+    pub fn vms(&self) -> &[Vm] {
+        &self.vms
+    }
+
+    pub fn vms_mut(&mut self) -> &mut [Vm] {
+        &mut self.vms
+    }
+
+    pub fn vm(&self, id: usize) -> Option<&Vm> {
+        self.vms.iter().find(|vm| vm.get_id() == id)
+    }
+
+    pub fn vm_mut(&mut self, id: usize) -> Option<&mut Vm> {
+        self.vms.iter_mut().find(|vm| vm.get_id() == id)
+    }
+
+    pub fn rams(&self) -> &[Memory] {
+        &self.rams
+    }
+
+    pub fn rams_mut(&mut self) -> &mut [Memory] {
+        &mut self.rams
+    }
+
+    pub fn ram(&self, id: usize) -> Option<&Memory> {
+        self.rams.iter().find(|ram| ram.get_id() == id)
+    }
+
+    pub fn ram_mut(&mut self, id: usize) -> Option<&mut Memory> {
+        self.rams.iter_mut().find(|ram| ram.get_id() == id)
+    }
+
+    pub fn devices(&self) -> &[Device] {
+        &self.devices
+    }
+
+    pub fn devices_mut(&mut self) -> &mut [Device] {
+        &mut self.devices
+    }
+
+    pub fn device(&self, id: usize) -> Option<&Device> {
+        self.devices.iter().find(|device| device.get_id() == id)
+    }
+
+    pub fn device_mut(&mut self, id: usize) -> Option<&mut Device> {
+        self.devices.iter_mut().find(|device| device.get_id() == id)
+    }
+
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    pub fn connection_name(&self, id: usize) -> Option<String> {
+        self.connection_names.get(&id).cloned()
+    }
+
+    pub fn connection_names(&self) -> &HashMap<usize, String> {
+        &self.connection_names
+    }
+
+    /// Id after the last vm
+    pub fn next_vm_id(&self) -> usize {
+        self.vms.last().map_or(0, |last| last.get_id() + 1)
+    }
+
+    /// Id after the last ram
+    pub fn next_ram_id(&self) -> usize {
+        self.rams.last().map_or(0, |last| last.get_id() + 1)
+    }
+
+    /// Id after the last device
+    pub fn next_device_id(&self) -> usize {
+        self.devices.last().map_or(0, |last| last.get_id() + 1)
+    }
+
+    /// Adds vm with id after the last one, returns the id
+    pub fn add_vm(&mut self, stack_present: bool) -> usize {
+        let id = self.next_vm_id();
+        self.vms.push(Vm::new(id, stack_present));
+        id
+    }
+
+    /// Changes name, delay and stack of vm, ports of rebuilt vm are connected again
+    pub fn apply_vm_settings(
+        &mut self,
+        id: usize,
+        settings: &VmSettings,
+    ) -> Result<(), CircuitError> {
+        let vm = self
+            .vms
+            .iter_mut()
+            .find(|vm| vm.get_id() == id)
+            .ok_or(CircuitError::UnknownVm(id))?;
+        if !vm.apply_settings(settings) {
+            return Ok(());
+        }
+        for conn in self.connections.iter_mut() {
+            for port in PortRef::from_connection(conn) {
+                if let PortRef::Vm { vm: vm_id, port } = port {
+                    if vm_id == id {
+                        vm.with_vm(|vm| vm.connect(port, conn));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds already created vm, e.g. loaded from project, vms are kept ordered by id
+    pub fn insert_vm(&mut self, vm: Vm) {
+        let index = self
+            .vms
+            .partition_point(|other| other.get_id() < vm.get_id());
+        self.vms.insert(index, vm);
+    }
+
+    /// Halts vm, disconnects its ports and removes it
+    pub fn remove_vm(&mut self, id: usize) -> Result<(), CircuitError> {
+        self.vm_mut(id)
+            .ok_or(CircuitError::UnknownVm(id))?
+            .halt_vm();
+        for port in 0..VM_PORTS {
+            let port = PortRef::Vm { vm: id, port };
+            if self.port_connection(&port).is_some() {
+                self.disconnect(&port)?;
+            }
+        }
+        self.vms.retain(|vm| vm.get_id() != id);
+        Ok(())
+    }
+
+    /// Adds ram of given size with id after the last one, returns the id
+    pub fn add_ram(&mut self, size: usize) -> usize {
+        let id = self.next_ram_id();
+        self.rams.push(Memory::with_size(id, size));
+        id
+    }
+
+    /// Changes size of ram keeping values that fit, its ports stay connected
+    pub fn resize_ram(&mut self, id: usize, size: usize) -> Result<(), CircuitError> {
+        let ram = self
+            .rams
+            .iter_mut()
+            .find(|ram| ram.get_id() == id)
+            .ok_or(CircuitError::UnknownRam(id))?;
+        ram.resize(size);
+        for conn in self.connections.iter_mut() {
+            for port in PortRef::from_connection(conn) {
+                if let PortRef::Ram { ram: ram_id, port } = port {
+                    if ram_id != id {
+                        continue;
+                    }
+                    match port {
+                        RamPort::Index => ram.ram.connect_index_port(conn),
+                        RamPort::Data => ram.ram.connect_data_port(conn),
+                        RamPort::Mode => ram.ram.connect_mode_port(conn),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds already created ram, e.g. loaded from project, rams are kept ordered by id
+    pub fn insert_ram(&mut self, ram: Memory) {
+        let index = self
+            .rams
+            .partition_point(|other| other.get_id() < ram.get_id());
+        self.rams.insert(index, ram);
+    }
+
+    /// Disconnects ports of ram and removes it
+    pub fn remove_ram(&mut self, id: usize) -> Result<(), CircuitError> {
+        self.ram(id).ok_or(CircuitError::UnknownRam(id))?;
+        for port in [RamPort::Index, RamPort::Data, RamPort::Mode] {
+            let port = PortRef::Ram { ram: id, port };
+            if self.port_connection(&port).is_some() {
+                self.disconnect(&port)?;
+            }
+        }
+        self.rams.retain(|ram| ram.get_id() != id);
+        Ok(())
+    }
+
+    /// Adds device with id after the last one, returns the id
+    pub fn add_device(&mut self, kind: DeviceKind) -> usize {
+        let id = self.next_device_id();
+        self.devices.push(Device::new(id, kind));
+        id
+    }
+
+    /// Adds already created device, e.g. loaded from project, devices are kept ordered by id
+    pub fn insert_device(&mut self, device: Device) {
+        let index = self
+            .devices
+            .partition_point(|other| other.get_id() < device.get_id());
+        self.devices.insert(index, device);
+    }
+
+    /// Disconnects ports of device and removes it
+    pub fn remove_device(&mut self, id: usize) -> Result<(), CircuitError> {
+        let port_count = self
+            .device(id)
+            .ok_or(CircuitError::UnknownDevice(id))?
+            .port_count();
+        for port in 0..port_count {
+            let port = PortRef::Device { device: id, port };
+            if self.port_connection(&port).is_some() {
+                self.disconnect(&port)?;
+            }
+        }
+        self.devices.retain(|device| device.get_id() != id);
+        Ok(())
+    }
+
+    pub fn rename_vm(&mut self, id: usize, name: String) -> Result<(), CircuitError> {
+        self.vm_mut(id)
+            .ok_or(CircuitError::UnknownVm(id))?
+            .set_name(name);
+        Ok(())
+    }
+
+    pub fn rename_ram(&mut self, id: usize, name: String) -> Result<(), CircuitError> {
+        self.ram_mut(id)
+            .ok_or(CircuitError::UnknownRam(id))?
+            .set_name(name);
+        Ok(())
+    }
+
+    pub fn rename_device(&mut self, id: usize, name: String) -> Result<(), CircuitError> {
+        self.device_mut(id)
+            .ok_or(CircuitError::UnknownDevice(id))?
+            .set_name(name);
+        Ok(())
+    }
+
+    // --------------------connections--------------------
+
+    /// Adds connection named `conn:<id>`, returns its id
+    pub fn add_connection(&mut self) -> usize {
+        let id = self.next_conn_id;
+        self.insert_connection(id, format!("conn:{}", id));
+        id
+    }
+
+    /// Adds connection with given id, e.g. loaded from project
+    pub fn insert_connection(&mut self, id: usize, name: String) {
+        self.connections.push(Connection::new_with_id(id));
+        self.connection_names.insert(id, name);
+        self.next_conn_id = self.next_conn_id.max(id + 1);
+    }
+
+    /// Disconnects every port from connection and removes it
+    pub fn remove_connection(&mut self, id: usize) -> Result<(), CircuitError> {
+        let index = self.connection_index(id)?;
+        for port in PortRef::from_connection(&self.connections[index]) {
+            self.disconnect_port(&port);
+        }
+        self.connections.remove(index);
+        self.connection_names.remove(&id);
+        Ok(())
+    }
+
+    pub fn rename_connection(&mut self, id: usize, name: String) -> Result<(), CircuitError> {
+        self.connection_index(id)?;
+        self.connection_names.insert(id, name);
+        Ok(())
+    }
+
+    fn connection_index(&self, id: usize) -> Result<usize, CircuitError> {
+        self.connections
+            .iter()
+            .position(|conn| conn.get_id() == Some(id))
+            .ok_or(CircuitError::UnknownConnection(id))
+    }
+
+    /// Current value of every connection, read through any port connected to it,
+    /// connections without ports are skipped
+    pub fn connection_values(&self) -> Vec<(usize, i32)> {
+        self.connections
+            .iter()
+            .filter_map(|conn| {
+                let value = PortRef::from_connection(conn)
+                    .iter()
+                    .find_map(|port| PortDriver::read(&self.port(port)?))?;
+                Some((conn.get_id()?, value))
+            })
+            .collect()
+    }
+
+    /// Name of port shown in ui, e.g. `vm:0 p3` or `ram:1 data`
+    pub fn port_label(&self, port: &PortRef) -> String {
+        match port {
+            PortRef::Vm { vm, port } => {
+                let name = self
+                    .vm(*vm)
+                    .map_or(format!("vm:{}", vm), |vm| vm.get_name());
+                format!("{} p{}", name, port)
+            }
+            PortRef::Ram { ram, port } => {
+                let name = self
+                    .ram(*ram)
+                    .map_or(format!("ram:{}", ram), |ram| ram.get_name());
+                format!("{} {}", name, port.name())
+            }
+            PortRef::Device { device, port } => match self.device(*device) {
+                Some(device) => format!(
+                    "{} {}",
+                    device.get_name(),
+                    device.kind.port_names().get(*port).unwrap_or(&"?")
+                ),
+                None => format!("device:{} {}", device, port),
+            },
+        }
+    }
+
+    /// Id of connection port is connected to
+    pub fn port_connection(&self, port: &PortRef) -> Option<usize> {
+        self.port(port)?.get_conn_id()
+    }
+
+    /// Port of component, connected port shares value with its connection
+    pub fn port(&self, port: &PortRef) -> Option<Port> {
+        match port {
+            PortRef::Vm { vm, port } => {
+                let ports = self.vm(*vm)?.vm.lock().ok()?.get_ports();
+                ports.into_iter().nth(*port)
+            }
+            PortRef::Ram { ram, port } => {
+                let ram = &self.ram(*ram)?.ram;
+                Some(match port {
+                    RamPort::Index => ram.get_index_port(),
+                    RamPort::Data => ram.get_data_port(),
+                    RamPort::Mode => ram.get_mode_port(),
+                })
+            }
+            PortRef::Device { device, port } => {
+                let device = self.device(*device)?;
+                if *port >= device.port_count() {
+                    return None;
+                }
+                Some(device.port(*port))
+            }
+        }
+    }
+
+    fn check_port(&self, port: &PortRef) -> Result<(), CircuitError> {
+        match port {
+            PortRef::Vm { vm, port } => {
+                self.vm(*vm).ok_or(CircuitError::UnknownVm(*vm))?;
+                if *port >= VM_PORTS {
+                    return Err(CircuitError::PortOutOfRange(*port));
+                }
+            }
+            PortRef::Ram { ram, .. } => {
+                self.ram(*ram).ok_or(CircuitError::UnknownRam(*ram))?;
+            }
+            PortRef::Device { device, port } => {
+                let device = self
+                    .device(*device)
+                    .ok_or(CircuitError::UnknownDevice(*device))?;
+                if *port >= device.port_count() {
+                    return Err(CircuitError::PortOutOfRange(*port));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn connect(&mut self, conn_id: usize, port: PortRef) -> Result<(), CircuitError> {
+        self.check_port(&port)?;
+        let index = self.connection_index(conn_id)?;
+        if self.port_connection(&port).is_some() {
+            return Err(CircuitError::PortAlreadyConnected(port));
+        }
+        let conn = &mut self.connections[index];
+        match port {
+            PortRef::Vm {
+                vm,
+                port: port_index,
+            } => {
+                if let Some(vm) = self.vms.iter_mut().find(|v| v.get_id() == vm) {
+                    vm.with_vm(|vm| vm.connect_with_id(port_index, conn, port.port_id()));
+                }
+            }
+            PortRef::Ram {
+                ram,
+                port: ram_port,
+            } => {
+                if let Some(ram) = self.rams.iter_mut().find(|r| r.get_id() == ram) {
+                    match ram_port {
+                        RamPort::Index => ram.ram.connect_index_port(conn),
+                        RamPort::Data => ram.ram.connect_data_port(conn),
+                        RamPort::Mode => ram.ram.connect_mode_port(conn),
+                    }
+                    conn.add_port_id(port.port_id());
+                }
+            }
+            PortRef::Device {
+                device,
+                port: port_index,
+            } => {
+                if let Some(device) = self.devices.iter_mut().find(|d| d.get_id() == device) {
+                    device.connect(port_index, conn, port.port_id());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self, port: &PortRef) -> Result<(), CircuitError> {
+        self.check_port(port)?;
+        let conn_id = self
+            .port_connection(port)
+            .ok_or_else(|| CircuitError::PortNotConnected(port.clone()))?;
+        self.disconnect_port(port);
+        let index = self.connection_index(conn_id)?;
+        self.connections[index].remove_port_id(port.port_id());
+        Ok(())
+    }
+
+    /// Joins two ports, used when wire is dragged between them.
+    /// Ports that aren't connected get new connection, unconnected port joins connection
+    /// of the other one and two connections are merged into connection of `from`.
+    /// Returns id of connection both ports end up on
+    pub fn wire(&mut self, from: PortRef, to: PortRef) -> Result<usize, CircuitError> {
+        self.check_port(&from)?;
+        self.check_port(&to)?;
+        if from == to {
+            return Err(CircuitError::WireToItself(from));
+        }
+        match (self.port_connection(&from), self.port_connection(&to)) {
+            (Some(from_conn), Some(to_conn)) => {
+                if from_conn != to_conn {
+                    let index = self.connection_index(to_conn)?;
+                    let ports = PortRef::from_connection(&self.connections[index]);
+                    self.remove_connection(to_conn)?;
+                    for port in ports {
+                        self.connect(from_conn, port)?;
+                    }
+                }
+                Ok(from_conn)
+            }
+            (Some(conn), None) => {
+                self.connect(conn, to)?;
+                Ok(conn)
+            }
+            (None, Some(conn)) => {
+                self.connect(conn, from)?;
+                Ok(conn)
+            }
+            (None, None) => {
+                let conn = self.add_connection();
+                self.connect(conn, from)?;
+                self.connect(conn, to)?;
+                Ok(conn)
+            }
+        }
+    }
+
+    /// Disconnects port of component, leaves its id in connection
+    fn disconnect_port(&mut self, port: &PortRef) {
+        match port {
+            PortRef::Vm { vm, port } => {
+                if let Some(vm) = self.vm_mut(*vm) {
+                    vm.with_vm(|vm| vm.disconnect(*port));
+                }
+            }
+            PortRef::Ram { ram, port } => {
+                if let Some(ram) = self.ram_mut(*ram) {
+                    match port {
+                        RamPort::Index => ram.ram.disconnect_index_port(),
+                        RamPort::Data => ram.ram.disconnect_data_port(),
+                        RamPort::Mode => ram.ram.disconnect_mode_port(),
+                    }
+                }
+            }
+            PortRef::Device { device, port } => {
+                if let Some(device) = self.device_mut(*device) {
+                    device.disconnect(*port);
+                }
+            }
+        }
+    }
+
+    /// Disconnects ports of all components, keeping port ids in connections,
+    /// done before serializing so values of connections aren't stored in ports
+    pub fn disconnect_ports(&mut self) {
+        for vm in self.vms.iter_mut() {
+            vm.with_vm(|vm| {
+                for port in 0..VM_PORTS {
+                    if let Port::Connected(_, _) = vm.get_ports()[port] {
+                        vm.disconnect(port);
+                    }
+                }
+            });
+        }
+        for ram in self.rams.iter_mut() {
+            ram.ram.disconnect_index_port();
+            ram.ram.disconnect_data_port();
+            ram.ram.disconnect_mode_port();
+        }
+        for device in self.devices.iter_mut() {
+            for port in 0..device.port_count() {
+                if device.port(port).get_conn_id().is_some() {
+                    device.disconnect(port);
+                }
+            }
+        }
+    }
+
+    /// Connects ports of components to connections holding their ids,
+    /// done after deserializing and after `disconnect_ports`
+    pub fn reconnect_ports(&mut self) {
+        for conn in self.connections.iter_mut() {
+            for port in PortRef::from_connection(conn) {
+                match port {
+                    PortRef::Vm { vm, port } => {
+                        if let Some(vm) = self.vms.iter_mut().find(|v| v.get_id() == vm) {
+                            vm.with_vm(|vm| vm.connect(port, conn));
+                        }
+                    }
+                    PortRef::Ram { ram, port } => {
+                        if let Some(ram) = self.rams.iter_mut().find(|r| r.get_id() == ram) {
+                            match port {
+                                RamPort::Index => ram.ram.connect_index_port(conn),
+                                RamPort::Data => ram.ram.connect_data_port(conn),
+                                RamPort::Mode => ram.ram.connect_mode_port(conn),
+                            }
+                        }
+                    }
+                    PortRef::Device { device, port } => {
+                        if let Some(device) = self.devices.iter_mut().find(|d| d.get_id() == device)
+                        {
+                            device.reconnect(port, conn);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes every component and connection
+    pub fn clear(&mut self) {
+        self.vms.iter_mut().for_each(|vm| vm.halt_vm());
+        *self = Circuit::default();
+    }
+
+    // --------------------simulation--------------------
+
+    pub fn refresh_rams(&mut self) {
+        self.rams.iter_mut().for_each(|ram| ram.refresh());
+    }
+
+    /// Lets devices react to values on their ports and writes values they put on ports
+    pub fn update_devices(&mut self) {
+        for index in 0..self.devices.len() {
+            let writes = self.devices[index].update();
+            self.write_device_ports(index, writes);
+        }
+    }
+
+    /// Lets time driven devices, e.g. clocks, count time in seconds or ticks of scheduler
+    pub fn advance_devices(&mut self, time: Option<f64>, tick: Option<u64>) {
+        for index in 0..self.devices.len() {
+            let writes = self.devices[index].advance(time, tick);
+            self.write_device_ports(index, writes);
+        }
+    }
+
+    fn write_device_ports(&self, index: usize, writes: Vec<(usize, i32)>) {
+        for (port, value) in writes {
+            PortDriver::write(&self.devices[index].port(port), value);
+        }
+    }
+
+    pub fn zero_rams(&mut self) {
+        self.rams.iter_mut().for_each(|ram| ram.zero_data());
+    }
+
+    pub fn all_vms_finished(&self) -> bool {
+        self.vms.iter().all(|vm| vm.is_finished())
+    }
+
+    /// Executes one instruction on every vm that hasn't finished, in order of vms,
+    /// rams and devices are updated after each vm
+    pub fn step_vms(&mut self, record_history: bool) {
+        for index in 0..self.vms.len() {
+            if self.vms[index].is_finished() {
+                continue;
+            }
+            if record_history {
+                self.step_vm_with_history(index);
+            } else {
+                self.vms[index].step();
+                self.refresh_rams();
+            }
+            self.update_devices();
+        }
+    }
+
+    /// Executes one instruction of vm, with `record_history` its state and ram cells
+    /// it changed are recorded so it can be stepped back
+    pub fn step_vm(&mut self, id: usize, record_history: bool) -> Result<(), CircuitError> {
+        let index = self
+            .vms
+            .iter()
+            .position(|vm| vm.get_id() == id)
+            .ok_or(CircuitError::UnknownVm(id))?;
+        if record_history {
+            self.step_vm_with_history(index);
+        } else {
+            self.vms[index].step();
+            self.refresh_rams();
+        }
+        self.update_devices();
+        Ok(())
+    }
+
+    fn step_vm_with_history(&mut self, index: usize) {
+        self.vms[index].step_with_history();
+
+        // refresh only writes data port to cell pointed by index port
+        let addressed: Vec<(usize, usize, i32)> = self
+            .rams
+            .iter()
+            .filter_map(|ram| {
+                let cell = ram.addressed_index()?;
+                Some((ram.get_id(), cell, ram.get_value(cell)?))
+            })
+            .collect();
+        self.refresh_rams();
+
+        let ram_writes = addressed
+            .into_iter()
+            .filter(|(ram_id, cell, old_value)| {
+                self.ram(*ram_id)
+                    .and_then(|ram| ram.get_value(*cell))
+                    .is_some_and(|value| value != *old_value)
+            })
+            .map(|(ram_id, index, old_value)| RamWrite {
+                ram_id,
+                index,
+                old_value,
+            })
+            .collect();
+        self.vms[index].attach_ram_writes(ram_writes);
+    }
+
+    /// Undoes last recorded instruction of vm, with ram writes it caused
+    pub fn step_back_vm(&mut self, id: usize) -> Result<(), CircuitError> {
+        let vm = self
+            .vms
+            .iter_mut()
+            .find(|vm| vm.get_id() == id)
+            .ok_or(CircuitError::UnknownVm(id))?;
+        let ram_writes = vm.step_back(&mut self.connections);
+        for write in ram_writes.unwrap_or_default().iter().rev() {
+            if let Some(ram) = self.ram_mut(write.ram_id) {
+                ram.set_value_at_index(write.index, write.old_value);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::clock::{Clock, ClockMode, ClockUnit};
+    use crate::model::memory::DEFAULT_RAM_SIZE;
+    use crate::model::rom::Rom;
+
+    fn vm_port(vm: usize, port: usize) -> PortRef {
+        PortRef::Vm { vm, port }
+    }
+
+    fn ram_port(ram: usize, port: RamPort) -> PortRef {
+        PortRef::Ram { ram, port }
+    }
+
+    fn device_port(device: usize, port: usize) -> PortRef {
+        PortRef::Device { device, port }
+    }
+
+    /// Adds vm without stack running code, returns its id
+    fn add_vm_running(circuit: &mut Circuit, code: &str) -> usize {
+        let vm = circuit.add_vm(false);
+        circuit.vm_mut(vm).unwrap().set_code(code.to_string());
+        vm
+    }
+
+    /// Adds connection joining ports, returns its id
+    fn connect_all(circuit: &mut Circuit, ports: &[PortRef]) -> usize {
+        let conn = circuit.add_connection();
+        for port in ports {
+            circuit.connect(conn, port.clone()).unwrap();
+        }
+        conn
+    }
+
+    fn step_vms(circuit: &mut Circuit, steps: usize) {
+        for _ in 0..steps {
+            circuit.step_vms(false);
+        }
+    }
+
+    #[test]
+    fn components_get_consecutive_ids() {
+        let mut circuit = Circuit::new();
+        assert_eq!(circuit.add_vm(false), 0);
+        assert_eq!(circuit.add_vm(true), 1);
+        assert_eq!(circuit.add_ram(DEFAULT_RAM_SIZE), 0);
+        assert_eq!(circuit.add_connection(), 1);
+        assert_eq!(circuit.add_connection(), 2);
+        assert_eq!(circuit.connection_name(2), Some("conn:2".to_string()));
+    }
+
+    #[test]
+    fn resized_ram_keeps_values_and_connections() {
+        let mut circuit = Circuit::new();
+        let vm = add_vm_running(&mut circuit, "MOV 20 p0\nMOV 7 p1\nMOV 0 p2");
+        let ram = circuit.add_ram(32);
+        let conns: Vec<usize> = [RamPort::Index, RamPort::Data, RamPort::Mode]
+            .into_iter()
+            .enumerate()
+            .map(|(port, kind)| {
+                connect_all(&mut circuit, &[vm_port(vm, port), ram_port(ram, kind)])
+            })
+            .collect();
+        circuit.ram_mut(ram).unwrap().set_value_at_index(3, 42);
+
+        circuit.resize_ram(ram, 16).unwrap();
+        assert_eq!(circuit.ram(ram).unwrap().size(), 16);
+        assert_eq!(circuit.ram(ram).unwrap().get_value(3), Some(42));
+        circuit.resize_ram(ram, 64).unwrap();
+        assert_eq!(circuit.ram(ram).unwrap().size(), 64);
+        assert_eq!(
+            circuit.port_connection(&ram_port(ram, RamPort::Mode)),
+            Some(conns[2])
+        );
+
+        step_vms(&mut circuit, 3);
+        assert_eq!(circuit.ram(ram).unwrap().get_value(20), Some(7));
+        assert_eq!(circuit.resize_ram(5, 16), Err(CircuitError::UnknownRam(5)));
+    }
+
+    #[test]
+    fn vm_settings_rebuild_stack_and_keep_ports() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        let conn = connect_all(&mut circuit, &[vm_port(0, 1)]);
+
+        let settings = VmSettings {
+            name: "adder".to_string(),
+            stack: true,
+            stack_size: 4,
+            delay_ms: 10,
+        };
+        circuit.apply_vm_settings(0, &settings).unwrap();
+        let vm = circuit.vm(0).unwrap();
+        assert_eq!(vm.settings(), settings);
+        assert!(vm.has_stack());
+        assert_eq!(circuit.port_connection(&vm_port(0, 1)), Some(conn));
+
+        circuit
+            .vm_mut(0)
+            .unwrap()
+            .set_code("PSH 5\nPOP r0\nMOV r0 p1".to_string());
+        step_vms(&mut circuit, 3);
+        assert_eq!(circuit.connection_values(), vec![(conn, 5)]);
+        assert_eq!(
+            circuit.apply_vm_settings(3, &settings),
+            Err(CircuitError::UnknownVm(3))
+        );
+    }
+
+    #[test]
+    fn inserted_connection_moves_next_id() {
+        let mut circuit = Circuit::new();
+        circuit.insert_connection(7, "bus".to_string());
+        assert_eq!(circuit.add_connection(), 8);
+    }
+
+    #[test]
+    fn connect_and_disconnect_ports() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        circuit.add_ram(DEFAULT_RAM_SIZE);
+        let conn = circuit.add_connection();
+
+        circuit.connect(conn, vm_port(0, 2)).unwrap();
+        circuit.connect(conn, ram_port(0, RamPort::Data)).unwrap();
+        assert_eq!(circuit.port_connection(&vm_port(0, 2)), Some(conn));
+        assert_eq!(
+            circuit.port_connection(&ram_port(0, RamPort::Data)),
+            Some(conn)
+        );
+        assert_eq!(
+            PortRef::from_connection(&circuit.connections()[0]),
+            vec![vm_port(0, 2), ram_port(0, RamPort::Data)]
+        );
+
+        circuit.disconnect(&vm_port(0, 2)).unwrap();
+        assert_eq!(circuit.port_connection(&vm_port(0, 2)), None);
+        assert_eq!(
+            PortRef::from_connection(&circuit.connections()[0]),
+            vec![ram_port(0, RamPort::Data)]
+        );
+    }
+
+    #[test]
+    fn connecting_connected_port_fails() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        let first = circuit.add_connection();
+        let second = circuit.add_connection();
+        circuit.connect(first, vm_port(0, 0)).unwrap();
+        assert_eq!(
+            circuit.connect(second, vm_port(0, 0)),
+            Err(CircuitError::PortAlreadyConnected(vm_port(0, 0)))
+        );
+    }
+
+    #[test]
+    fn invalid_edits_are_rejected() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        let conn = circuit.add_connection();
+        assert_eq!(
+            circuit.connect(conn, vm_port(3, 0)),
+            Err(CircuitError::UnknownVm(3))
+        );
+        assert_eq!(
+            circuit.connect(conn, vm_port(0, VM_PORTS)),
+            Err(CircuitError::PortOutOfRange(VM_PORTS))
+        );
+        assert_eq!(
+            circuit.connect(conn, ram_port(0, RamPort::Mode)),
+            Err(CircuitError::UnknownRam(0))
+        );
+        assert_eq!(
+            circuit.connect(9, vm_port(0, 0)),
+            Err(CircuitError::UnknownConnection(9))
+        );
+        assert_eq!(
+            circuit.disconnect(&vm_port(0, 1)),
+            Err(CircuitError::PortNotConnected(vm_port(0, 1)))
+        );
+        assert_eq!(
+            circuit.rename_ram(0, "x".to_string()),
+            Err(CircuitError::UnknownRam(0))
+        );
+    }
+
+    #[test]
+    fn wiring_creates_joins_and_merges_connections() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        circuit.add_vm(false);
+        circuit.add_ram(DEFAULT_RAM_SIZE);
+
+        let first = circuit.wire(vm_port(0, 0), vm_port(1, 0)).unwrap();
+        assert_eq!(circuit.connections().len(), 1);
+        assert_eq!(
+            circuit.wire(ram_port(0, RamPort::Data), vm_port(1, 0)),
+            Ok(first)
+        );
+        assert_eq!(
+            circuit.port_connection(&ram_port(0, RamPort::Data)),
+            Some(first)
+        );
+
+        let second = circuit.wire(vm_port(0, 1), vm_port(1, 1)).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(circuit.wire(vm_port(0, 1), vm_port(0, 0)), Ok(second));
+        assert_eq!(circuit.connections().len(), 1);
+        assert_eq!(circuit.connection_name(first), None);
+        for port in [vm_port(0, 0), vm_port(1, 0), ram_port(0, RamPort::Data)] {
+            assert_eq!(circuit.port_connection(&port), Some(second));
+        }
+    }
+
+    #[test]
+    fn port_is_not_wired_to_itself() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+
+        assert_eq!(
+            circuit.wire(vm_port(0, 0), vm_port(0, 0)),
+            Err(CircuitError::WireToItself(vm_port(0, 0)))
+        );
+        assert!(circuit.connections().is_empty());
+        assert_eq!(circuit.port_connection(&vm_port(0, 0)), None);
+    }
+
+    #[test]
+    fn removing_connection_disconnects_its_ports() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        circuit.add_ram(DEFAULT_RAM_SIZE);
+        let conn = connect_all(&mut circuit, &[vm_port(0, 1), ram_port(0, RamPort::Index)]);
+
+        circuit.remove_connection(conn).unwrap();
+        assert!(circuit.connections().is_empty());
+        assert_eq!(circuit.connection_name(conn), None);
+        assert_eq!(circuit.port_connection(&vm_port(0, 1)), None);
+        assert_eq!(circuit.port_connection(&ram_port(0, RamPort::Index)), None);
+    }
+
+    #[test]
+    fn removing_components_removes_their_ports_from_connections() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        circuit.add_vm(false);
+        circuit.add_ram(DEFAULT_RAM_SIZE);
+        connect_all(
+            &mut circuit,
+            &[vm_port(0, 0), vm_port(1, 0), ram_port(0, RamPort::Mode)],
+        );
+
+        circuit.remove_vm(0).unwrap();
+        circuit.remove_ram(0).unwrap();
+        assert_eq!(circuit.vms().len(), 1);
+        assert!(circuit.rams().is_empty());
+        assert_eq!(
+            PortRef::from_connection(&circuit.connections()[0]),
+            vec![vm_port(1, 0)]
+        );
+    }
+
+    #[test]
+    fn renaming() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        let conn = circuit.add_connection();
+        circuit.rename_vm(0, "cpu".to_string()).unwrap();
+        circuit.rename_connection(conn, "bus".to_string()).unwrap();
+        assert_eq!(circuit.vm(0).unwrap().get_name(), "cpu");
+        assert_eq!(circuit.connection_name(conn), Some("bus".to_string()));
+    }
+
+    #[test]
+    fn values_flow_through_connections() {
+        let mut circuit = Circuit::new();
+        let writer = add_vm_running(&mut circuit, "MOV 5 p0");
+        let reader = add_vm_running(&mut circuit, "MOV p0 acc");
+        connect_all(&mut circuit, &[vm_port(writer, 0), vm_port(reader, 0)]);
+
+        step_vms(&mut circuit, 1);
+        assert_eq!(circuit.vm(reader).unwrap().watched_register("acc"), Some(5));
+        assert!(circuit.all_vms_finished());
+    }
+
+    #[test]
+    fn connection_values_follow_writes() {
+        let mut circuit = Circuit::new();
+        add_vm_running(&mut circuit, "MOV 9 p2");
+        let conn = connect_all(&mut circuit, &[vm_port(0, 2)]);
+
+        assert_eq!(circuit.connection_values(), vec![(conn, 0)]);
+        step_vms(&mut circuit, 1);
+        assert_eq!(circuit.connection_values(), vec![(conn, 9)]);
+        assert_eq!(circuit.port_label(&vm_port(0, 2)), "vm:0 p2");
+    }
+
+    #[test]
+    fn clock_ticks_reach_connections() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        let clock = circuit.add_device(DeviceKind::Clock(Clock::new(
+            ClockMode::Counter,
+            2,
+            ClockUnit::Ticks,
+            true,
+        )));
+        let conn = connect_all(&mut circuit, &[vm_port(0, 0), device_port(clock, 0)]);
+
+        circuit.advance_devices(None, Some(0));
+        assert_eq!(circuit.connection_values(), vec![(conn, 0)]);
+        circuit.advance_devices(None, Some(2));
+        assert_eq!(circuit.connection_values(), vec![(conn, 1)]);
+    }
+
+    #[test]
+    fn devices_write_negative_values_to_connections() {
+        let mut circuit = Circuit::new();
+        let vm = add_vm_running(&mut circuit, "MOV 0 p0\nMOV 1 p0");
+        let rom = circuit.add_device(DeviceKind::Rom(Rom::new(
+            vec![-5, i32::MIN],
+            Default::default(),
+        )));
+        let index = connect_all(&mut circuit, &[vm_port(vm, 0), device_port(rom, 0)]);
+        let value = connect_all(&mut circuit, &[vm_port(vm, 1), device_port(rom, 1)]);
+
+        step_vms(&mut circuit, 1);
+        assert_eq!(circuit.connection_values(), vec![(index, 0), (value, -5)]);
+        step_vms(&mut circuit, 1);
+        assert_eq!(
+            circuit.connection_values(),
+            vec![(index, 1), (value, i32::MIN)]
+        );
+    }
+
+    #[test]
+    fn ports_survive_disconnect_and_reconnect() {
+        let mut circuit = Circuit::new();
+        circuit.add_vm(false);
+        circuit.add_ram(DEFAULT_RAM_SIZE);
+        let conn = connect_all(&mut circuit, &[vm_port(0, 4), ram_port(0, RamPort::Data)]);
+
+        circuit.disconnect_ports();
+        assert_eq!(circuit.port_connection(&vm_port(0, 4)), None);
+        circuit.reconnect_ports();
+        assert_eq!(circuit.port_connection(&vm_port(0, 4)), Some(conn));
+        assert_eq!(
+            circuit.port_connection(&ram_port(0, RamPort::Data)),
+            Some(conn)
+        );
+    }
+
+    #[test]
+    fn step_back_restores_vm() {
+        let mut circuit = Circuit::new();
+        add_vm_running(&mut circuit, "MOV 7 acc");
+
+        circuit.step_vm(0, true).unwrap();
+        assert_eq!(circuit.vm(0).unwrap().watched_register("acc"), Some(7));
+        circuit.step_back_vm(0).unwrap();
+        assert_eq!(circuit.vm(0).unwrap().watched_register("acc"), Some(0));
+        assert_eq!(circuit.vm(0).unwrap().watched_register("pc"), Some(0));
+    }
+}
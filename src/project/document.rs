@@ -0,0 +1,1677 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use simple_virtual_assembler::language::Language;
+use simple_virtual_assembler::vm::virtual_machine::VmStatus;
+
+use crate::model::breakpoint::Breakpoint;
+use crate::model::clock::Clock;
+use crate::model::connection_history::{ConnectionHistory, ValueChange};
+use crate::model::console::Console;
+use crate::model::device::{Device, DeviceKind};
+use crate::model::keypad::Keypad;
+use crate::model::led_bar::LedBar;
+use crate::model::memory::Memory;
+use crate::model::pixel_display::PixelDisplay;
+use crate::model::push_buttons::PushButtons;
+use crate::model::rom::Rom;
+use crate::model::scheduler::Scheduler;
+use crate::model::seven_segment::SevenSegment;
+use crate::model::switches::Switches;
+use crate::model::vm::{Vm, VmSettings};
+use crate::model::watchpoint::{WatchList, WatchTarget};
+use crate::storage::toasts::ToastsManager;
+
+use super::circuit::{Circuit, CircuitError};
+use super::schema::{
+    BreakpointRecord, ConnectionRecord, DeviceRecord, DeviceSettings, PortRef, Project, RamRecord,
+    SchedulerRecord, VmRecord, CURRENT_VERSION,
+};
+use super::undo::{Command, NameTarget, UndoStack};
+
+/// Kind of component, vm, ram or device
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ComponentKind {
+    Vm,
+    Ram,
+    Device,
+}
+
+/// What clicking on port does
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum WiringMode {
+    #[default]
+    Idle,
+    /// Clicked port gets connected to connection with this id
+    Connecting(usize),
+    /// Clicked port gets disconnected
+    Disconnecting,
+}
+
+impl WiringMode {
+    pub fn is_connecting(&self) -> bool {
+        matches!(self, WiringMode::Connecting(_))
+    }
+
+    pub fn is_disconnecting(&self) -> bool {
+        *self == WiringMode::Disconnecting
+    }
+}
+
+/// Vm, ram or device copied from one tab to be pasted into another
+#[derive(Debug, Clone)]
+pub enum ComponentCopy {
+    Vm(VmRecord),
+    Ram(RamRecord),
+    Device(DeviceRecord),
+}
+
+impl ComponentCopy {
+    pub fn name(&self) -> &str {
+        match self {
+            ComponentCopy::Vm(record) => &record.name,
+            ComponentCopy::Ram(record) => &record.name,
+            ComponentCopy::Device(record) => &record.name,
+        }
+    }
+
+    pub fn kind(&self) -> ComponentKind {
+        match self {
+            ComponentCopy::Vm(_) => ComponentKind::Vm,
+            ComponentCopy::Ram(_) => ComponentKind::Ram,
+            ComponentCopy::Device(_) => ComponentKind::Device,
+        }
+    }
+
+    pub fn id(&self) -> usize {
+        match self {
+            ComponentCopy::Vm(record) => record.id,
+            ComponentCopy::Ram(record) => record.id,
+            ComponentCopy::Device(record) => record.id,
+        }
+    }
+}
+
+/// Seconds between checks of linked source files of vms
+const SOURCE_CHECK_INTERVAL: f64 = 1.0;
+
+/// Project opened in tab, with its own circuit, scheduler and watchpoints
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Document {
+    pub circuit: Circuit,
+
+    pub active_vms: HashMap<usize, bool>,
+
+    pub active_rams: HashMap<usize, bool>,
+
+    pub active_devices: HashMap<usize, bool>,
+
+    /// Language code of vms is assembled in, saved with project. None in state
+    /// saved before documents had own language, then language of ui is used
+    language: Option<Language>,
+
+    /// File project was imported from or exported to
+    pub path: Option<PathBuf>,
+
+    /// Lockstep scheduler of this project
+    pub scheduler: Scheduler,
+
+    /// Watchpoints of vms and rams of this project
+    #[serde(alias = "watch_window")]
+    pub watchpoints: WatchList,
+
+    /// What clicking on port does
+    #[serde(skip)]
+    pub wiring: WiringMode,
+
+    /// Recent values of connections
+    #[serde(skip)]
+    pub connection_history: ConnectionHistory,
+
+    /// Time of current frame, used to timestamp values written between frames
+    #[serde(skip)]
+    frame_time: f64,
+
+    /// Time linked source files of vms were last checked for changes
+    #[serde(skip)]
+    last_source_check: f64,
+
+    /// Changes that can be undone and redone
+    #[serde(skip)]
+    undo_stack: UndoStack,
+
+    /// Document was changed since it was last saved to or loaded from file
+    pub changed: bool,
+}
+
+impl Document {
+    /// Builds document from project, code of vms is assembled in language of project
+    pub fn from_project(project: Project) -> Document {
+        let language = project.assembler_language();
+        let mut document = Document::with_language(language.clone());
+
+        for record in project.vms {
+            document.active_vms.insert(record.id, record.visible);
+            document
+                .circuit
+                .insert_vm(vm_from_record(record, &language));
+        }
+
+        for record in project.rams {
+            document.active_rams.insert(record.id, record.visible);
+            document.circuit.insert_ram(ram_from_record(record));
+        }
+
+        for record in project.devices {
+            document.active_devices.insert(record.id, record.visible);
+            document.circuit.insert_device(device_from_record(record));
+        }
+
+        for record in project.connections {
+            document.circuit.insert_connection(record.id, record.name);
+            for port in record.ports {
+                if let Err(err) = document.circuit.connect(record.id, port) {
+                    ToastsManager::show_info(err.to_string(), 10);
+                }
+            }
+        }
+
+        *document.scheduler.tick_ms_mut() = project.scheduler.tick_ms;
+        document.set_lockstep(project.scheduler.lockstep);
+        document
+    }
+
+    /// Empty document whose vms are assembled in language
+    pub fn with_language(language: Language) -> Document {
+        Document {
+            language: Some(language),
+            ..Document::default()
+        }
+    }
+
+    /// Vms, rams, connections and scheduler settings in project file format
+    pub fn to_project(&self) -> Project {
+        let vms = self
+            .circuit
+            .vms()
+            .iter()
+            .map(|vm| self.vm_record(vm))
+            .collect();
+        let rams = self
+            .circuit
+            .rams()
+            .iter()
+            .map(|ram| self.ram_record(ram))
+            .collect();
+        let devices = self
+            .circuit
+            .devices()
+            .iter()
+            .map(|device| self.device_record(device))
+            .collect();
+        let connections = self.connection_records();
+        Project {
+            version: CURRENT_VERSION,
+            language: self.language().string_code().to_string(),
+            vms,
+            rams,
+            devices,
+            connections,
+            scheduler: SchedulerRecord {
+                lockstep: self.scheduler.is_enabled(),
+                tick_ms: self.scheduler.get_tick_ms(),
+            },
+        }
+    }
+
+    fn connection_records(&self) -> Vec<ConnectionRecord> {
+        self.circuit
+            .connections()
+            .iter()
+            .filter_map(|conn| {
+                let id = conn.get_id()?;
+                Some(ConnectionRecord {
+                    id,
+                    name: self
+                        .circuit
+                        .connection_name(id)
+                        .unwrap_or(format!("conn:{}", id)),
+                    ports: PortRef::from_connection(conn),
+                })
+            })
+            .collect()
+    }
+
+    fn vm_record(&self, vm: &Vm) -> VmRecord {
+        VmRecord {
+            id: vm.get_id(),
+            name: vm.get_name(),
+            stack: vm.has_stack(),
+            stack_size: vm.stack_size(),
+            code: vm.get_code(),
+            delay_ms: vm.get_delay_ms(),
+            visible: *self.active_vms.get(&vm.get_id()).unwrap_or(&false),
+            breakpoints: vm
+                .get_breakpoints()
+                .into_iter()
+                .map(|bp| BreakpointRecord {
+                    line: bp.line,
+                    condition: bp.condition().to_owned(),
+                })
+                .collect(),
+            source: vm
+                .source_path()
+                .map(|path| path.to_string_lossy().to_string()),
+            reload_source: vm.reloads_source(),
+        }
+    }
+
+    fn ram_record(&self, ram: &Memory) -> RamRecord {
+        RamRecord {
+            id: ram.get_id(),
+            name: ram.get_name(),
+            visible: *self.active_rams.get(&ram.get_id()).unwrap_or(&false),
+            size: ram.size(),
+            data: ram.get_data(),
+        }
+    }
+
+    fn device_record(&self, device: &Device) -> DeviceRecord {
+        DeviceRecord {
+            id: device.get_id(),
+            name: device.get_name(),
+            visible: *self.active_devices.get(&device.get_id()).unwrap_or(&false),
+            settings: match &device.kind {
+                DeviceKind::Console(console) => DeviceSettings::Console {
+                    format: console.format,
+                },
+                DeviceKind::Display(display) => DeviceSettings::Display {
+                    width: display.width(),
+                    height: display.height(),
+                },
+                DeviceKind::Clock(clock) => DeviceSettings::Clock {
+                    mode: clock.mode,
+                    period: clock.period,
+                    unit: clock.unit,
+                    running: clock.running,
+                },
+                DeviceKind::LedBar(leds) => DeviceSettings::Leds { bits: leds.bits() },
+                DeviceKind::SevenSegment(segments) => DeviceSettings::Segments {
+                    digits: segments.digit_count(),
+                    base: segments.base(),
+                },
+                DeviceKind::Switches(switches) => DeviceSettings::Switches {
+                    count: switches.count(),
+                    state: switches.state(),
+                },
+                DeviceKind::Buttons(buttons) => DeviceSettings::Buttons {
+                    count: buttons.count(),
+                },
+                DeviceKind::Keypad(_) => DeviceSettings::Keypad,
+                DeviceKind::Rom(rom) => DeviceSettings::Rom {
+                    format: rom.format,
+                    data: rom.data().to_vec(),
+                },
+            },
+        }
+    }
+
+    /// Name of tab, file name or `untitled`
+    pub fn title(&self) -> String {
+        self.path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(t!("tab.untitled"))
+    }
+
+    /// Title of tab, `*` marks unsaved changes
+    pub fn tab_label(&self) -> String {
+        if self.changed {
+            format!("{}*", self.title())
+        } else {
+            self.title()
+        }
+    }
+
+    /// Document without components that wasn't saved to file
+    pub fn is_blank(&self) -> bool {
+        self.path.is_none()
+            && self.circuit.vms().is_empty()
+            && self.circuit.rams().is_empty()
+            && self.circuit.devices().is_empty()
+            && self.circuit.connections().is_empty()
+    }
+
+    pub fn language(&self) -> Language {
+        self.language_or(&Language::En)
+    }
+
+    /// Language of document, `default` for state saved before documents had own language
+    pub fn language_or(&self, default: &Language) -> Language {
+        self.language.clone().unwrap_or_else(|| default.clone())
+    }
+
+    /// Changes language of document, code of its vms is assembled again
+    pub fn set_language(&mut self, language: &Language) {
+        self.language = Some(language.clone());
+        self.circuit
+            .vms_mut()
+            .iter_mut()
+            .for_each(|vm| vm.set_language(language.clone()));
+    }
+
+    // --------------------components--------------------
+
+    pub fn add_vm(&mut self, settings: &VmSettings) {
+        let id = self.circuit.add_vm(settings.stack);
+        if let Err(err) = self.circuit.apply_vm_settings(id, settings) {
+            ToastsManager::show_err(err.to_string(), 10);
+        }
+        let lockstep = self.scheduler.is_enabled();
+        let language = self.language();
+        if let Some(vm) = self.circuit.vm_mut(id) {
+            vm.set_language(language);
+            vm.set_lockstep(lockstep);
+        }
+        self.active_vms.insert(id, true);
+        self.record_added(ComponentKind::Vm, id);
+    }
+
+    /// Changes name, delay and stack of vm, ports of rebuilt vm stay connected
+    pub fn apply_vm_settings(
+        &mut self,
+        id: usize,
+        settings: &VmSettings,
+    ) -> Result<(), CircuitError> {
+        let old = self
+            .circuit
+            .vm(id)
+            .ok_or(CircuitError::UnknownVm(id))?
+            .settings();
+        self.circuit.apply_vm_settings(id, settings)?;
+        let new = self
+            .circuit
+            .vm(id)
+            .map_or_else(|| settings.clone(), |vm| vm.settings());
+        if new != old {
+            self.push_command(Command::Settings { vm: id, old, new });
+        }
+        Ok(())
+    }
+
+    /// Changes delay of vm, recorded like other settings
+    pub fn set_vm_delay(&mut self, id: usize, delay_ms: u64) -> Result<(), CircuitError> {
+        let mut settings = self
+            .circuit
+            .vm(id)
+            .ok_or(CircuitError::UnknownVm(id))?
+            .settings();
+        settings.delay_ms = delay_ms;
+        self.apply_vm_settings(id, &settings)
+    }
+
+    pub fn add_ram(&mut self, size: usize) {
+        let id = self.circuit.add_ram(size);
+        self.active_rams.insert(id, true);
+        self.record_added(ComponentKind::Ram, id);
+    }
+
+    pub fn add_device(&mut self, kind: DeviceKind) {
+        let id = self.circuit.add_device(kind);
+        self.active_devices.insert(id, true);
+        self.record_added(ComponentKind::Device, id);
+    }
+
+    fn record_added(&mut self, kind: ComponentKind, id: usize) {
+        if let Some(copy) = self.copy_component(kind, id) {
+            self.push_command(Command::Add(copy));
+        }
+    }
+
+    fn copy_component(&self, kind: ComponentKind, id: usize) -> Option<ComponentCopy> {
+        match kind {
+            ComponentKind::Vm => self.copy_vm(id),
+            ComponentKind::Ram => self.copy_ram(id),
+            ComponentKind::Device => self.copy_device(id),
+        }
+    }
+
+    pub fn copy_vm(&self, id: usize) -> Option<ComponentCopy> {
+        let vm = self.circuit.vm(id)?;
+        Some(ComponentCopy::Vm(self.vm_record(vm)))
+    }
+
+    pub fn copy_ram(&self, id: usize) -> Option<ComponentCopy> {
+        let ram = self.circuit.ram(id)?;
+        Some(ComponentCopy::Ram(self.ram_record(ram)))
+    }
+
+    pub fn copy_device(&self, id: usize) -> Option<ComponentCopy> {
+        let device = self.circuit.device(id)?;
+        Some(ComponentCopy::Device(self.device_record(device)))
+    }
+
+    /// Adds copied component with new id, its ports are left disconnected
+    pub fn paste(&mut self, copy: ComponentCopy) {
+        let copy = match copy {
+            ComponentCopy::Vm(mut record) => {
+                record.id = self.circuit.next_vm_id();
+                ComponentCopy::Vm(record)
+            }
+            ComponentCopy::Ram(mut record) => {
+                record.id = self.circuit.next_ram_id();
+                ComponentCopy::Ram(record)
+            }
+            ComponentCopy::Device(mut record) => {
+                record.id = self.circuit.next_device_id();
+                ComponentCopy::Device(record)
+            }
+        };
+        let (kind, id) = (copy.kind(), copy.id());
+        self.insert_component(copy);
+        self.record_added(kind, id);
+    }
+
+    /// Adds component with id of its record, shown as it was when copied
+    fn insert_component(&mut self, copy: ComponentCopy) {
+        match copy {
+            ComponentCopy::Vm(record) => {
+                let id = record.id;
+                let visible = record.visible;
+                let mut vm = vm_from_record(record, &self.language());
+                vm.set_lockstep(self.scheduler.is_enabled());
+                self.circuit.insert_vm(vm);
+                self.active_vms.insert(id, visible);
+            }
+            ComponentCopy::Ram(record) => {
+                self.active_rams.insert(record.id, record.visible);
+                self.circuit.insert_ram(ram_from_record(record));
+            }
+            ComponentCopy::Device(record) => {
+                self.active_devices.insert(record.id, record.visible);
+                self.circuit.insert_device(device_from_record(record));
+            }
+        }
+    }
+
+    pub fn remove_vm(&mut self, id: usize) {
+        self.remove_component(ComponentKind::Vm, id);
+    }
+
+    pub fn remove_ram(&mut self, id: usize) {
+        self.remove_component(ComponentKind::Ram, id);
+    }
+
+    pub fn remove_device(&mut self, id: usize) {
+        self.remove_component(ComponentKind::Device, id);
+    }
+
+    fn remove_component(&mut self, kind: ComponentKind, id: usize) {
+        if let Some(component) = self.copy_component(kind, id) {
+            self.push_command(Command::Remove {
+                component,
+                connections: self.connection_records(),
+            });
+        }
+        if let Err(err) = self.drop_component(kind, id) {
+            ToastsManager::show_err(err.to_string(), 10);
+        }
+    }
+
+    /// Removes component with its watchpoints
+    fn drop_component(&mut self, kind: ComponentKind, id: usize) -> Result<(), CircuitError> {
+        match kind {
+            ComponentKind::Vm => {
+                self.watchpoints.retain(|target| match target {
+                    WatchTarget::VmRegister { vm_id, .. } | WatchTarget::VmStackDepth { vm_id } => {
+                        *vm_id != id
+                    }
+                    WatchTarget::RamCell { .. } => true,
+                });
+                self.circuit.remove_vm(id)
+            }
+            ComponentKind::Ram => {
+                self.watchpoints.retain(|target| match target {
+                    WatchTarget::RamCell { ram_id, .. } => *ram_id != id,
+                    _ => true,
+                });
+                self.circuit.remove_ram(id)
+            }
+            ComponentKind::Device => self.circuit.remove_device(id),
+        }
+    }
+
+    /// Removes every component, connection and watchpoint, file path is kept
+    pub fn clear(&mut self) {
+        let mut components = Vec::new();
+        components.extend(
+            self.circuit
+                .vms()
+                .iter()
+                .map(|vm| ComponentCopy::Vm(self.vm_record(vm))),
+        );
+        components.extend(
+            self.circuit
+                .rams()
+                .iter()
+                .map(|ram| ComponentCopy::Ram(self.ram_record(ram))),
+        );
+        components.extend(
+            self.circuit
+                .devices()
+                .iter()
+                .map(|device| ComponentCopy::Device(self.device_record(device))),
+        );
+        let connections = self.connection_records();
+        if !components.is_empty() || !connections.is_empty() {
+            self.push_command(Command::Clear {
+                components,
+                connections,
+            });
+        }
+        self.clear_circuit();
+    }
+
+    fn clear_circuit(&mut self) {
+        self.circuit.clear();
+        self.watchpoints.clear();
+        self.connection_history.clear();
+        self.wiring = WiringMode::Idle;
+    }
+
+    /// Changes name of component or connection
+    pub fn rename(&mut self, target: NameTarget, name: String) -> Result<(), CircuitError> {
+        let old = self.name_of(target).unwrap_or_default();
+        self.set_name(target, name.clone())?;
+        if old != name {
+            self.push_command(Command::Rename {
+                target,
+                old,
+                new: name,
+            });
+        }
+        Ok(())
+    }
+
+    fn name_of(&self, target: NameTarget) -> Option<String> {
+        match target {
+            NameTarget::Component(ComponentKind::Vm, id) => {
+                self.circuit.vm(id).map(|vm| vm.get_name())
+            }
+            NameTarget::Component(ComponentKind::Ram, id) => {
+                self.circuit.ram(id).map(|ram| ram.get_name())
+            }
+            NameTarget::Component(ComponentKind::Device, id) => {
+                self.circuit.device(id).map(|device| device.get_name())
+            }
+            NameTarget::Connection(id) => self.circuit.connection_name(id),
+        }
+    }
+
+    fn set_name(&mut self, target: NameTarget, name: String) -> Result<(), CircuitError> {
+        match target {
+            NameTarget::Component(ComponentKind::Vm, id) => self.circuit.rename_vm(id, name),
+            NameTarget::Component(ComponentKind::Ram, id) => self.circuit.rename_ram(id, name),
+            NameTarget::Component(ComponentKind::Device, id) => {
+                self.circuit.rename_device(id, name)
+            }
+            NameTarget::Connection(id) => self.circuit.rename_connection(id, name),
+        }
+    }
+
+    /// Writes values to cells of ram starting at index
+    pub fn set_ram_values(&mut self, ram: usize, index: usize, values: Vec<i32>) {
+        let Some(ram_window) = self.circuit.ram_mut(ram) else {
+            return;
+        };
+        let old = ram_window
+            .get_data()
+            .iter()
+            .skip(index)
+            .take(values.len())
+            .copied()
+            .collect();
+        for (offset, value) in values.iter().enumerate() {
+            ram_window.set_value_at_index(index + offset, *value);
+        }
+        self.push_command(Command::RamValues {
+            ram,
+            index,
+            old,
+            new: values,
+        });
+    }
+
+    /// Sets every cell of ram to 0
+    pub fn zero_ram(&mut self, id: usize) {
+        let size = self.circuit.ram(id).map_or(0, |ram| ram.size());
+        self.set_ram_values(id, 0, vec![0; size]);
+    }
+
+    /// Changes size of ram, ports stay connected
+    pub fn resize_ram(&mut self, id: usize, size: usize) -> Result<(), CircuitError> {
+        let old = self
+            .circuit
+            .ram(id)
+            .ok_or(CircuitError::UnknownRam(id))?
+            .get_data();
+        self.circuit.resize_ram(id, size)?;
+        let new = self.circuit.ram(id).map_or(size, |ram| ram.size());
+        if new != old.len() {
+            self.push_command(Command::RamSize { ram: id, old, new });
+        }
+        Ok(())
+    }
+
+    /// Replaces contents of rom, e.g. with values loaded from file
+    pub fn load_rom(&mut self, id: usize, data: Vec<i32>) -> Result<(), CircuitError> {
+        let rom = self.rom_mut(id)?;
+        let old = rom.data().to_vec();
+        rom.set_data(data);
+        let new = rom.data().to_vec();
+        if new != old {
+            self.push_command(Command::RomData { rom: id, old, new });
+        }
+        Ok(())
+    }
+
+    fn rom_mut(&mut self, id: usize) -> Result<&mut Rom, CircuitError> {
+        match self.circuit.device_mut(id).map(|device| &mut device.kind) {
+            Some(DeviceKind::Rom(rom)) => Ok(rom),
+            _ => Err(CircuitError::UnknownDevice(id)),
+        }
+    }
+
+    /// Records edit of vm code made in its editor or by opening file, `old` is code before it
+    pub fn record_code_edit(&mut self, vm: usize, old: String) {
+        let Some(new) = self.circuit.vm(vm).map(|vm| vm.get_code()) else {
+            return;
+        };
+        if old != new {
+            self.changed = true;
+            self.undo_stack
+                .push_code_edit(vm, old, new, self.frame_time);
+        }
+    }
+
+    /// Runs change of vm that can't be undone but is saved with project
+    fn edit_vm<T>(&mut self, id: usize, change: impl FnOnce(&mut Vm) -> T) -> Option<T> {
+        let result = change(self.circuit.vm_mut(id)?);
+        self.changed = true;
+        Some(result)
+    }
+
+    pub fn toggle_breakpoint(&mut self, vm: usize, line: usize) {
+        self.edit_vm(vm, |vm| vm.toggle_breakpoint(line));
+    }
+
+    pub fn remove_breakpoint(&mut self, vm: usize, index: usize) {
+        self.edit_vm(vm, |vm| vm.remove_breakpoint(index));
+    }
+
+    pub fn set_breakpoint_condition(&mut self, vm: usize, index: usize, condition: String) {
+        self.edit_vm(vm, |vm| {
+            if let Some(breakpoint) = vm.breakpoints_mut().get_mut(index) {
+                breakpoint.set_condition(condition);
+            }
+        });
+    }
+
+    pub fn set_reload_source(&mut self, vm: usize, reload: bool) {
+        self.edit_vm(vm, |vm| vm.set_reload_source(reload));
+    }
+
+    pub fn unlink_source(&mut self, vm: usize) {
+        self.edit_vm(vm, |vm| vm.unlink_source());
+    }
+
+    /// Replaces code of vm with content of file and links vm to it, code change can be undone
+    pub fn open_source(&mut self, vm: usize, path: &Path) -> Result<(), String> {
+        let Some(old_code) = self.circuit.vm(vm).map(|vm| vm.get_code()) else {
+            return Ok(());
+        };
+        self.edit_vm(vm, |vm| vm.open_source(path))
+            .unwrap_or(Ok(()))?;
+        self.record_code_edit(vm, old_code);
+        Ok(())
+    }
+
+    /// Writes code of vm to file and links vm to it
+    pub fn save_source(&mut self, vm: usize, path: &Path) -> Result<(), String> {
+        self.edit_vm(vm, |vm| vm.save_source(path))
+            .unwrap_or(Ok(()))
+    }
+
+    // --------------------connections--------------------
+
+    /// Runs change of connections and records it when connections differ afterwards
+    fn record_wiring<T>(&mut self, change: impl FnOnce(&mut Circuit) -> T) -> T {
+        let before = self.connection_records();
+        let result = change(&mut self.circuit);
+        let after = self.connection_records();
+        if before != after {
+            self.push_command(Command::Wiring { before, after });
+        }
+        result
+    }
+
+    pub fn add_connection(&mut self) -> usize {
+        self.record_wiring(|circuit| circuit.add_connection())
+    }
+
+    pub fn remove_connection(&mut self, id: usize) -> Result<(), CircuitError> {
+        self.record_wiring(|circuit| circuit.remove_connection(id))?;
+        if self.wiring == WiringMode::Connecting(id) {
+            self.wiring = WiringMode::Idle;
+        }
+        Ok(())
+    }
+
+    /// Connects or disconnects clicked port depending on wiring mode
+    pub fn port_clicked(&mut self, port: PortRef) {
+        let wiring = self.wiring;
+        let result = self.record_wiring(|circuit| match wiring {
+            WiringMode::Idle => Ok(()),
+            WiringMode::Connecting(conn_id) => circuit.connect(conn_id, port),
+            WiringMode::Disconnecting => match circuit.port_connection(&port) {
+                Some(_) => circuit.disconnect(&port),
+                None => Ok(()),
+            },
+        });
+        if let Err(err) = result {
+            ToastsManager::show_info(err.to_string(), 10);
+        }
+    }
+
+    /// Joins ports with wire, e.g. dragged on canvas
+    pub fn wire(&mut self, from: PortRef, to: PortRef) {
+        let result = self.record_wiring(|circuit| circuit.wire(from, to));
+        if let Err(err) = result {
+            ToastsManager::show_info(err.to_string(), 10);
+        }
+    }
+
+    /// Disconnects port from its connection
+    pub fn disconnect_port(&mut self, port: PortRef) {
+        let result = self.record_wiring(|circuit| circuit.disconnect(&port));
+        if let Err(err) = result {
+            ToastsManager::show_info(err.to_string(), 10);
+        }
+    }
+
+    /// Makes connections match records, connections that are the same are left untouched
+    fn restore_connections(&mut self, records: &[ConnectionRecord]) {
+        let current = self.connection_records();
+        for record in current.iter().filter(|record| !records.contains(record)) {
+            if let Err(err) = self.circuit.remove_connection(record.id) {
+                ToastsManager::show_info(err.to_string(), 10);
+            }
+        }
+        for record in records.iter().filter(|record| !current.contains(record)) {
+            self.circuit
+                .insert_connection(record.id, record.name.clone());
+            for port in record.ports.iter() {
+                if let Err(err) = self.circuit.connect(record.id, port.clone()) {
+                    ToastsManager::show_info(err.to_string(), 10);
+                }
+            }
+        }
+        if let WiringMode::Connecting(id) = self.wiring {
+            if !records.iter().any(|record| record.id == id) {
+                self.wiring = WiringMode::Idle;
+            }
+        }
+    }
+
+    // --------------------undo--------------------
+
+    fn push_command(&mut self, command: Command) {
+        self.undo_stack.push(command);
+        self.changed = true;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_stack.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.undo_stack.can_redo()
+    }
+
+    /// Reverts last recorded change
+    pub fn undo(&mut self) {
+        if let Some(command) = self.undo_stack.pop_undo() {
+            self.apply(&command, true);
+            self.undo_stack.undone(command);
+            self.changed = true;
+        }
+    }
+
+    /// Does last undone change again
+    pub fn redo(&mut self) {
+        if let Some(command) = self.undo_stack.pop_redo() {
+            self.apply(&command, false);
+            self.undo_stack.redone(command);
+            self.changed = true;
+        }
+    }
+
+    /// Reverts command when `undo` is true, does it again otherwise
+    fn apply(&mut self, command: &Command, undo: bool) {
+        let result = match command {
+            Command::Add(component) if undo => {
+                self.drop_component(component.kind(), component.id())
+            }
+            Command::Add(component) => {
+                self.insert_component(component.clone());
+                Ok(())
+            }
+            Command::Remove {
+                component,
+                connections,
+            } if undo => {
+                self.insert_component(component.clone());
+                self.restore_connections(connections);
+                Ok(())
+            }
+            Command::Remove { component, .. } => {
+                self.drop_component(component.kind(), component.id())
+            }
+            Command::Clear {
+                components,
+                connections,
+            } if undo => {
+                for component in components {
+                    self.insert_component(component.clone());
+                }
+                self.restore_connections(connections);
+                Ok(())
+            }
+            Command::Clear { .. } => {
+                self.clear_circuit();
+                Ok(())
+            }
+            Command::Rename { target, old, new } => {
+                let name = if undo { old } else { new };
+                self.set_name(*target, name.clone())
+            }
+            Command::Wiring { before, after } => {
+                self.restore_connections(if undo { before } else { after });
+                Ok(())
+            }
+            Command::RamValues {
+                ram,
+                index,
+                old,
+                new,
+            } => {
+                let values = if undo { old } else { new };
+                if let Some(ram) = self.circuit.ram_mut(*ram) {
+                    for (offset, value) in values.iter().enumerate() {
+                        ram.set_value_at_index(index + offset, *value);
+                    }
+                }
+                Ok(())
+            }
+            Command::RamSize { ram, old, new } if undo => {
+                let result = self.circuit.resize_ram(*ram, old.len());
+                if let Some(ram) = self.circuit.ram_mut(*ram) {
+                    for (index, value) in old.iter().enumerate() {
+                        ram.set_value_at_index(index, *value);
+                    }
+                }
+                result
+            }
+            Command::RamSize { ram, new, .. } => self.circuit.resize_ram(*ram, *new),
+            Command::RomData { rom, old, new } => {
+                let data = if undo { old } else { new };
+                self.rom_mut(*rom).map(|rom| rom.set_data(data.clone()))
+            }
+            Command::Settings { vm, old, new } => {
+                let settings = if undo { old } else { new };
+                self.circuit.apply_vm_settings(*vm, settings)
+            }
+            Command::Code { vm, old, new } => {
+                let code = if undo { old } else { new };
+                if let Some(vm) = self.circuit.vm_mut(*vm) {
+                    vm.set_code(code.clone());
+                }
+                Ok(())
+            }
+        };
+        if let Err(err) = result {
+            ToastsManager::show_err(err.to_string(), 10);
+        }
+    }
+
+    /// Stops running vms and disconnects ports so document can be serialized,
+    /// returns ids of vms that have to be resumed by `resume_after_save`
+    pub fn suspend_for_save(&mut self) -> Vec<usize> {
+        let mut to_be_restarted = Vec::new();
+        self.circuit.vms_mut().iter_mut().for_each(|vm| {
+            if vm.read_status() == VmStatus::Running {
+                vm.stop_vm();
+                to_be_restarted.push(vm.get_id())
+            }
+        });
+        // values of connections aren't stored in ports
+        self.circuit.disconnect_ports();
+        to_be_restarted
+    }
+
+    pub fn resume_after_save(&mut self, to_be_restarted: &[usize]) {
+        self.circuit.reconnect_ports();
+        self.circuit
+            .vms_mut()
+            .iter_mut()
+            .filter(|vm| to_be_restarted.contains(&vm.get_id()))
+            .for_each(|vm| vm.resume_vm());
+    }
+
+    // --------------------simulation--------------------
+
+    /// Executes one instruction on every vm that hasn't finished, in order of vms,
+    /// rams are refreshed after each vm
+    pub fn step_all(&mut self) {
+        self.circuit.step_vms(false);
+    }
+
+    pub fn all_vms_finished(&self) -> bool {
+        self.circuit.all_vms_finished()
+    }
+
+    /// Turns lockstep scheduler on or off, vms running on their own threads get stopped
+    pub fn set_lockstep(&mut self, enabled: bool) {
+        self.scheduler.set_enabled(enabled);
+        self.circuit
+            .vms_mut()
+            .iter_mut()
+            .for_each(|vm| vm.set_lockstep(enabled));
+    }
+
+    /// Executes one global tick, with `record_history` it can be stepped back per vm
+    pub fn scheduler_step(&mut self, record_history: bool) {
+        self.circuit.step_vms(record_history);
+        self.scheduler.advance();
+        let tick = self.scheduler.get_tick();
+        self.circuit.advance_devices(None, Some(tick));
+        self.record_connection_values(Some(tick));
+    }
+
+    /// Adds values of connections that changed to their history
+    fn record_connection_values(&mut self, tick: Option<u64>) {
+        let values = self.circuit.connection_values();
+        self.connection_history
+            .retain(|id| values.iter().any(|(conn_id, _)| *conn_id == id));
+        for (conn_id, value) in values {
+            self.connection_history.record(
+                conn_id,
+                ValueChange {
+                    value,
+                    time: self.frame_time,
+                    tick,
+                },
+            );
+        }
+    }
+
+    /// Stops scheduler and resets all vms
+    pub fn scheduler_stop(&mut self) {
+        self.scheduler.stop();
+        self.circuit
+            .vms_mut()
+            .iter_mut()
+            .for_each(|vm| vm.reset_vm());
+    }
+
+    /// Starts every vm, or scheduler in lockstep mode
+    pub fn run_all(&mut self) {
+        if self.scheduler.is_enabled() {
+            self.scheduler.start();
+        } else {
+            self.circuit
+                .vms_mut()
+                .iter_mut()
+                .for_each(|vm| vm.start_vm());
+        }
+    }
+
+    /// Stops every running vm so it can be resumed later
+    pub fn stop_all(&mut self) {
+        self.scheduler.pause();
+        self.circuit
+            .vms_mut()
+            .iter_mut()
+            .filter(|vm| vm.read_status() == VmStatus::Running)
+            .for_each(|vm| vm.stop_vm());
+    }
+
+    pub fn halt_all(&mut self) {
+        self.scheduler.pause();
+        self.circuit
+            .vms_mut()
+            .iter_mut()
+            .filter(|vm| {
+                let status = vm.read_status();
+                status == VmStatus::Running || status == VmStatus::Stopped
+            })
+            .for_each(|vm| vm.halt_vm());
+    }
+
+    /// Stops every vm and executes one instruction on each of them
+    pub fn step_all_vms(&mut self) {
+        self.stop_all();
+        if self.scheduler.is_enabled() {
+            self.scheduler_step(true);
+        } else {
+            self.circuit.step_vms(true);
+        }
+        self.check_watchpoints();
+    }
+
+    /// Executes one instruction of vm and checks watchpoints
+    pub fn step_vm(&mut self, id: usize) -> Result<(), CircuitError> {
+        self.circuit.step_vm(id, true)?;
+        self.check_watchpoints();
+        Ok(())
+    }
+
+    pub fn reset_all(&mut self) {
+        self.stop_all();
+        if self.scheduler.is_enabled() {
+            self.scheduler_stop();
+        } else {
+            self.circuit
+                .vms_mut()
+                .iter_mut()
+                .for_each(|vm| vm.reset_vm());
+        }
+    }
+
+    pub fn zero_all_rams(&mut self) {
+        self.circuit.zero_rams();
+    }
+
+    /// Refreshes rams, runs scheduler and checks breakpoints and watchpoints,
+    /// done every frame for every tab so background tabs keep running.
+    /// `time` is time of frame in seconds, returns how soon ui should be repainted
+    pub fn update(&mut self, time: f64) -> Option<Duration> {
+        self.frame_time = time;
+        self.circuit.refresh_rams();
+
+        // values changed by hand or by devices since last frame don't hit watchpoints
+        self.sync_watchpoints();
+        let watched = !self.watchpoints.is_empty();
+        self.circuit
+            .vms_mut()
+            .iter_mut()
+            .for_each(|vm| vm.set_watched(watched));
+
+        // lockstep scheduler
+        let mut repaint: Vec<Duration> = self.run_scheduler().into_iter().collect();
+
+        // devices react to vms running on their own threads
+        self.circuit.advance_devices(Some(self.frame_time), None);
+        self.circuit.update_devices();
+
+        // values written by vms running on their own threads or by hand
+        self.record_connection_values(None);
+
+        repaint.extend(self.run_checked_vms());
+
+        repaint.extend(self.reload_sources());
+
+        let vms_running = self
+            .circuit
+            .vms()
+            .iter()
+            .any(|vm| vm.read_status() == VmStatus::Running);
+        let devices_running = self
+            .circuit
+            .devices()
+            .iter()
+            .any(|device| device.kind.is_running());
+        if (vms_running && (!self.watchpoints.is_empty() || !self.circuit.devices().is_empty()))
+            || devices_running
+        {
+            repaint.push(Duration::from_millis(10));
+        }
+        repaint.into_iter().min()
+    }
+
+    /// Reloads code of vms whose linked source files changed, checked once per second
+    fn reload_sources(&mut self) -> Option<Duration> {
+        if !self.circuit.vms().iter().any(|vm| vm.reloads_source()) {
+            return None;
+        }
+        let repaint = Some(Duration::from_secs(1));
+        if self.frame_time - self.last_source_check < SOURCE_CHECK_INTERVAL {
+            return repaint;
+        }
+        self.last_source_check = self.frame_time;
+        for vm in self.circuit.vms_mut().iter_mut() {
+            match vm.reload_source_if_changed() {
+                Ok(true) => ToastsManager::show_info(
+                    format!("{}: {}", t!("sva_shell.source_reloaded"), vm.get_name()),
+                    5,
+                ),
+                Ok(false) => {}
+                Err(err) => ToastsManager::show_err(err, 10),
+            }
+        }
+        repaint
+    }
+
+    /// Steps vms in checked run, checking their breakpoints before every instruction
+    fn run_checked_vms(&mut self) -> Option<Duration> {
+        let ids: Vec<usize> = self
+            .circuit
+            .vms()
+            .iter()
+            .filter(|vm| vm.is_checked_run())
+            .map(|vm| vm.get_id())
+            .collect();
+        let now = self.frame_time;
+        for id in ids.iter().copied() {
+            let steps = self.circuit.vm_mut(id).map_or(0, |vm| vm.steps_due(now));
+            for _ in 0..steps {
+                let Some(vm) = self.circuit.vm_mut(id) else {
+                    break;
+                };
+                if vm.is_finished() {
+                    vm.finish_checked_run();
+                    break;
+                }
+                if let Some(line) = vm.hit_breakpoint() {
+                    vm.stop_vm();
+                    show_breakpoint_toast(&vm.get_name(), line);
+                    break;
+                }
+                if let Err(err) = self.circuit.step_vm(id, false) {
+                    ToastsManager::show_err(err.to_string(), 10);
+                    break;
+                }
+                if self.check_watchpoints() {
+                    return None;
+                }
+            }
+        }
+        (!ids.is_empty()).then_some(Duration::from_millis(10))
+    }
+
+    /// Checks breakpoints of all vms before lockstep tick, returns true if any of them was hit
+    fn lockstep_breakpoint_hit(&mut self) -> bool {
+        let mut hit = false;
+        for vm in self
+            .circuit
+            .vms_mut()
+            .iter_mut()
+            .filter(|vm| !vm.is_finished())
+        {
+            if let Some(line) = vm.hit_breakpoint() {
+                show_breakpoint_toast(&vm.get_name(), line);
+                hit = true;
+            }
+        }
+        hit
+    }
+
+    /// Current value of watched register, port, stack depth or ram cell
+    fn watched_value(&self, target: &WatchTarget) -> Option<i32> {
+        match target {
+            WatchTarget::VmRegister { vm_id, name } => {
+                self.circuit.vm(*vm_id)?.watched_register(name)
+            }
+            WatchTarget::VmStackDepth { vm_id } => self.circuit.vm(*vm_id)?.stack_depth(),
+            WatchTarget::RamCell { ram_id, index } => self.circuit.ram(*ram_id)?.get_value(*index),
+        }
+    }
+
+    /// Name of watched value shown in watch window and toasts
+    fn describe_target(&self, target: &WatchTarget) -> String {
+        match target {
+            WatchTarget::VmRegister { vm_id, name } => {
+                format!("{} {}", self.vm_name(*vm_id), name)
+            }
+            WatchTarget::VmStackDepth { vm_id } => {
+                format!("{} {}", self.vm_name(*vm_id), t!("watch.stack_depth"))
+            }
+            WatchTarget::RamCell { ram_id, index } => {
+                let ram_name = self
+                    .circuit
+                    .ram(*ram_id)
+                    .map(|ram| ram.get_name())
+                    .unwrap_or(format!("ram:{}", ram_id));
+                format!("{} [{}]", ram_name, index)
+            }
+        }
+    }
+
+    fn vm_name(&self, id: usize) -> String {
+        self.circuit
+            .vm(id)
+            .map(|vm| vm.get_name())
+            .unwrap_or(format!("vm:{}", id))
+    }
+
+    fn watched_values(&self) -> Vec<Option<i32>> {
+        self.watchpoints
+            .targets()
+            .map(|target| self.watched_value(target))
+            .collect()
+    }
+
+    /// Takes current watched values without hitting watchpoints
+    fn sync_watchpoints(&mut self) {
+        let values = self.watched_values();
+        self.watchpoints.sync(&values);
+    }
+
+    /// Stops every vm when watched value changes or its condition becomes true,
+    /// called after executing instructions, returns true if any watchpoint was hit
+    fn check_watchpoints(&mut self) -> bool {
+        let values = self.watched_values();
+        let hits = self.watchpoints.check(&values);
+        if hits.is_empty() {
+            return false;
+        }
+        self.stop_all();
+        let targets: Vec<WatchTarget> = self.watchpoints.targets().cloned().collect();
+        for index in hits {
+            ToastsManager::show_info(
+                format!(
+                    "{} {} = {}",
+                    t!("toast_info.watchpoint_hit"),
+                    self.describe_target(&targets[index]),
+                    values[index].unwrap_or_default()
+                ),
+                5,
+            );
+        }
+        true
+    }
+
+    /// Executes ticks that are due since last frame
+    fn run_scheduler(&mut self) -> Option<Duration> {
+        if !self.scheduler.is_running() {
+            return None;
+        }
+        let ticks = self.scheduler.ticks_due(self.frame_time);
+        for _ in 0..ticks {
+            if self.lockstep_breakpoint_hit() {
+                self.scheduler.pause();
+                break;
+            }
+            // running ticks aren't stepped back, so they don't pay for history
+            self.scheduler_step(false);
+            if self.check_watchpoints() {
+                break;
+            }
+            if self.all_vms_finished() {
+                self.scheduler.pause();
+                break;
+            }
+        }
+        Some(Duration::from_millis(self.scheduler.get_tick_ms()))
+    }
+
+    /// Name and current value of every watched target, in order of watchpoints
+    pub fn watch_labels(&self) -> Vec<(String, Option<i32>)> {
+        self.watchpoints
+            .targets()
+            .map(|target| (self.describe_target(target), self.watched_value(target)))
+            .collect()
+    }
+
+    /// State of vms, rams and consoles as json, used by headless runner
+    pub fn state_to_json(&self) -> serde_json::Value {
+        let vms: Vec<serde_json::Value> = self
+            .circuit
+            .vms()
+            .iter()
+            .map(|vm| vm.state_to_json())
+            .collect();
+        let rams: Vec<serde_json::Value> = self
+            .circuit
+            .rams()
+            .iter()
+            .map(|ram| {
+                serde_json::json!({
+                    "id": ram.get_id(),
+                    "name": ram.get_name(),
+                    "data": ram.get_data(),
+                })
+            })
+            .collect();
+        let consoles: Vec<serde_json::Value> = self
+            .circuit
+            .devices()
+            .iter()
+            .filter_map(|device| match &device.kind {
+                DeviceKind::Console(console) => Some(serde_json::json!({
+                    "id": device.get_id(),
+                    "name": device.get_name(),
+                    "output": console.output_text(),
+                })),
+                _ => None,
+            })
+            .collect();
+        serde_json::json!({ "vms": vms, "rams": rams, "consoles": consoles })
+    }
+}
+
+/// Builds vm from record, language is set first so code is assembled in it
+fn vm_from_record(record: VmRecord, language: &Language) -> Vm {
+    let mut vm = Vm::new(record.id, record.stack);
+    vm.set_language(language.clone());
+    vm.apply_settings(&VmSettings {
+        name: record.name,
+        stack: record.stack,
+        stack_size: record.stack_size,
+        delay_ms: record.delay_ms,
+    });
+    vm.set_breakpoints(
+        record
+            .breakpoints
+            .into_iter()
+            .map(|bp| Breakpoint::with_condition(bp.line, bp.condition))
+            .collect(),
+    );
+    vm.set_code(record.code);
+    if let Some(source) = record.source {
+        vm.link_source(PathBuf::from(source), record.reload_source);
+    }
+    vm
+}
+
+fn ram_from_record(record: RamRecord) -> Memory {
+    let mut ram = Memory::with_size(record.id, record.size);
+    ram.set_name(record.name);
+    for (index, value) in record.data.into_iter().enumerate() {
+        ram.set_value_at_index(index, value);
+    }
+    ram
+}
+
+fn device_from_record(record: DeviceRecord) -> Device {
+    let kind = match record.settings {
+        DeviceSettings::Console { format } => DeviceKind::Console(Console::with_format(format)),
+        DeviceSettings::Display { width, height } => {
+            DeviceKind::Display(PixelDisplay::new(width, height))
+        }
+        DeviceSettings::Clock {
+            mode,
+            period,
+            unit,
+            running,
+        } => DeviceKind::Clock(Clock::new(mode, period, unit, running)),
+        DeviceSettings::Leds { bits } => DeviceKind::LedBar(LedBar::new(bits)),
+        DeviceSettings::Segments { digits, base } => {
+            DeviceKind::SevenSegment(SevenSegment::new(digits, base))
+        }
+        DeviceSettings::Switches { count, state } => {
+            DeviceKind::Switches(Switches::new(count, state))
+        }
+        DeviceSettings::Buttons { count } => DeviceKind::Buttons(PushButtons::new(count)),
+        DeviceSettings::Keypad => DeviceKind::Keypad(Keypad::default()),
+        DeviceSettings::Rom { format, data } => DeviceKind::Rom(Rom::new(data, format)),
+    };
+    let mut device = Device::new(record.id, kind);
+    device.set_name(record.name);
+    device
+}
+
+fn show_breakpoint_toast(vm_name: &str, line: usize) {
+    ToastsManager::show_info(
+        format!(
+            "{} {}, {}: {}",
+            t!("toast_info.breakpoint_hit"),
+            vm_name,
+            t!("sva_shell.line"),
+            line + 1
+        ),
+        5,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::memory::{DEFAULT_RAM_SIZE, MIN_RAM_SIZE};
+    use crate::project::schema::RamPort;
+
+    #[test]
+    fn ram_resize_is_undone_with_cut_off_values() {
+        let mut document = Document::default();
+        document.add_ram(32);
+        document.set_ram_values(0, 20, vec![5]);
+
+        document.resize_ram(0, MIN_RAM_SIZE).unwrap();
+        assert_eq!(document.circuit.ram(0).unwrap().size(), MIN_RAM_SIZE);
+        assert_eq!(document.circuit.ram(0).unwrap().get_value(20), None);
+        document.undo();
+        assert_eq!(document.circuit.ram(0).unwrap().size(), 32);
+        assert_eq!(document.circuit.ram(0).unwrap().get_value(20), Some(5));
+        document.redo();
+        assert_eq!(document.circuit.ram(0).unwrap().size(), MIN_RAM_SIZE);
+
+        assert_eq!(
+            document.resize_ram(3, MIN_RAM_SIZE),
+            Err(CircuitError::UnknownRam(3))
+        );
+    }
+
+    #[test]
+    fn zeroing_ram_is_undone() {
+        let mut document = Document::default();
+        document.add_ram(MIN_RAM_SIZE);
+        document.set_ram_values(0, 3, vec![7, 8]);
+        document.changed = false;
+
+        document.zero_ram(0);
+        assert!(document.changed);
+        assert_eq!(
+            document.circuit.ram(0).unwrap().get_data(),
+            vec![0; MIN_RAM_SIZE]
+        );
+        document.undo();
+        assert_eq!(document.circuit.ram(0).unwrap().get_value(3), Some(7));
+        assert_eq!(document.circuit.ram(0).unwrap().get_value(4), Some(8));
+    }
+
+    #[test]
+    fn loading_rom_is_undone() {
+        let mut document = Document::default();
+        document.add_device(DeviceKind::Rom(Rom::new(vec![1, 2], Default::default())));
+        document.add_device(DeviceKind::Keypad(Keypad::default()));
+        let rom_data = |document: &Document| match &document.circuit.device(0).unwrap().kind {
+            DeviceKind::Rom(rom) => rom.data().to_vec(),
+            kind => panic!("{} isn't rom", kind.type_name()),
+        };
+
+        document.load_rom(0, vec![7, 8, 9]).unwrap();
+        assert_eq!(rom_data(&document), vec![7, 8, 9]);
+        document.undo();
+        assert_eq!(rom_data(&document), vec![1, 2]);
+        document.redo();
+        assert_eq!(rom_data(&document), vec![7, 8, 9]);
+
+        assert_eq!(
+            document.load_rom(1, vec![1]),
+            Err(CircuitError::UnknownDevice(1))
+        );
+    }
+
+    #[test]
+    fn vm_settings_are_undone() {
+        let mut document = Document::default();
+        document.add_vm(&VmSettings::default());
+        let old = document.circuit.vm(0).unwrap().settings();
+        let conn = document.add_connection();
+        document.wiring = WiringMode::Connecting(conn);
+        document.port_clicked(PortRef::Vm { vm: 0, port: 2 });
+
+        let settings = VmSettings {
+            name: "cpu".to_string(),
+            stack: false,
+            stack_size: old.stack_size,
+            delay_ms: 20,
+        };
+        document.apply_vm_settings(0, &settings).unwrap();
+        assert_eq!(document.circuit.vm(0).unwrap().settings(), settings);
+        document.undo();
+        assert_eq!(document.circuit.vm(0).unwrap().settings(), old);
+        assert!(document.circuit.vm(0).unwrap().has_stack());
+        assert_eq!(
+            document
+                .circuit
+                .port_connection(&PortRef::Vm { vm: 0, port: 2 }),
+            Some(conn)
+        );
+        document.redo();
+        assert_eq!(document.circuit.vm(0).unwrap().get_name(), "cpu");
+
+        assert_eq!(
+            document.apply_vm_settings(4, &settings),
+            Err(CircuitError::UnknownVm(4))
+        );
+    }
+
+    #[test]
+    fn breakpoints_and_source_link_mark_document_changed() {
+        let mut document = Document::default();
+        document.add_vm(&VmSettings::default());
+        document.changed = false;
+
+        document.toggle_breakpoint(0, 2);
+        assert!(document.changed);
+        assert!(document.circuit.vm(0).unwrap().has_breakpoint(2));
+
+        document.changed = false;
+        document.unlink_source(0);
+        assert!(document.changed);
+
+        document.changed = false;
+        document.toggle_breakpoint(3, 2);
+        assert!(!document.changed);
+    }
+
+    #[test]
+    fn document_keeps_language_of_its_project() {
+        let mut project = Document::default().to_project();
+        assert_eq!(project.language, "en");
+        project.language = "pl".to_string();
+
+        let document = Document::from_project(project);
+        assert_eq!(document.to_project().language, "pl");
+    }
+
+    #[test]
+    fn vm_delay_is_undone() {
+        let mut document = Document::default();
+        document.add_vm(&VmSettings::default());
+        let old = document.circuit.vm(0).unwrap().get_delay_ms();
+        document.changed = false;
+
+        document.set_vm_delay(0, old + 100).unwrap();
+        assert!(document.changed);
+        assert_eq!(document.circuit.vm(0).unwrap().get_delay_ms(), old + 100);
+        document.undo();
+        assert_eq!(document.circuit.vm(0).unwrap().get_delay_ms(), old);
+
+        assert_eq!(document.set_vm_delay(4, 0), Err(CircuitError::UnknownVm(4)));
+    }
+
+    #[test]
+    fn document_undo_and_redo_changes() {
+        let mut document = Document::default();
+        document.add_vm(&VmSettings::default());
+        document.add_ram(DEFAULT_RAM_SIZE);
+        let conn = document.add_connection();
+        document.wiring = WiringMode::Connecting(conn);
+        document.port_clicked(PortRef::Vm { vm: 0, port: 1 });
+        document.port_clicked(PortRef::Ram {
+            ram: 0,
+            port: RamPort::Data,
+        });
+        document.wiring = WiringMode::Idle;
+
+        document.remove_vm(0);
+        assert!(document.circuit.vm(0).is_none());
+        document.undo();
+        assert!(document.circuit.vm(0).is_some());
+        assert_eq!(
+            document
+                .circuit
+                .port_connection(&PortRef::Vm { vm: 0, port: 1 }),
+            Some(conn)
+        );
+        document.redo();
+        assert!(document.circuit.vm(0).is_none());
+        document.undo();
+
+        // disconnecting ram and connecting it again
+        document.undo();
+        assert_eq!(
+            document.circuit.port_connection(&PortRef::Ram {
+                ram: 0,
+                port: RamPort::Data
+            }),
+            None
+        );
+        document.redo();
+        assert_eq!(
+            document.circuit.port_connection(&PortRef::Ram {
+                ram: 0,
+                port: RamPort::Data
+            }),
+            Some(conn)
+        );
+
+        let ram = NameTarget::Component(ComponentKind::Ram, 0);
+        document.rename(ram, "memory".to_string()).unwrap();
+        document.set_ram_values(0, 2, vec![7, 8]);
+        document.undo();
+        assert_eq!(
+            &document.circuit.ram(0).unwrap().get_data()[..4],
+            &[0, 0, 0, 0]
+        );
+        document.undo();
+        assert_eq!(document.circuit.ram(0).unwrap().get_name(), "ram:0");
+        document.redo();
+        document.redo();
+        assert_eq!(document.circuit.ram(0).unwrap().get_name(), "memory");
+        assert_eq!(
+            &document.circuit.ram(0).unwrap().get_data()[..4],
+            &[0, 0, 7, 8]
+        );
+
+        // edits typed one after another are undone together
+        let vm = document.circuit.vm_mut(0).unwrap();
+        vm.set_code("MOV 1 acc".to_string());
+        document.record_code_edit(0, String::new());
+        let vm = document.circuit.vm_mut(0).unwrap();
+        vm.set_code("MOV 12 acc".to_string());
+        document.record_code_edit(0, "MOV 1 acc".to_string());
+        document.undo();
+        assert_eq!(document.circuit.vm(0).unwrap().get_code(), "");
+        assert!(document.can_redo());
+
+        document.clear();
+        assert!(document.circuit.vms().is_empty());
+        assert!(!document.can_redo());
+        document.undo();
+        assert_eq!(document.circuit.vms().len(), 1);
+        assert_eq!(document.circuit.rams().len(), 1);
+        assert_eq!(
+            document
+                .circuit
+                .port_connection(&PortRef::Vm { vm: 0, port: 1 }),
+            Some(conn)
+        );
+    }
+}
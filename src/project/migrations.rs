@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+use simple_virtual_assembler::components::connection::Connection;
+use simple_virtual_assembler::components::ram::Ram;
+use simple_virtual_assembler::language::Language;
+
+use super::reader::Reader;
+use super::schema::{PortRef, CURRENT_VERSION};
+use super::ProjectError;
+
+/// Upgrades json of project by one version
+type Migration = fn(&Reader<'_>) -> Result<Value, ProjectError>;
+
+/// Migrations indexed by version they upgrade from,
+/// version 0 are files exported before projects were versioned (whole serialized ui state)
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [from_unversioned, from_v1];
+
+/// Version of project, files without `version` field are version 0
+pub fn version_of(reader: &Reader<'_>) -> Result<u64, ProjectError> {
+    match reader.opt_field("version")? {
+        Some(version) => version.u64(),
+        None => Ok(0),
+    }
+}
+
+/// Upgrades project json to current version, one migration at a time
+pub fn migrate(mut value: Value) -> Result<Value, ProjectError> {
+    let version = version_of(&Reader::root(&value))?;
+    if version > CURRENT_VERSION {
+        return Err(ProjectError::new(
+            "version",
+            format!(
+                "{} ({} > {})",
+                t!("project.error.newer_version"),
+                version,
+                CURRENT_VERSION
+            ),
+        ));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        value = migration(&Reader::root(&value))?;
+    }
+    Ok(value)
+}
+
+/// Version 0 -> 1, picks project data out of serialized `SvaUI`
+fn from_unversioned(root: &Reader<'_>) -> Result<Value, ProjectError> {
+    let language = match root.opt_field("language")? {
+        Some(language) => language
+            .deserialize::<Language>()?
+            .string_code()
+            .to_string(),
+        None => "en".to_string(),
+    };
+
+    let active_vms = read_visibility(root, "active_vms")?;
+    let active_rams = read_visibility(root, "active_rams")?;
+
+    let mut vms = Vec::new();
+    if let Some(list) = root.opt_field("vms")? {
+        for vm in list.items()? {
+            let id = vm.field("id")?.usize()?;
+            let breakpoints = match vm.opt_field("breakpoints")? {
+                Some(breakpoints) => breakpoints.value().clone(),
+                None => json!([]),
+            };
+            vms.push(json!({
+                "id": id,
+                "name": vm.field("name")?.string()?,
+                "stack": vm.field("stack_present")?.bool()?,
+                "code": vm.field("code")?.string()?,
+                "delay_ms": vm.field("delay_ms")?.u64()?,
+                "visible": active_vms.get(&id).copied().unwrap_or(true),
+                "breakpoints": breakpoints,
+            }));
+        }
+    }
+
+    let mut rams = Vec::new();
+    if let Some(list) = root.opt_field("rams")? {
+        for ram in list.items()? {
+            let id = ram.field("id")?.usize()?;
+            let data = ram
+                .field("ram")?
+                .deserialize::<Ram>()?
+                .get_data_ref()
+                .clone();
+            rams.push(json!({
+                "id": id,
+                "name": ram.field("name")?.string()?,
+                "visible": active_rams.get(&id).copied().unwrap_or(true),
+                "data": data,
+            }));
+        }
+    }
+
+    let mut names = Map::new();
+    if let Some(list) = root.opt_field("conn_names_copies")? {
+        for (id, name) in list.entries()? {
+            names.insert(id, Value::String(name.string()?));
+        }
+    }
+
+    let mut connections = Vec::new();
+    if let Some(list) = root.opt_field("connections_copy")? {
+        for conn in list.items()? {
+            let connection = conn.deserialize::<Connection>()?;
+            let id = connection
+                .get_id()
+                .ok_or_else(|| conn.error(t!("project.error.missing_id")))?;
+            let ports: Vec<Value> = PortRef::from_connection(&connection)
+                .iter()
+                .map(port_to_json)
+                .collect();
+            let name = match names.get(&id.to_string()) {
+                Some(name) => name.clone(),
+                None => json!(format!("conn:{}", id)),
+            };
+            connections.push(json!({
+                "id": id,
+                "name": name,
+                "ports": ports,
+            }));
+        }
+    }
+
+    let scheduler = match root.opt_field("scheduler")? {
+        Some(scheduler) => json!({
+            "lockstep": scheduler.field("enabled")?.bool()?,
+            "tick_ms": scheduler.field("tick_ms")?.u64()?,
+        }),
+        None => json!({ "lockstep": false, "tick_ms": 100 }),
+    };
+
+    Ok(json!({
+        "version": 1,
+        "language": language,
+        "vms": vms,
+        "rams": rams,
+        "connections": connections,
+        "scheduler": scheduler,
+    }))
+}
+
+/// Version 1 -> 2, devices were added
+fn from_v1(root: &Reader<'_>) -> Result<Value, ProjectError> {
+    let mut value = root.value().clone();
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), json!(2));
+        object.insert("devices".to_string(), json!([]));
+    }
+    Ok(value)
+}
+
+/// Reads map of component id to visibility, e.g. `active_vms`
+fn read_visibility(root: &Reader<'_>, name: &str) -> Result<HashMap<usize, bool>, ProjectError> {
+    let mut visibility = HashMap::new();
+    if let Some(map) = root.opt_field(name)? {
+        for (id, visible) in map.entries()? {
+            let id = id
+                .parse::<usize>()
+                .map_err(|_err| visible.error(t!("project.error.bad_id")))?;
+            visibility.insert(id, visible.bool()?);
+        }
+    }
+    Ok(visibility)
+}
+
+/// Port in version 1 layout, written by hand so later schema changes don't affect this migration
+fn port_to_json(port: &PortRef) -> Value {
+    match port {
+        PortRef::Vm { vm, port } => json!({ "vm": vm, "port": port }),
+        PortRef::Ram { ram, port } => json!({ "ram": ram, "port": port.name() }),
+        // devices didn't exist in version 1
+        PortRef::Device { device, port } => json!({ "device": device, "port": port }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::schema::Project;
+
+    #[test]
+    fn unversioned_file_is_migrated_to_current_version() {
+        let value = json!({
+            "language": serde_json::to_value(Language::Pl).unwrap(),
+            "active_vms": { "0": false },
+            "vms": [{
+                "id": 0,
+                "name": "main",
+                "stack_present": true,
+                "code": "MOV 1 acc",
+                "delay_ms": 10,
+            }],
+            "conn_names_copies": {},
+            "scheduler": { "enabled": true, "tick_ms": 50 },
+        });
+        let migrated = migrate(value).unwrap();
+        assert_eq!(migrated["version"], json!(CURRENT_VERSION));
+        assert_eq!(migrated["language"], json!("pl"));
+        assert_eq!(
+            migrated["vms"],
+            json!([{
+                "id": 0,
+                "name": "main",
+                "stack": true,
+                "code": "MOV 1 acc",
+                "delay_ms": 10,
+                "visible": false,
+                "breakpoints": [],
+            }])
+        );
+        assert_eq!(migrated["rams"], json!([]));
+        assert_eq!(migrated["devices"], json!([]));
+        assert_eq!(
+            migrated["scheduler"],
+            json!({ "lockstep": true, "tick_ms": 50 })
+        );
+
+        let project = Project::read(&Reader::root(&migrated)).unwrap();
+        assert_eq!(project.vms.len(), 1);
+        assert!(!project.vms[0].visible);
+        assert!(project.scheduler.lockstep);
+    }
+
+    #[test]
+    fn unversioned_file_defaults_language_and_scheduler() {
+        let migrated = migrate(json!({})).unwrap();
+        assert_eq!(migrated["language"], json!("en"));
+        assert_eq!(
+            migrated["scheduler"],
+            json!({ "lockstep": false, "tick_ms": 100 })
+        );
+    }
+
+    #[test]
+    fn current_version_is_left_unchanged() {
+        let value = json!({ "version": CURRENT_VERSION, "language": "en", "vms": [] });
+        assert_eq!(migrate(value.clone()).unwrap(), value);
+    }
+
+    #[test]
+    fn errors_point_at_invalid_field() {
+        let err = migrate(json!({ "version": CURRENT_VERSION + 1 })).unwrap_err();
+        assert_eq!(err.path, "version");
+
+        let err = migrate(json!({ "version": "two" })).unwrap_err();
+        assert_eq!(err.path, "version");
+
+        let err = migrate(json!({
+            "vms": [{ "id": 0, "name": "a", "stack_present": false, "delay_ms": 1 }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "vms[0].code");
+
+        let err = migrate(json!({ "active_vms": { "x": true } })).unwrap_err();
+        assert_eq!(err.path, "active_vms.x");
+    }
+}
@@ -0,0 +1,61 @@
+//! Versioned project files
+//!
+//! Projects are saved in their own schema instead of serialized ui state,
+//! files written by older versions are upgraded by migrations before being read
+//!
+//! `Document` is project opened in tab, with its undo history, independent of egui
+use std::fmt;
+
+use serde_json::Value;
+
+pub mod circuit;
+pub mod document;
+pub mod migrations;
+pub mod reader;
+pub mod schema;
+pub mod undo;
+
+use reader::Reader;
+use schema::Project;
+
+/// Error reading project file, with path to part of file that is invalid e.g. `vms[1].code`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectError {
+    pub path: String,
+    pub message: String,
+}
+
+impl ProjectError {
+    pub fn new(path: &str, message: String) -> Self {
+        Self {
+            path: path.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+/// Parses project file of any known version
+pub fn load(data: &str) -> Result<Project, ProjectError> {
+    let value: Value = serde_json::from_str(data).map_err(|err| {
+        ProjectError::new("", format!("{}: {}", t!("error.import.bad_json"), err))
+    })?;
+    let value = migrations::migrate(value)?;
+    Project::read(&Reader::root(&value))
+}
+
+/// Writes project in current version
+pub fn save(project: &Project) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(project)
+}
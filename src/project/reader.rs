@@ -0,0 +1,156 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::ProjectError;
+
+/// Part of parsed json together with path leading to it, e.g. `vms[1].code`,
+/// so errors can point at exact place in file
+pub struct Reader<'a> {
+    path: String,
+    value: &'a Value,
+}
+
+impl<'a> Reader<'a> {
+    pub fn root(value: &'a Value) -> Self {
+        Self {
+            path: String::new(),
+            value,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn value(&self) -> &'a Value {
+        self.value
+    }
+
+    pub fn error(&self, message: String) -> ProjectError {
+        ProjectError::new(&self.path, message)
+    }
+
+    fn expected(&self, kind: &str) -> ProjectError {
+        self.error(format!(
+            "{} {}, {} {}",
+            t!("project.error.expected"),
+            kind,
+            t!("project.error.found"),
+            kind_of(self.value)
+        ))
+    }
+
+    fn child_path(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.path, name)
+        }
+    }
+
+    fn child(&self, name: &str, value: &'a Value) -> Reader<'a> {
+        Reader {
+            path: self.child_path(name),
+            value,
+        }
+    }
+
+    /// Field of object that has to be present
+    pub fn field(&self, name: &str) -> Result<Reader<'a>, ProjectError> {
+        match self.opt_field(name)? {
+            Some(field) => Ok(field),
+            None => Err(ProjectError::new(
+                &self.child_path(name),
+                t!("project.error.missing_field"),
+            )),
+        }
+    }
+
+    /// Field of object, None when it is missing or null
+    pub fn opt_field(&self, name: &str) -> Result<Option<Reader<'a>>, ProjectError> {
+        let object = self
+            .value
+            .as_object()
+            .ok_or_else(|| self.expected("object"))?;
+        Ok(object
+            .get(name)
+            .filter(|value| !value.is_null())
+            .map(|value| self.child(name, value)))
+    }
+
+    pub fn items(&self) -> Result<Vec<Reader<'a>>, ProjectError> {
+        let array = self
+            .value
+            .as_array()
+            .ok_or_else(|| self.expected("array"))?;
+        Ok(array
+            .iter()
+            .enumerate()
+            .map(|(index, value)| Reader {
+                path: format!("{}[{}]", self.path, index),
+                value,
+            })
+            .collect())
+    }
+
+    /// Key-value pairs of object, used for maps like connection names
+    pub fn entries(&self) -> Result<Vec<(String, Reader<'a>)>, ProjectError> {
+        let object = self
+            .value
+            .as_object()
+            .ok_or_else(|| self.expected("object"))?;
+        Ok(object
+            .iter()
+            .map(|(key, value)| (key.clone(), self.child(key, value)))
+            .collect())
+    }
+
+    pub fn string(&self) -> Result<String, ProjectError> {
+        self.value
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| self.expected("string"))
+    }
+
+    pub fn bool(&self) -> Result<bool, ProjectError> {
+        self.value.as_bool().ok_or_else(|| self.expected("bool"))
+    }
+
+    pub fn u64(&self) -> Result<u64, ProjectError> {
+        self.value
+            .as_u64()
+            .ok_or_else(|| self.expected("unsigned integer"))
+    }
+
+    pub fn usize(&self) -> Result<usize, ProjectError> {
+        self.u64()?
+            .try_into()
+            .map_err(|_err| self.error(t!("project.error.out_of_range")))
+    }
+
+    pub fn i32(&self) -> Result<i32, ProjectError> {
+        let value = self
+            .value
+            .as_i64()
+            .ok_or_else(|| self.expected("integer"))?;
+        value
+            .try_into()
+            .map_err(|_err| self.error(t!("project.error.out_of_range")))
+    }
+
+    /// Deserializes value with serde, used for types from simple_virtual_assembler
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, ProjectError> {
+        serde_json::from_value(self.value.clone()).map_err(|err| self.error(err.to_string()))
+    }
+}
+
+fn kind_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
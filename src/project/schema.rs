@@ -0,0 +1,784 @@
+use serde::Serialize;
+use simple_virtual_assembler::components::connection::Connection;
+use simple_virtual_assembler::language::Language;
+
+use crate::model::clock::{Clock, ClockMode, ClockUnit};
+use crate::model::console::Console;
+use crate::model::keypad::Keypad;
+use crate::model::led_bar::LedBar;
+use crate::model::memory::{DEFAULT_RAM_SIZE, MAX_RAM_SIZE, MIN_RAM_SIZE};
+use crate::model::pixel_display::PixelDisplay;
+use crate::model::push_buttons::PushButtons;
+use crate::model::rom::{Rom, MAX_ROM_SIZE};
+use crate::model::seven_segment::SevenSegment;
+use crate::model::switches::Switches;
+use crate::model::value_format::ValueFormat;
+use crate::model::vm::{DEFAULT_STACK_SIZE, MAX_STACK_SIZE};
+
+use super::reader::Reader;
+use super::ProjectError;
+
+/// Version of project files written by this build
+pub const CURRENT_VERSION: u64 = 2;
+
+/// Number of ports of every vm
+pub const VM_PORTS: usize = 6;
+
+/// Languages that can be stored in project
+const LANGUAGES: [&str; 2] = ["en", "pl"];
+
+/// Project file, kept separate from ui state so ui can change without breaking saved files
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Project {
+    pub version: u64,
+    /// Language code, `en` or `pl`
+    pub language: String,
+    pub vms: Vec<VmRecord>,
+    pub rams: Vec<RamRecord>,
+    pub devices: Vec<DeviceRecord>,
+    pub connections: Vec<ConnectionRecord>,
+    pub scheduler: SchedulerRecord,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VmRecord {
+    pub id: usize,
+    pub name: String,
+    pub stack: bool,
+    /// Number of values stack can hold
+    pub stack_size: usize,
+    pub code: String,
+    pub delay_ms: u64,
+    /// Is window shown
+    pub visible: bool,
+    pub breakpoints: Vec<BreakpointRecord>,
+    /// Source file code was opened from or saved to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Code is reloaded when source file changes
+    pub reload_source: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BreakpointRecord {
+    pub line: usize,
+    pub condition: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RamRecord {
+    pub id: usize,
+    pub name: String,
+    /// Is window shown
+    pub visible: bool,
+    /// Number of cells
+    pub size: usize,
+    pub data: Vec<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceRecord {
+    pub id: usize,
+    pub name: String,
+    /// Is window shown
+    pub visible: bool,
+    #[serde(flatten)]
+    pub settings: DeviceSettings,
+}
+
+/// Kind of device with its settings, written as `"kind": "console"` next to other fields of device
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DeviceSettings {
+    Console {
+        format: ValueFormat,
+    },
+    Display {
+        width: usize,
+        height: usize,
+    },
+    Clock {
+        mode: ClockMode,
+        period: u64,
+        unit: ClockUnit,
+        running: bool,
+    },
+    Leds {
+        bits: usize,
+    },
+    Segments {
+        digits: usize,
+        base: u32,
+    },
+    Switches {
+        count: usize,
+        state: u32,
+    },
+    Buttons {
+        count: usize,
+    },
+    Keypad,
+    Rom {
+        format: ValueFormat,
+        data: Vec<i32>,
+    },
+}
+
+impl DeviceSettings {
+    pub fn port_count(&self) -> usize {
+        match self {
+            DeviceSettings::Console { .. } => Console::PORTS.len(),
+            DeviceSettings::Display { .. } => PixelDisplay::PORTS.len(),
+            DeviceSettings::Clock { .. } => Clock::PORTS.len(),
+            DeviceSettings::Leds { .. } => LedBar::PORTS.len(),
+            DeviceSettings::Segments { .. } => SevenSegment::PORTS.len(),
+            DeviceSettings::Switches { .. } => Switches::PORTS.len(),
+            DeviceSettings::Buttons { .. } => PushButtons::PORTS.len(),
+            DeviceSettings::Keypad => Keypad::PORTS.len(),
+            DeviceSettings::Rom { .. } => Rom::PORTS.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectionRecord {
+    pub id: usize,
+    pub name: String,
+    pub ports: Vec<PortRef>,
+}
+
+/// Port connected to connection, written as `{"vm": 0, "port": 3}`, `{"ram": 1, "port": "data"}`
+/// or `{"device": 2, "port": 0}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum PortRef {
+    Vm { vm: usize, port: usize },
+    Ram { ram: usize, port: RamPort },
+    Device { device: usize, port: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RamPort {
+    Index,
+    Data,
+    Mode,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchedulerRecord {
+    pub lockstep: bool,
+    pub tick_ms: u64,
+}
+
+impl Default for SchedulerRecord {
+    fn default() -> Self {
+        Self {
+            lockstep: false,
+            tick_ms: 100,
+        }
+    }
+}
+
+impl RamPort {
+    const ALL: [(&'static str, RamPort); 3] = [
+        ("index", RamPort::Index),
+        ("data", RamPort::Data),
+        ("mode", RamPort::Mode),
+    ];
+
+    /// Index used by `Connection::get_connected_rams`
+    pub fn from_index(index: usize) -> Option<RamPort> {
+        RamPort::ALL.get(index).map(|(_, port)| *port)
+    }
+
+    pub fn name(&self) -> &'static str {
+        RamPort::ALL
+            .iter()
+            .find(|(_, port)| port == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("index")
+    }
+}
+
+impl PortRef {
+    /// Id under which port is stored in connection, same as ones created by ui
+    pub fn port_id(&self) -> String {
+        match self {
+            PortRef::Vm { vm, port } => format!("{}P{}", vm, port),
+            PortRef::Ram { ram, port } => format!("R{}:{}", ram, port.name()),
+            PortRef::Device { device, port } => format!("{}D{}", device, port),
+        }
+    }
+
+    /// Ports stored in connection
+    pub fn from_connection(conn: &Connection) -> Vec<PortRef> {
+        let vm_ports = conn
+            .get_connected_vms_and_ports('P')
+            .into_iter()
+            .filter_map(|(vm, port)| {
+                Some(PortRef::Vm {
+                    vm: usize::try_from(vm).ok()?,
+                    port,
+                })
+            });
+        let ram_ports = conn
+            .get_connected_rams()
+            .into_iter()
+            .filter_map(|(ram, port)| {
+                Some(PortRef::Ram {
+                    ram,
+                    port: RamPort::from_index(port)?,
+                })
+            });
+        let device_ports = conn
+            .get_connected_vms_and_ports('D')
+            .into_iter()
+            .filter_map(|(device, port)| {
+                Some(PortRef::Device {
+                    device: usize::try_from(device).ok()?,
+                    port,
+                })
+            });
+        vm_ports.chain(ram_ports).chain(device_ports).collect()
+    }
+
+    fn read(reader: &Reader<'_>) -> Result<PortRef, ProjectError> {
+        let port_field = reader.field("port")?;
+        if let Some(vm) = reader.opt_field("vm")? {
+            return Ok(PortRef::Vm {
+                vm: vm.usize()?,
+                port: port_field.usize()?,
+            });
+        }
+        if let Some(ram) = reader.opt_field("ram")? {
+            let name = port_field.string()?;
+            let port = RamPort::ALL
+                .iter()
+                .find(|(port_name, _)| *port_name == name)
+                .map(|(_, port)| *port)
+                .ok_or_else(|| {
+                    port_field.error(format!(
+                        "{}: {}",
+                        t!("project.error.unknown_ram_port"),
+                        name
+                    ))
+                })?;
+            return Ok(PortRef::Ram {
+                ram: ram.usize()?,
+                port,
+            });
+        }
+        if let Some(device) = reader.opt_field("device")? {
+            return Ok(PortRef::Device {
+                device: device.usize()?,
+                port: port_field.usize()?,
+            });
+        }
+        Err(reader.error(t!("project.error.missing_component")))
+    }
+}
+
+/// Reads optional field, using default when it's missing
+fn read_or<'a, T>(
+    reader: &Reader<'a>,
+    name: &str,
+    default: T,
+    read: impl Fn(&Reader<'a>) -> Result<T, ProjectError>,
+) -> Result<T, ProjectError> {
+    match reader.opt_field(name)? {
+        Some(field) => read(&field),
+        None => Ok(default),
+    }
+}
+
+/// Reads optional array field, missing array is empty
+fn read_list<'a, T>(
+    reader: &Reader<'a>,
+    name: &str,
+    read: impl Fn(&Reader<'a>) -> Result<T, ProjectError>,
+) -> Result<Vec<T>, ProjectError> {
+    match reader.opt_field(name)? {
+        Some(field) => field.items()?.iter().map(read).collect(),
+        None => Ok(Vec::new()),
+    }
+}
+
+impl VmRecord {
+    fn read(reader: &Reader<'_>) -> Result<VmRecord, ProjectError> {
+        let id = reader.field("id")?.usize()?;
+        Ok(VmRecord {
+            id,
+            name: read_or(reader, "name", format!("vm:{}", id), Reader::string)?,
+            stack: read_or(reader, "stack", true, Reader::bool)?,
+            stack_size: read_or(reader, "stack_size", DEFAULT_STACK_SIZE, Reader::usize)?,
+            code: read_or(reader, "code", String::new(), Reader::string)?,
+            delay_ms: read_or(reader, "delay_ms", 1000, Reader::u64)?,
+            visible: read_or(reader, "visible", true, Reader::bool)?,
+            breakpoints: read_list(reader, "breakpoints", |bp| {
+                Ok(BreakpointRecord {
+                    line: bp.field("line")?.usize()?,
+                    condition: read_or(bp, "condition", String::new(), Reader::string)?,
+                })
+            })?,
+            source: read_or(reader, "source", None, |source| source.string().map(Some))?,
+            reload_source: read_or(reader, "reload_source", false, Reader::bool)?,
+        })
+    }
+}
+
+impl RamRecord {
+    fn read(reader: &Reader<'_>) -> Result<RamRecord, ProjectError> {
+        let id = reader.field("id")?.usize()?;
+        Ok(RamRecord {
+            id,
+            name: read_or(reader, "name", format!("ram:{}", id), Reader::string)?,
+            visible: read_or(reader, "visible", true, Reader::bool)?,
+            size: read_or(reader, "size", DEFAULT_RAM_SIZE, Reader::usize)?,
+            data: read_list(reader, "data", Reader::i32)?,
+        })
+    }
+}
+
+impl DeviceRecord {
+    fn read(reader: &Reader<'_>) -> Result<DeviceRecord, ProjectError> {
+        let id = reader.field("id")?.usize()?;
+        let kind_field = reader.field("kind")?;
+        let kind = kind_field.string()?;
+        let settings = match kind.as_str() {
+            "console" => DeviceSettings::Console {
+                format: read_or(
+                    reader,
+                    "format",
+                    ValueFormat::Unicode,
+                    Reader::deserialize::<ValueFormat>,
+                )?,
+            },
+            "display" => DeviceSettings::Display {
+                width: read_or(reader, "width", 32, Reader::usize)?,
+                height: read_or(reader, "height", 32, Reader::usize)?,
+            },
+            "clock" => {
+                let default = Clock::default();
+                DeviceSettings::Clock {
+                    mode: read_or(
+                        reader,
+                        "mode",
+                        default.mode,
+                        Reader::deserialize::<ClockMode>,
+                    )?,
+                    period: read_or(reader, "period", default.period, Reader::u64)?,
+                    unit: read_or(
+                        reader,
+                        "unit",
+                        default.unit,
+                        Reader::deserialize::<ClockUnit>,
+                    )?,
+                    running: read_or(reader, "running", default.running, Reader::bool)?,
+                }
+            }
+            "leds" => DeviceSettings::Leds {
+                bits: read_or(reader, "bits", 8, Reader::usize)?,
+            },
+            "segments" => DeviceSettings::Segments {
+                digits: read_or(reader, "digits", 4, Reader::usize)?,
+                base: read_or(reader, "base", 10, Reader::deserialize::<u32>)?,
+            },
+            "switches" => DeviceSettings::Switches {
+                count: read_or(reader, "count", 8, Reader::usize)?,
+                state: read_or(reader, "state", 0, Reader::deserialize::<u32>)?,
+            },
+            "buttons" => DeviceSettings::Buttons {
+                count: read_or(reader, "count", 4, Reader::usize)?,
+            },
+            "keypad" => DeviceSettings::Keypad,
+            "rom" => {
+                let data = read_list(reader, "data", Reader::i32)?;
+                if data.len() > MAX_ROM_SIZE {
+                    return Err(reader.field("data")?.error(format!(
+                        "{} ({} > {})",
+                        t!("project.error.too_many_values"),
+                        data.len(),
+                        MAX_ROM_SIZE
+                    )));
+                }
+                DeviceSettings::Rom {
+                    format: read_or(
+                        reader,
+                        "format",
+                        ValueFormat::default(),
+                        Reader::deserialize::<ValueFormat>,
+                    )?,
+                    data,
+                }
+            }
+            _ => {
+                return Err(kind_field.error(format!(
+                    "{}: {}",
+                    t!("project.error.unknown_device"),
+                    kind
+                )))
+            }
+        };
+        Ok(DeviceRecord {
+            id,
+            name: read_or(reader, "name", format!("{}:{}", kind, id), Reader::string)?,
+            visible: read_or(reader, "visible", true, Reader::bool)?,
+            settings,
+        })
+    }
+}
+
+impl ConnectionRecord {
+    fn read(reader: &Reader<'_>) -> Result<ConnectionRecord, ProjectError> {
+        let id = reader.field("id")?.usize()?;
+        Ok(ConnectionRecord {
+            id,
+            name: read_or(reader, "name", format!("conn:{}", id), Reader::string)?,
+            ports: read_list(reader, "ports", PortRef::read)?,
+        })
+    }
+}
+
+impl Project {
+    /// Language code parsed for assembler, unknown codes fall back to english
+    pub fn assembler_language(&self) -> Language {
+        match self.language.as_str() {
+            "pl" => Language::Pl,
+            _ => Language::En,
+        }
+    }
+
+    /// Reads project in current version, checks that ids are unique
+    /// and that connections only reference existing components
+    pub fn read(reader: &Reader<'_>) -> Result<Project, ProjectError> {
+        let language = read_or(reader, "language", "en".to_string(), Reader::string)?;
+        if !LANGUAGES.contains(&language.as_str()) {
+            let field = reader.field("language")?;
+            return Err(field.error(format!(
+                "{}: {}",
+                t!("project.error.unknown_language"),
+                language
+            )));
+        }
+        let project = Project {
+            version: reader.field("version")?.u64()?,
+            language,
+            vms: read_list(reader, "vms", VmRecord::read)?,
+            rams: read_list(reader, "rams", RamRecord::read)?,
+            devices: read_list(reader, "devices", DeviceRecord::read)?,
+            connections: read_list(reader, "connections", ConnectionRecord::read)?,
+            scheduler: read_or(reader, "scheduler", SchedulerRecord::default(), |s| {
+                let default = SchedulerRecord::default();
+                Ok(SchedulerRecord {
+                    lockstep: read_or(s, "lockstep", default.lockstep, Reader::bool)?,
+                    tick_ms: read_or(s, "tick_ms", default.tick_ms, Reader::u64)?,
+                })
+            })?,
+        };
+        project.validate()?;
+        Ok(project)
+    }
+
+    fn validate(&self) -> Result<(), ProjectError> {
+        let vm_ids: Vec<usize> = self.vms.iter().map(|vm| vm.id).collect();
+        let ram_ids: Vec<usize> = self.rams.iter().map(|ram| ram.id).collect();
+        let device_ids: Vec<usize> = self.devices.iter().map(|device| device.id).collect();
+        let conn_ids: Vec<usize> = self.connections.iter().map(|conn| conn.id).collect();
+        check_unique("vms", &vm_ids)?;
+        check_unique("rams", &ram_ids)?;
+        check_unique("devices", &device_ids)?;
+        check_unique("connections", &conn_ids)?;
+
+        for (index, vm) in self.vms.iter().enumerate() {
+            if !(1..=MAX_STACK_SIZE).contains(&vm.stack_size) {
+                return Err(ProjectError::new(
+                    &format!("vms[{}].stack_size", index),
+                    format!(
+                        "{} (1..={})",
+                        t!("project.error.out_of_range"),
+                        MAX_STACK_SIZE
+                    ),
+                ));
+            }
+        }
+
+        for (index, ram) in self.rams.iter().enumerate() {
+            if !(MIN_RAM_SIZE..=MAX_RAM_SIZE).contains(&ram.size) {
+                return Err(ProjectError::new(
+                    &format!("rams[{}].size", index),
+                    format!(
+                        "{} ({}..={})",
+                        t!("project.error.out_of_range"),
+                        MIN_RAM_SIZE,
+                        MAX_RAM_SIZE
+                    ),
+                ));
+            }
+            if ram.data.len() > ram.size {
+                return Err(ProjectError::new(
+                    &format!("rams[{}].data", index),
+                    format!(
+                        "{} ({} > {})",
+                        t!("project.error.too_many_values"),
+                        ram.data.len(),
+                        ram.size
+                    ),
+                ));
+            }
+        }
+
+        let mut used_ports = Vec::new();
+        for (conn_index, conn) in self.connections.iter().enumerate() {
+            for (port_index, port) in conn.ports.iter().enumerate() {
+                let path = format!("connections[{}].ports[{}]", conn_index, port_index);
+                match port {
+                    PortRef::Vm { vm, port } => {
+                        if !vm_ids.contains(vm) {
+                            return Err(ProjectError::new(
+                                &path,
+                                format!("{}: {}", t!("project.error.unknown_vm"), vm),
+                            ));
+                        }
+                        if *port >= VM_PORTS {
+                            return Err(ProjectError::new(
+                                &format!("{}.port", path),
+                                t!("project.error.out_of_range"),
+                            ));
+                        }
+                    }
+                    PortRef::Ram { ram, .. } => {
+                        if !ram_ids.contains(ram) {
+                            return Err(ProjectError::new(
+                                &path,
+                                format!("{}: {}", t!("project.error.unknown_ram"), ram),
+                            ));
+                        }
+                    }
+                    PortRef::Device { device, port } => {
+                        let Some(record) = self.devices.iter().find(|d| d.id == *device) else {
+                            return Err(ProjectError::new(
+                                &path,
+                                format!("{}: {}", t!("project.error.unknown_device_id"), device),
+                            ));
+                        };
+                        if *port >= record.settings.port_count() {
+                            return Err(ProjectError::new(
+                                &format!("{}.port", path),
+                                t!("project.error.out_of_range"),
+                            ));
+                        }
+                    }
+                }
+                let port_id = port.port_id();
+                if used_ports.contains(&port_id) {
+                    return Err(ProjectError::new(
+                        &path,
+                        format!("{}: {}", t!("project.error.port_used_twice"), port_id),
+                    ));
+                }
+                used_ports.push(port_id);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_unique(list: &str, ids: &[usize]) -> Result<(), ProjectError> {
+    for (index, id) in ids.iter().enumerate() {
+        if ids[..index].contains(id) {
+            return Err(ProjectError::new(
+                &format!("{}[{}].id", list, index),
+                format!("{}: {}", t!("project.error.duplicate_id"), id),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{load, save};
+
+    fn load_json(value: serde_json::Value) -> Result<Project, ProjectError> {
+        load(&value.to_string())
+    }
+
+    fn vm(id: usize) -> serde_json::Value {
+        serde_json::json!({ "id": id, "code": "MOV 1 acc" })
+    }
+
+    #[test]
+    fn missing_fields_get_defaults() {
+        let project = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "vms": [vm(3)],
+            "rams": [{ "id": 1 }],
+        }))
+        .unwrap();
+        assert_eq!(project.language, "en");
+        assert_eq!(project.vms[0].name, "vm:3");
+        assert_eq!(project.vms[0].stack_size, DEFAULT_STACK_SIZE);
+        assert_eq!(project.rams[0].size, DEFAULT_RAM_SIZE);
+        assert_eq!(project.scheduler, SchedulerRecord::default());
+    }
+
+    #[test]
+    fn duplicate_ids_are_rejected() {
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "vms": [vm(0), vm(1), vm(0)],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "vms[2].id");
+
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "connections": [{ "id": 4 }, { "id": 4 }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "connections[1].id");
+    }
+
+    #[test]
+    fn ports_must_reference_existing_components() {
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "vms": [vm(0)],
+            "connections": [{ "id": 0, "ports": [{ "vm": 0, "port": 0 }, { "vm": 5, "port": 1 }] }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "connections[0].ports[1]");
+
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "connections": [{ "id": 0, "ports": [{ "ram": 2, "port": "data" }] }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "connections[0].ports[0]");
+
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "devices": [{ "id": 0, "kind": "keypad" }],
+            "connections": [{ "id": 0, "ports": [{ "device": 0, "port": 9 }] }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "connections[0].ports[0].port");
+    }
+
+    #[test]
+    fn port_can_be_used_only_once() {
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "vms": [vm(0)],
+            "connections": [
+                { "id": 0, "ports": [{ "vm": 0, "port": 2 }] },
+                { "id": 1, "ports": [{ "vm": 0, "port": 2 }] },
+            ],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "connections[1].ports[0]");
+    }
+
+    #[test]
+    fn invalid_values_point_at_field() {
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "vms": [{ "id": "first" }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "vms[0].id");
+
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "language": "de",
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "language");
+
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "rams": [{ "id": 0, "size": MIN_RAM_SIZE, "data": vec![0; MIN_RAM_SIZE + 1] }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "rams[0].data");
+
+        let err = load_json(serde_json::json!({
+            "version": CURRENT_VERSION,
+            "devices": [{ "id": 0, "kind": "printer" }],
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "devices[0].kind");
+
+        let err = load("{ not json").unwrap_err();
+        assert_eq!(err.path, "");
+    }
+
+    #[test]
+    fn saved_project_is_loaded_unchanged() {
+        let project = Project {
+            version: CURRENT_VERSION,
+            language: "pl".to_string(),
+            vms: vec![VmRecord {
+                id: 2,
+                name: "main".to_string(),
+                stack: true,
+                stack_size: 64,
+                code: "MOV 1 acc\nMOV acc p0".to_string(),
+                delay_ms: 5,
+                visible: false,
+                breakpoints: vec![BreakpointRecord {
+                    line: 1,
+                    condition: "acc > 0".to_string(),
+                }],
+                source: Some("main.sva".to_string()),
+                reload_source: true,
+            }],
+            rams: vec![RamRecord {
+                id: 0,
+                name: "memory".to_string(),
+                visible: true,
+                size: DEFAULT_RAM_SIZE,
+                data: vec![1, -2, 3],
+            }],
+            devices: vec![
+                DeviceRecord {
+                    id: 0,
+                    name: "out".to_string(),
+                    visible: true,
+                    settings: DeviceSettings::Console {
+                        format: ValueFormat::Hex,
+                    },
+                },
+                DeviceRecord {
+                    id: 1,
+                    name: "table".to_string(),
+                    visible: false,
+                    settings: DeviceSettings::Rom {
+                        format: ValueFormat::Dec,
+                        data: vec![i32::MIN, 0, i32::MAX],
+                    },
+                },
+            ],
+            connections: vec![ConnectionRecord {
+                id: 7,
+                name: "bus".to_string(),
+                ports: vec![
+                    PortRef::Vm { vm: 2, port: 0 },
+                    PortRef::Ram {
+                        ram: 0,
+                        port: RamPort::Data,
+                    },
+                    PortRef::Device { device: 0, port: 0 },
+                ],
+            }],
+            scheduler: SchedulerRecord {
+                lockstep: true,
+                tick_ms: 25,
+            },
+        };
+        let saved = save(&project).unwrap();
+        assert_eq!(load(&saved).unwrap(), project);
+    }
+}
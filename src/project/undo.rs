@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+
+use crate::model::vm::VmSettings;
+
+use super::document::{ComponentCopy, ComponentKind};
+use super::schema::ConnectionRecord;
+
+/// How many changes of document can be undone
+pub const UNDO_CAPACITY: usize = 100;
+
+/// Edits of the same code made within this many seconds are undone together
+pub const CODE_MERGE_SECONDS: f64 = 1.0;
+
+/// Component or connection whose name was changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NameTarget {
+    Component(ComponentKind, usize),
+    Connection(usize),
+}
+
+/// Change of document that can be undone and done again
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Component was added or pasted, copy holds it as it was added
+    Add(ComponentCopy),
+    /// Component was removed, connections are as they were before removing it
+    Remove {
+        component: ComponentCopy,
+        connections: Vec<ConnectionRecord>,
+    },
+    /// Every component and connection was removed
+    Clear {
+        components: Vec<ComponentCopy>,
+        connections: Vec<ConnectionRecord>,
+    },
+    Rename {
+        target: NameTarget,
+        old: String,
+        new: String,
+    },
+    /// Connections were added, removed, connected or disconnected
+    Wiring {
+        before: Vec<ConnectionRecord>,
+        after: Vec<ConnectionRecord>,
+    },
+    /// Cells of ram starting at index were overwritten
+    RamValues {
+        ram: usize,
+        index: usize,
+        old: Vec<i32>,
+        new: Vec<i32>,
+    },
+    /// Ram was resized, old holds all its values from before so cut off cells come back
+    RamSize {
+        ram: usize,
+        old: Vec<i32>,
+        new: usize,
+    },
+    /// Contents of rom were loaded from file
+    RomData {
+        rom: usize,
+        old: Vec<i32>,
+        new: Vec<i32>,
+    },
+    /// Name, delay or stack of vm were changed in its settings window
+    Settings {
+        vm: usize,
+        old: VmSettings,
+        new: VmSettings,
+    },
+    /// Code of vm was edited or opened from file
+    Code { vm: usize, old: String, new: String },
+}
+
+/// Bounded stacks of commands to undo and redo, oldest commands are dropped
+#[derive(Default)]
+pub struct UndoStack {
+    undo: VecDeque<Command>,
+    redo: Vec<Command>,
+    /// Vm and time of last code edit, following edits of the same vm are merged with it
+    last_code_edit: Option<(usize, f64)>,
+}
+
+impl UndoStack {
+    /// Adds command that was just done, commands that were undone can't be redone anymore
+    pub fn push(&mut self, command: Command) {
+        self.last_code_edit = None;
+        self.redo.clear();
+        if self.undo.len() >= UNDO_CAPACITY {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(command);
+    }
+
+    /// Adds code edit, merging it with previous edit of the same vm made shortly before
+    pub fn push_code_edit(&mut self, vm: usize, old: String, new: String, time: f64) {
+        let merge = matches!(
+            self.last_code_edit,
+            Some((last_vm, last_time)) if last_vm == vm && time - last_time < CODE_MERGE_SECONDS
+        );
+        if merge {
+            if let Some(Command::Code { new: last_new, .. }) = self.undo.back_mut() {
+                *last_new = new;
+                self.redo.clear();
+                self.last_code_edit = Some((vm, time));
+                return;
+            }
+        }
+        self.push(Command::Code { vm, old, new });
+        self.last_code_edit = Some((vm, time));
+    }
+
+    /// Takes command to undo, it should be given back with `undone`
+    pub fn pop_undo(&mut self) -> Option<Command> {
+        self.last_code_edit = None;
+        self.undo.pop_back()
+    }
+
+    pub fn undone(&mut self, command: Command) {
+        self.redo.push(command);
+    }
+
+    /// Takes command to redo, it should be given back with `redone`
+    pub fn pop_redo(&mut self) -> Option<Command> {
+        self.last_code_edit = None;
+        self.redo.pop()
+    }
+
+    pub fn redone(&mut self, command: Command) {
+        self.undo.push_back(command);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
@@ -0,0 +1,360 @@
+//! Files with values of memory, e.g. contents of rom
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::condition::parse_value;
+
+/// Layout of values in file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DataFormat {
+    /// Values as i32 little endian
+    Raw,
+    /// Values separated by commas, in one or more lines
+    Csv,
+    /// Values separated by whitespace, usually one per line
+    Lines,
+    /// Intel HEX records holding values as i32 little endian
+    IntelHex,
+}
+
+impl DataFormat {
+    /// Guesses format from extension, `.bin` and `.dat` are raw, `.csv` is csv,
+    /// `.hex` and `.ihex` are Intel HEX, anything else lines
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("bin") | Some("dat") => DataFormat::Raw,
+            Some("csv") => DataFormat::Csv,
+            Some("hex") | Some("ihex") => DataFormat::IntelHex,
+            _ => DataFormat::Lines,
+        }
+    }
+}
+
+/// Reason why file couldn't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataFileError {
+    /// Length of raw file isn't multiple of 4 bytes
+    BadLength(usize),
+    NotText,
+    /// Value that couldn't be parsed and its line, counted from 1
+    BadValue {
+        line: usize,
+        value: String,
+    },
+    /// File has more values than fit
+    TooManyValues(usize),
+}
+
+impl fmt::Display for DataFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataFileError::BadLength(length) => {
+                write!(f, "{}: {}", t!("data_file.error.bad_length"), length)
+            }
+            DataFileError::NotText => write!(f, "{}", t!("data_file.error.not_text")),
+            DataFileError::BadValue { line, value } => {
+                write!(f, "{} {}: {}", t!("data_file.error.bad_value"), line, value)
+            }
+            DataFileError::TooManyValues(count) => {
+                write!(f, "{}: {}", t!("project.error.too_many_values"), count)
+            }
+        }
+    }
+}
+/// Values written in one line of csv
+const CSV_ROW_LEN: usize = 8;
+/// Bytes in one data record of Intel HEX
+const HEX_RECORD_LEN: usize = 16;
+
+/// Parses at most `max_len` values, text values can be written like in programs,
+/// e.g. `-5`, `0x1F`, `0b101` or `'a'`
+pub fn parse(bytes: &[u8], format: DataFormat, max_len: usize) -> Result<Vec<i32>, DataFileError> {
+    let values = match format {
+        DataFormat::Raw => from_le_bytes(bytes)?,
+        DataFormat::Csv | DataFormat::Lines => parse_text(bytes, format)?,
+        DataFormat::IntelHex => parse_intel_hex(bytes, max_len)?,
+    };
+    if values.len() > max_len {
+        return Err(DataFileError::TooManyValues(values.len()));
+    }
+    Ok(values)
+}
+
+/// Writes values in format, csv has 8 values per line
+pub fn write(values: &[i32], format: DataFormat) -> Vec<u8> {
+    match format {
+        DataFormat::Raw => values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect(),
+        DataFormat::Csv => values
+            .chunks(CSV_ROW_LEN)
+            .map(|row| {
+                let row: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+                row.join(",") + "\n"
+            })
+            .collect::<String>()
+            .into_bytes(),
+        DataFormat::Lines => values
+            .iter()
+            .map(|value| format!("{}\n", value))
+            .collect::<String>()
+            .into_bytes(),
+        DataFormat::IntelHex => write_intel_hex(values).into_bytes(),
+    }
+}
+
+fn from_le_bytes(bytes: &[u8]) -> Result<Vec<i32>, DataFileError> {
+    if bytes.len() % 4 != 0 {
+        return Err(DataFileError::BadLength(bytes.len()));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect())
+}
+
+fn parse_text(bytes: &[u8], format: DataFormat) -> Result<Vec<i32>, DataFileError> {
+    let text = std::str::from_utf8(bytes).map_err(|_err| DataFileError::NotText)?;
+    let mut values = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let words: Vec<&str> = match format {
+            DataFormat::Csv => line
+                .split(',')
+                .map(|word| word.trim())
+                .filter(|word| !word.is_empty())
+                .collect(),
+            _ => line.split_whitespace().collect(),
+        };
+        for word in words {
+            let value = parse_value(word).ok_or_else(|| DataFileError::BadValue {
+                line: index + 1,
+                value: word.to_string(),
+            })?;
+            values.push(value);
+        }
+    }
+    Ok(values)
+}
+
+/// Reads data records into memory starting at address 0, gaps are filled with zeros
+fn parse_intel_hex(bytes: &[u8], max_len: usize) -> Result<Vec<i32>, DataFileError> {
+    let text = std::str::from_utf8(bytes).map_err(|_err| DataFileError::NotText)?;
+    let mut memory: Vec<u8> = Vec::new();
+    let mut base = 0;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let bad_record = || DataFileError::BadValue {
+            line: index + 1,
+            value: line.to_string(),
+        };
+        let digits = line.strip_prefix(':').ok_or_else(bad_record)?;
+        let record: Vec<u8> = (0..digits.len())
+            .step_by(2)
+            .map(|i| {
+                digits
+                    .get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(bad_record)?;
+        // length, 2 bytes of address, type, data and checksum
+        if record.len() < 5 || record.len() != record[0] as usize + 5 {
+            return Err(bad_record());
+        }
+        if record.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+            return Err(bad_record());
+        }
+        let address = (record[1] as usize) << 8 | record[2] as usize;
+        let data = &record[4..record.len() - 1];
+        match record[3] {
+            // data
+            0x00 => {
+                let start = base + address;
+                let end = start + data.len();
+                if end > max_len * 4 {
+                    return Err(DataFileError::TooManyValues((end + 3) / 4));
+                }
+                if memory.len() < end {
+                    memory.resize(end, 0);
+                }
+                memory[start..end].copy_from_slice(data);
+            }
+            // end of file
+            0x01 => break,
+            // extended segment and extended linear address
+            0x02 | 0x04 => {
+                if data.len() != 2 {
+                    return Err(bad_record());
+                }
+                let upper = (data[0] as usize) << 8 | data[1] as usize;
+                base = if record[3] == 0x02 {
+                    upper << 4
+                } else {
+                    upper << 16
+                };
+            }
+            // start address records don't hold data
+            _ => {}
+        }
+    }
+    memory.resize((memory.len() + 3) / 4 * 4, 0);
+    from_le_bytes(&memory)
+}
+
+fn write_intel_hex(values: &[i32]) -> String {
+    let bytes: Vec<u8> = values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    let mut text = String::new();
+    let mut upper = 0;
+    for (index, chunk) in bytes.chunks(HEX_RECORD_LEN).enumerate() {
+        let address = index * HEX_RECORD_LEN;
+        if address >> 16 != upper {
+            upper = address >> 16;
+            text += &hex_record(0, 0x04, &[(upper >> 8) as u8, upper as u8]);
+        }
+        text += &hex_record((address & 0xFFFF) as u16, 0x00, chunk);
+    }
+    text += &hex_record(0, 0x01, &[]);
+    text
+}
+
+fn hex_record(address: u16, kind: u8, data: &[u8]) -> String {
+    let mut record = vec![data.len() as u8];
+    record.extend(address.to_be_bytes());
+    record.push(kind);
+    record.extend(data);
+    let checksum = record
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg();
+    record.push(checksum);
+    let digits: String = record.iter().map(|byte| format!("{:02X}", byte)).collect();
+    format!(":{}\n", digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_round_trip_through_every_format() {
+        let len = 65536;
+        let mut data = vec![0; len];
+        for index in (0..len).step_by(997) {
+            data[index] = index as i32 * -3;
+        }
+
+        for format in [
+            DataFormat::Raw,
+            DataFormat::Csv,
+            DataFormat::Lines,
+            DataFormat::IntelHex,
+        ] {
+            let bytes = write(&data, format);
+            assert_eq!(parse(&bytes, format, len), Ok(data.clone()), "{:?}", format);
+            assert_eq!(
+                parse(&bytes, format, 16),
+                Err(DataFileError::TooManyValues(len)),
+                "{:?}",
+                format
+            );
+        }
+        let hex = write(&data[..4], DataFormat::IntelHex);
+        assert_eq!(
+            String::from_utf8(hex).unwrap(),
+            ":1000000000000000000000000000000000000000F0\n:00000001FF\n"
+        );
+    }
+
+    #[test]
+    fn intel_hex_checksum_is_checked() {
+        assert_eq!(
+            parse(
+                b":0400000001000000FB\n:00000001FF\n",
+                DataFormat::IntelHex,
+                4
+            ),
+            Ok(vec![1])
+        );
+        assert_eq!(
+            parse(
+                b":0400000001000000FA\n:00000001FF\n",
+                DataFormat::IntelHex,
+                4
+            ),
+            Err(DataFileError::BadValue {
+                line: 1,
+                value: ":0400000001000000FA".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn bad_intel_hex_records_are_rejected() {
+        for record in [
+            // no start code
+            "0400000001000000FB",
+            // odd number of digits
+            ":0400000001000000F",
+            ":04000000010000ZZFB",
+            // length doesn't match data
+            ":04000000010000FB",
+            // extended address with one byte
+            ":0100000401FA",
+        ] {
+            let text = format!(":0400000001000000FB\n{}\n", record);
+            assert_eq!(
+                parse(text.as_bytes(), DataFormat::IntelHex, 4),
+                Err(DataFileError::BadValue {
+                    line: 2,
+                    value: record.to_string()
+                }),
+                "{}",
+                record
+            );
+        }
+    }
+
+    #[test]
+    fn intel_hex_extended_address_moves_data() {
+        let text = b":020000040001F9\n:0400000001000000FB\n:00000001FF\n";
+        let values = parse(text, DataFormat::IntelHex, 16385).unwrap();
+        assert_eq!(values.len(), 16385);
+        assert_eq!(values[16384], 1);
+        assert_eq!(
+            parse(text, DataFormat::IntelHex, 16384),
+            Err(DataFileError::TooManyValues(16385))
+        );
+    }
+
+    #[test]
+    fn bad_raw_and_text_files_are_rejected() {
+        assert_eq!(
+            parse(&[1, 2, 3], DataFormat::Raw, 4),
+            Err(DataFileError::BadLength(3))
+        );
+        assert_eq!(
+            parse(b"1, 2\n3, x\n", DataFormat::Csv, 4),
+            Err(DataFileError::BadValue {
+                line: 2,
+                value: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&[0xFF, 0xFE], DataFormat::Lines, 4),
+            Err(DataFileError::NotText)
+        );
+    }
+}
@@ -1,4 +1,5 @@
-pub mod connections_manager;
 pub mod custom_logger;
+pub mod data_file;
 pub mod modals_manager;
+pub mod recovery;
 pub mod toasts;
@@ -9,15 +9,6 @@ static MODALS: Lazy<Mutex<HashMap<usize, Modal>>> = Lazy::new(|| Mutex::new(Hash
 
 static CURRENT_MODAL_ID: Mutex<Option<usize>> = Mutex::new(None);
 
-pub static MODAL_TEXT_EDIT_BUFFER: Mutex<String> = Mutex::new(String::new());
-
-pub static MODAL_BUFFER_VALUE_I32: Mutex<Option<i32>> = Mutex::new(None);
-
-pub static MODAL_INDEX_BUFFER: Mutex<Option<usize>> = Mutex::new(None);
-
-// why not?
-pub static RAM_ID: Mutex<Option<usize>> = Mutex::new(None);
-
 pub struct ModalManager {}
 
 impl ModalManager {
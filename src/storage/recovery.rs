@@ -0,0 +1,95 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Directory auto-save snapshots are written to
+pub const RECOVERY_DIR: &str = "SVA_recovery";
+
+/// Seconds between auto-save snapshots of current project
+pub const AUTOSAVE_INTERVAL: f64 = 30.0;
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+
+/// Last snapshot of current project, written to file by panic hook
+static LAST_SNAPSHOT: Mutex<Option<String>> = Mutex::new(None);
+
+pub struct RecoveryManager {}
+
+impl RecoveryManager {
+    pub fn snapshot_path() -> PathBuf {
+        PathBuf::from(RECOVERY_DIR).join(SNAPSHOT_FILE)
+    }
+
+    /// Keeps snapshot for panic hook and writes it to recovery directory when it changed
+    pub fn save_snapshot(data: String) -> io::Result<()> {
+        save_snapshot_in(Path::new(RECOVERY_DIR), data)
+    }
+
+    /// Snapshot saved last, doesn't block so it can be used by panic hook
+    pub fn last_snapshot() -> Option<String> {
+        match LAST_SNAPSHOT.try_lock() {
+            Ok(last) => last.clone(),
+            Err(_err) => None,
+        }
+    }
+
+    /// Snapshot left by previous run that didn't exit cleanly
+    pub fn read_snapshot() -> Option<String> {
+        read_snapshot_from(Path::new(RECOVERY_DIR))
+    }
+
+    /// Removes snapshot file, called on clean exit and when user declines restoring it
+    pub fn discard_snapshot() {
+        discard_snapshot_in(Path::new(RECOVERY_DIR));
+    }
+}
+
+fn save_snapshot_in(dir: &Path, data: String) -> io::Result<()> {
+    let mut last = LAST_SNAPSHOT.lock().unwrap_or_else(|err| err.into_inner());
+    if last.as_ref() == Some(&data) {
+        return Ok(());
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(SNAPSHOT_FILE), &data)?;
+    *last = Some(data);
+    Ok(())
+}
+
+fn read_snapshot_from(dir: &Path) -> Option<String> {
+    fs::read_to_string(dir.join(SNAPSHOT_FILE)).ok()
+}
+
+fn discard_snapshot_in(dir: &Path) {
+    *LAST_SNAPSHOT.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    let _ = fs::remove_file(dir.join(SNAPSHOT_FILE));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_is_saved_read_and_discarded() {
+        let dir = std::env::temp_dir().join(format!("sva_recovery_{}", std::process::id()));
+        let first = r#"{"version":1}"#.to_string();
+        let second = r#"{"version":2}"#.to_string();
+        assert_eq!(read_snapshot_from(&dir), None);
+
+        save_snapshot_in(&dir, first.clone()).unwrap();
+        assert_eq!(read_snapshot_from(&dir), Some(first.clone()));
+        assert_eq!(RecoveryManager::last_snapshot(), Some(first.clone()));
+
+        // unchanged snapshot isn't written again
+        fs::remove_file(dir.join(SNAPSHOT_FILE)).unwrap();
+        save_snapshot_in(&dir, first).unwrap();
+        assert_eq!(read_snapshot_from(&dir), None);
+        save_snapshot_in(&dir, second.clone()).unwrap();
+        assert_eq!(read_snapshot_from(&dir), Some(second));
+
+        discard_snapshot_in(&dir);
+        assert_eq!(read_snapshot_from(&dir), None);
+        assert_eq!(RecoveryManager::last_snapshot(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,52 +1,98 @@
-use std::collections::HashMap;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 use std::fs;
 use std::fs::File;
 use std::time::Duration;
 
 use egui::Context;
-use egui::{ScrollArea, Ui};
+use egui::{Button, Key, KeyboardShortcut, Modifiers, ScrollArea, Ui};
 
 use egui_file::FileDialog;
 use egui_modal::Modal;
 
-use simple_virtual_assembler::components::connection::Connection;
-
 use egui_notify::Toasts;
 
 use simple_virtual_assembler::language::Language;
 
 use serde_json;
-use simple_virtual_assembler::vm::virtual_machine::VmStatus;
 
-use crate::storage::connections_manager::{
-    ConnectionManager, ANOTHER_ID_BUFFER, CONNECTION_NAMES, CURRENT_CONN_ID_FOR_RENAME,
-    RELOAD_CONNECTION,
-};
+use crate::model::clock::Clock;
+use crate::model::console::Console;
+use crate::model::device::DeviceKind;
+use crate::model::keypad::Keypad;
+use crate::model::led_bar::LedBar;
+use crate::model::memory::{DEFAULT_RAM_SIZE, MAX_RAM_SIZE, MIN_RAM_SIZE};
+use crate::model::pixel_display::PixelDisplay;
+use crate::model::push_buttons::PushButtons;
+use crate::model::rom::{Rom, MAX_ROM_SIZE};
+use crate::model::scheduler::SchedulerStatus;
+use crate::model::seven_segment::SevenSegment;
+use crate::model::switches::Switches;
+use crate::model::vm::VmSettings;
+use crate::project;
+use crate::project::document::{ComponentCopy, ComponentKind, Document, WiringMode};
+use crate::project::schema::{PortRef, Project};
+use crate::project::undo::NameTarget;
 use crate::storage::custom_logger::CustomLogger;
-use crate::storage::modals_manager::{
-    ModalManager, MODAL_BUFFER_VALUE_I32, MODAL_INDEX_BUFFER, MODAL_TEXT_EDIT_BUFFER, RAM_ID,
-};
+use crate::storage::data_file::{self, DataFormat};
+use crate::storage::modals_manager::ModalManager;
+use crate::storage::recovery::{RecoveryManager, AUTOSAVE_INTERVAL};
 use crate::storage::toasts::{ToastsManager, TOASTS};
 
 use super::component_list_widget::{ComponentAction, ComponentListWidget};
-use super::connection_widget::ConnectionWidget;
+use super::connection_widget::{ConnectionAction, ConnectionWidget};
+use super::device::DeviceAction;
+use super::document_view::DocumentView;
 use super::help_window::HelpWindow;
-use super::ram_window::RamWidow;
-use super::sva_window::SVAWindow;
+use super::ram_window::RamAction;
+use super::sva_window::VmAction;
 
 use peak_alloc::PeakAlloc;
 
 #[global_allocator]
 static PEAK_ALLOC: PeakAlloc = PeakAlloc;
 
+const UNDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
+const SAVE_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::S);
+
+/// How many recently opened or saved projects are listed in file menu
+const RECENT_FILES_CAPACITY: usize = 8;
+
+/// What file chosen in component file dialog is used for, with tab of component
+enum ComponentFileTarget {
+    LoadRom {
+        tab: usize,
+        id: usize,
+    },
+    ImportRam {
+        tab: usize,
+        id: usize,
+    },
+    ExportRam {
+        tab: usize,
+        id: usize,
+        range: Range<usize>,
+    },
+    OpenSource {
+        tab: usize,
+        id: usize,
+    },
+    SaveSource {
+        tab: usize,
+        id: usize,
+    },
+}
+
 enum AreYouSureModalAction {
     DoNothing,
     Clear,
     RemoveVm,
     RemoveRam,
+    RemoveDevice,
+    CloseTab,
     //RemoveConnection
 }
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
@@ -56,28 +102,56 @@ pub struct SvaUI {
     //#[serde(skip)]
     language: Language,
 
-    vms: Vec<SVAWindow>,
+    /// Projects opened in tabs, there is always at least one
+    documents: Vec<Document>,
+
+    /// Windows and canvas of tabs, one for every document
+    views: Vec<DocumentView>,
+
+    /// Recently opened or saved project files, newest first
+    recent_files: Vec<PathBuf>,
+
+    /// Title of window set in last frame
+    #[serde(skip)]
+    window_title: String,
+
+    /// Index of shown tab
+    current_tab: usize,
+
+    /// Vm or ram copied to be pasted into any tab
+    #[serde(skip)]
+    clipboard: Option<ComponentCopy>,
+
+    #[serde(skip)]
+    tab_to_close: Option<usize>,
 
     ui_scale: f32,
     help_widow: HelpWindow,
 
     debug_window_open: bool,
 
-    #[serde(skip)]
-    opened_file: Option<PathBuf>,
+    /// Size of ram added from add menu
+    new_ram_size: usize,
+
     #[serde(skip)]
     open_file_dialog: Option<FileDialog>,
 
     #[serde(skip)]
     save_file_dialog: Option<FileDialog>,
-    #[serde(skip)]
-    toasts: Toasts,
 
-    connections_copy: Vec<Connection>,
+    /// Tab saved to file chosen in save file dialog
+    #[serde(skip)]
+    save_file_tab: usize,
 
-    conn_names_copies: HashMap<usize, String>,
+    /// Dialog choosing file with values of rom or ram, or source of vm
+    #[serde(skip)]
+    component_file_dialog: Option<FileDialog>,
 
-    rams: Vec<RamWidow>,
+    /// What file chosen in component file dialog is used for
+    #[serde(skip)]
+    component_file_target: Option<ComponentFileTarget>,
+    #[serde(skip)]
+    toasts: Toasts,
 
     connections_panel_visible: bool,
 
@@ -87,11 +161,7 @@ pub struct SvaUI {
 
     change_conn_name_modal_open: bool,
 
-    active_vms: HashMap<usize, bool>,
-
-    active_rams: HashMap<usize, bool>,
-
-    component_change_name_is_ram: Option<bool>,
+    component_change_name_kind: Option<ComponentKind>,
     component_change_name_id: Option<usize>,
     component_change_name_buffer: String,
 
@@ -100,6 +170,35 @@ pub struct SvaUI {
 
     #[serde(skip)]
     are_you_sure_modal_action: AreYouSureModalAction,
+
+    #[serde(skip)]
+    ram_value_buffer: String,
+
+    /// Settings edited in vm settings modal
+    #[serde(skip)]
+    vm_settings_buffer: VmSettings,
+
+    /// Vm edited in vm settings modal, None when vm is being added
+    #[serde(skip)]
+    vm_settings_target: Option<usize>,
+
+    /// Ram id and index of cell edited in set ram value modal
+    #[serde(skip)]
+    ram_value_target: Option<(usize, usize)>,
+
+    #[serde(skip)]
+    connection_to_rename: Option<usize>,
+
+    #[serde(skip)]
+    connection_to_remove: Option<usize>,
+
+    /// Snapshot left by previous run that didn't exit cleanly, user is asked to restore it
+    #[serde(skip)]
+    recovery_snapshot: Option<String>,
+
+    /// Time of last auto-save snapshot, None before first one
+    #[serde(skip)]
+    last_autosave: Option<f64>,
 }
 
 impl Default for SvaUI {
@@ -108,7 +207,11 @@ impl Default for SvaUI {
 
         Self {
             language: Language::En,
-            vms: Vec::new(),
+            documents: vec![Document::default()],
+            views: vec![DocumentView::default()],
+            current_tab: 0,
+            clipboard: None,
+            tab_to_close: None,
 
             ui_scale: 1.25,
             help_widow: HelpWindow {
@@ -117,24 +220,32 @@ impl Default for SvaUI {
             },
 
             debug_window_open: false,
-            opened_file: None,
+            new_ram_size: DEFAULT_RAM_SIZE,
             open_file_dialog: None,
             save_file_dialog: None,
+            save_file_tab: 0,
+            component_file_dialog: None,
+            component_file_target: None,
             toasts: Toasts::default(),
-            connections_copy: Default::default(),
-            conn_names_copies: HashMap::new(),
-            rams: Vec::new(),
             connections_panel_visible: false,
             new_connection_name_buffer: String::new(),
             change_conn_name_modal_open: false,
             components_panel_visible: true,
-            active_vms: HashMap::new(),
-            component_change_name_is_ram: None,
+            component_change_name_kind: None,
             component_change_name_id: None,
             component_change_name_buffer: String::new(),
-            active_rams: HashMap::new(),
             are_you_sure_modal_text: String::new(),
             are_you_sure_modal_action: AreYouSureModalAction::DoNothing,
+            ram_value_buffer: String::new(),
+            vm_settings_buffer: VmSettings::default(),
+            vm_settings_target: None,
+            ram_value_target: None,
+            connection_to_rename: None,
+            connection_to_remove: None,
+            recovery_snapshot: None,
+            last_autosave: None,
+            recent_files: Vec::new(),
+            window_title: String::new(),
         }
     }
 }
@@ -148,138 +259,189 @@ impl SvaUI {
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         //rust_i18n::set_locale("en");
+        let mut sva_ui: SvaUI = Default::default();
         if let Some(storage) = cc.storage {
-            let mut sva_ui: SvaUI = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-            sva_ui.set_connections_and_their_names();
-            sva_ui.reconnect_vm_ports();
-            sva_ui.reconnect_ram_ports();
-            sva_ui.set_language(sva_ui.language.clone());
-
-            return sva_ui;
+            sva_ui = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            if sva_ui.documents.is_empty() {
+                sva_ui.documents.push(Document::default());
+            }
+            sva_ui.current_tab = sva_ui.current_tab.min(sva_ui.documents.len() - 1);
+            sva_ui
+                .views
+                .resize_with(sva_ui.documents.len(), DocumentView::default);
+            for document in sva_ui.documents.iter_mut() {
+                document.circuit.reconnect_ports();
+                document.set_lockstep(document.scheduler.is_enabled());
+            }
+            let language = sva_ui.language.clone();
+            for document in sva_ui.documents.iter_mut() {
+                let document_language = document.language_or(&language);
+                document.set_language(&document_language);
+            }
+            sva_ui.set_ui_language(language);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            sva_ui.recovery_snapshot = RecoveryManager::read_snapshot();
         }
-        let sva_ui: SvaUI = Default::default();
 
         sva_ui
     }
 
-    pub fn set_language(&mut self, language: Language) {
+    /// Changes language of ui, tabs keep language of their projects
+    pub fn set_ui_language(&mut self, language: Language) {
         rust_i18n::set_locale(language.string_code());
         //CustomLogger::log("Changing language");
-        match language {
-            Language::Pl => {
-                self.vms
-                    .iter_mut()
-                    .for_each(|vm| vm.set_language(Language::Pl));
-                self.help_widow.set_language(Language::Pl);
-            }
-            Language::En => {
-                self.vms
-                    .iter_mut()
-                    .for_each(|vm| vm.set_language(Language::En));
-                self.help_widow.set_language(Language::Pl);
-            }
-        }
         self.help_widow.set_language(language);
     }
 
-    fn disconnect_vm_ports(&mut self) {
-        for vm in self.vms.iter_mut() {
-            for i in 0..4 {
-                {
-                    vm.vm.lock().unwrap().disconnect(i);
-                }
-            }
-        }
+    /// Changes language of ui and of shown tab
+    pub fn set_language(&mut self, language: Language) {
+        self.doc_mut().set_language(&language);
+        self.set_ui_language(language);
+    }
+
+    /// Document in shown tab
+    fn doc(&self) -> &Document {
+        &self.documents[self.current_tab]
     }
 
-    fn disconnect_ram_ports(&mut self) {
-        for ram in self.rams.iter_mut() {
-            ram.ram.disconnect_data_port();
-            ram.ram.disconnect_index_port();
-            ram.ram.disconnect_mode_port();
+    fn doc_mut(&mut self) -> &mut Document {
+        &mut self.documents[self.current_tab]
+    }
+
+    /// Windows of shown tab
+    fn view_mut(&mut self) -> &mut DocumentView {
+        &mut self.views[self.current_tab]
+    }
+
+    fn max_vm_height(&self) -> f32 {
+        400.0 * (2.25 / self.ui_scale)
+    }
+
+    /// Opens project in new tab, blank tab is replaced instead
+    fn open_project(&mut self, project: Project, path: Option<PathBuf>) {
+        let mut document = Document::from_project(project);
+        document.path = path;
+        if self.doc().is_blank() {
+            *self.doc_mut() = document;
+            *self.view_mut() = DocumentView::default();
+        } else {
+            self.documents.push(document);
+            self.views.push(DocumentView::default());
+            self.current_tab = self.documents.len() - 1;
         }
     }
 
-    fn copy_connections_and_their_names(&mut self) {
-        self.connections_copy = ConnectionManager::get_connections().lock().unwrap().clone();
-        //CustomLogger::log("Copying connections");
-        //CustomLogger::log(&format!("{:?}", self.connections_copy));
-        //CustomLogger::log("________________________________________");
-        self.conn_names_copies = ConnectionManager::get_names();
+    fn new_tab(&mut self) {
+        self.documents
+            .push(Document::with_language(self.language.clone()));
+        self.views.push(DocumentView::default());
+        self.current_tab = self.documents.len() - 1;
     }
 
-    fn set_connections_and_their_names(&mut self) {
-        ConnectionManager::set_connection(self.connections_copy.clone());
-        self.connections_copy.clear();
-        ConnectionManager::set_names(self.conn_names_copies.clone());
-        self.conn_names_copies.clear();
+    /// Halts vms of tab and removes it, last tab is replaced with empty one
+    fn close_tab(&mut self, index: usize) {
+        if index >= self.documents.len() {
+            return;
+        }
+        self.documents[index].clear();
+        self.documents.remove(index);
+        self.views.remove(index);
+        // tabs after closed one move back, dialog saving closed tab has nothing to save
+        if self.save_file_tab == index {
+            self.save_file_dialog = None;
+        } else if self.save_file_tab > index {
+            self.save_file_tab -= 1;
+        }
+        if self.documents.is_empty() {
+            self.documents
+                .push(Document::with_language(self.language.clone()));
+            self.views.push(DocumentView::default());
+        }
+        if self.current_tab > index || self.current_tab >= self.documents.len() {
+            self.current_tab = self.current_tab.saturating_sub(1);
+        }
     }
 
-    fn reconnect_vm_ports(&mut self) {
-        let binding = ConnectionManager::get_connections();
-        let mut connections = binding.lock().unwrap();
-        for conn in connections.iter_mut() {
-            let id_pairs = conn.get_connected_vms_and_ports('P');
-            for (vm_id, port_index) in id_pairs {
-                let x = self
-                    .vms
-                    .iter()
-                    .find(|vm| vm.get_id() == TryInto::<usize>::try_into(vm_id).unwrap());
-                if let Some(y) = x {
-                    {
-                        y.vm.lock().unwrap().connect(port_index, conn);
-                    }
-                }
-            }
+    /// Writes snapshot of current project to recovery directory every `AUTOSAVE_INTERVAL` seconds
+    fn autosave(&mut self, ctx: &Context) {
+        // snapshot of previous run is kept until user decides what to do with it
+        if self.recovery_snapshot.is_some() || self.doc().is_blank() {
+            return;
+        }
+        let time = ctx.input(|i| i.time);
+        if matches!(self.last_autosave, Some(last) if time - last < AUTOSAVE_INTERVAL) {
+            return;
+        }
+        self.last_autosave = Some(time);
+        let result = project::save(&self.doc().to_project())
+            .map_err(|err| err.to_string())
+            .and_then(|data| RecoveryManager::save_snapshot(data).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            CustomLogger::log(&format!("{} \n {}", t!("error.recovery.cant_save"), err));
         }
     }
 
-    fn reconnect_ram_ports(&mut self) {
-        let binding = ConnectionManager::get_connections();
-        let mut connections = binding.lock().unwrap();
-        for conn in connections.iter_mut() {
-            let id_pairs = conn.get_connected_rams();
-            for (ram_id, port_index) in id_pairs {
-                let x = self.rams.iter_mut().find(|ram| ram.get_id() == ram_id);
-                if let Some(y) = x {
-                    if port_index == 0 {
-                        y.ram.connect_index_port(conn);
-                    } else if port_index == 1 {
-                        y.ram.connect_data_port(conn);
-                    } else if port_index == 2 {
-                        y.ram.connect_mode_port(conn);
-                    }
-                }
+    /// Opens snapshot of previous run in new tab
+    fn restore_snapshot(&mut self) {
+        let Some(data) = self.recovery_snapshot.take() else {
+            return;
+        };
+        match project::load(&data) {
+            Ok(project) => self.open_project(project, None),
+            Err(err) => {
+                ToastsManager::show_err(format!("{}\n{}", t!("error.import.bad_project"), err), 10)
             }
         }
     }
 
-    fn export_to_file(&mut self, path: String) {
-        self.copy_connections_and_their_names();
-        self.disconnect_vm_ports();
-        self.disconnect_ram_ports();
-        let mut to_be_restated = Vec::new();
-        self.vms.iter_mut().for_each(|vm| {
-            if vm.get_status() == VmStatus::Running {
-                vm.stop_vm();
-                to_be_restated.push(vm.get_id())
-            }
-        });
-        let serialized_state = serde_json::to_string(&self);
-        self.vms
-            .iter_mut()
-            .filter(|vm| to_be_restated.contains(&vm.get_id()))
-            .for_each(|vm| vm.resume_vm());
-        self.set_connections_and_their_names();
-        self.reconnect_vm_ports();
-        self.reconnect_ram_ports();
+    /// Moves file to the top of recent files
+    fn remember_file(&mut self, path: &Path) {
+        self.recent_files.retain(|recent| recent != path);
+        self.recent_files.insert(0, path.to_path_buf());
+        self.recent_files.truncate(RECENT_FILES_CAPACITY);
+    }
+
+    /// Saves shown tab to its file, file dialog is opened when it wasn't saved before
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_file(&mut self) {
+        match self.doc().path.clone() {
+            Some(path) => self.export_to_file(self.current_tab, path),
+            None => self.open_save_file_dialog(None),
+        }
+    }
+
+    /// Opens dialog saving shown tab, it's saved even if other tab is shown when dialog closes
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_save_file_dialog(&mut self, path: Option<PathBuf>) {
+        let mut dialog = FileDialog::save_file(path);
+        dialog.open();
+        self.save_file_dialog = Some(dialog);
+        self.save_file_tab = self.current_tab;
+    }
+
+    fn export_to_file(&mut self, tab: usize, path: PathBuf) {
+        let Some(document) = self.documents.get(tab) else {
+            return;
+        };
+        let serialized_state = project::save(&document.to_project());
 
         match serialized_state {
             Ok(data) => {
-                let file = File::create(path).unwrap();
-                let mut writer = BufWriter::new(file);
-                writer.write_all(data.as_bytes()).unwrap();
-                writer.flush().unwrap();
+                let written = File::create(&path).and_then(|file| {
+                    let mut writer = BufWriter::new(file);
+                    writer.write_all(data.as_bytes())?;
+                    writer.flush()
+                });
+                if written.is_err() {
+                    ToastsManager::show_err(t!("error.file.cant_save"), 10);
+                    return;
+                }
+                self.remember_file(&path);
+                let document = &mut self.documents[tab];
+                document.path = Some(path);
+                document.changed = false;
             }
             Err(_err) => {
                 self.toasts
@@ -289,30 +451,26 @@ impl SvaUI {
         };
     }
 
-    fn import_file(&mut self, path: String) {
-        let data = fs::read_to_string(path);
+    fn import_file(&mut self, path: PathBuf) {
+        let data = fs::read_to_string(&path);
         match data {
-            Ok(data) => {
-                let json: Result<SvaUI, serde_json::Error> = serde_json::from_str(&data);
-                match json {
-                    Ok(sva_ui) => {
-                        *self = sva_ui;
-                        self.set_connections_and_their_names();
-                        self.reconnect_vm_ports();
-                        self.reconnect_ram_ports();
-                    }
-                    Err(err) => {
-                        CustomLogger::log(&format!("{} \n {}", t!("error.import.bad_json"), err));
-                        TOASTS
-                            .lock()
-                            .unwrap()
-                            .error(t!("error.import.bad_json"))
-                            .set_duration(Some(Duration::from_secs(10)));
-                    }
+            Ok(data) => match project::load(&data) {
+                Ok(project) => {
+                    self.remember_file(&path);
+                    self.open_project(project, Some(path));
                 }
-            }
+                Err(err) => {
+                    CustomLogger::log(&format!("{} \n {}", t!("error.import.bad_project"), err));
+                    TOASTS
+                        .lock()
+                        .unwrap()
+                        .error(format!("{}\n{}", t!("error.import.bad_project"), err))
+                        .set_duration(Some(Duration::from_secs(10)));
+                }
+            },
             Err(_err) => {
                 //CustomLogger::log(&format!("Could not open file \n {}", err));
+                self.recent_files.retain(|recent| recent != &path);
                 TOASTS
                     .lock()
                     .unwrap()
@@ -338,13 +496,14 @@ impl SvaUI {
                 ui.collapsing("variables", |ui| {
                     ui.label("Connection state");
                     ui.separator();
-                    ui.label(format!("{:?}", CONNECTION_NAMES));
+                    let document = &self.documents[self.current_tab];
+                    ui.label(format!("{:?}", document.circuit.connection_names()));
                     ui.separator();
-                    ui.label(format!("{:?}", ConnectionManager::get_current_id_index()));
+                    ui.label(format!("{:?}", document.wiring));
                     ui.separator();
                     ui.horizontal(|ui| {
-                        ui.label("MODAL VALUE BUFFER (for setting ram)");
-                        ui.label(format!("{:?}", *MODAL_BUFFER_VALUE_I32.lock().unwrap()));
+                        ui.label("RAM VALUE TARGET (ram id, index)");
+                        ui.label(format!("{:?}", self.ram_value_target));
                     });
                 });
                 ui.collapsing("logs", |ui| {
@@ -366,16 +525,9 @@ impl SvaUI {
         if let Some(dialog) = &mut self.save_file_dialog {
             if dialog.show(ctx).selected() {
                 if let Some(file) = dialog.path() {
-                    self.opened_file = Some(PathBuf::from(file));
-                    CustomLogger::log(&format!("{:?}", self.opened_file));
-                    self.export_to_file(
-                        self.opened_file
-                            .clone()
-                            .unwrap()
-                            .to_str()
-                            .unwrap()
-                            .to_owned(),
-                    );
+                    let path = PathBuf::from(file);
+                    CustomLogger::log(&format!("{:?}", path));
+                    self.export_to_file(self.save_file_tab, path);
                 }
             }
         }
@@ -385,21 +537,128 @@ impl SvaUI {
         if let Some(dialog) = &mut self.open_file_dialog {
             if dialog.show(ctx).selected() {
                 if let Some(file) = dialog.path() {
-                    self.opened_file = Some(PathBuf::from(file));
-                    CustomLogger::log(&format!("{:?}", self.opened_file));
-                    self.import_file(
-                        self.opened_file
-                            .clone()
-                            .unwrap()
-                            .to_str()
-                            .unwrap()
-                            .to_owned(),
-                    );
+                    let path = PathBuf::from(file);
+                    CustomLogger::log(&format!("{:?}", path));
+                    self.import_file(path);
+                }
+            }
+        }
+    }
+
+    fn open_component_file_dialog(&mut self, target: ComponentFileTarget) {
+        let path = self.doc().path.clone();
+        let mut dialog = match target {
+            ComponentFileTarget::ExportRam { .. } => FileDialog::save_file(path),
+            ComponentFileTarget::SaveSource { tab, id } => {
+                let source = self.source_path(tab, id);
+                FileDialog::save_file(source.or(path))
+            }
+            ComponentFileTarget::OpenSource { tab, id } => {
+                let source = self.source_path(tab, id);
+                FileDialog::open_file(source.or(path))
+            }
+            _ => FileDialog::open_file(path),
+        };
+        dialog.open();
+        self.component_file_dialog = Some(dialog);
+        self.component_file_target = Some(target);
+    }
+
+    fn show_component_file_dialog(&mut self, ctx: &Context) {
+        if let Some(dialog) = &mut self.component_file_dialog {
+            if dialog.show(ctx).selected() {
+                if let (Some(file), Some(target)) =
+                    (dialog.path(), self.component_file_target.take())
+                {
+                    let path = PathBuf::from(file);
+                    let result = match target {
+                        ComponentFileTarget::LoadRom { tab, id } => self.load_rom(tab, id, &path),
+                        ComponentFileTarget::ImportRam { tab, id } => {
+                            self.import_ram(tab, id, &path)
+                        }
+                        ComponentFileTarget::ExportRam { tab, id, range } => {
+                            self.export_ram(tab, id, range, &path)
+                        }
+                        ComponentFileTarget::OpenSource { tab, id } => self
+                            .documents
+                            .get_mut(tab)
+                            .map_or(Ok(()), |document| document.open_source(id, &path)),
+                        ComponentFileTarget::SaveSource { tab, id } => self
+                            .documents
+                            .get_mut(tab)
+                            .map_or(Ok(()), |document| document.save_source(id, &path)),
+                    };
+                    if let Err(err) = result {
+                        ToastsManager::show_err(err, 10);
+                    }
                 }
             }
         }
     }
 
+    /// Source file vm is linked to
+    fn source_path(&self, tab: usize, id: usize) -> Option<PathBuf> {
+        self.documents
+            .get(tab)
+            .and_then(|document| document.circuit.vm(id))
+            .and_then(|vm| vm.source_path())
+            .map(Path::to_path_buf)
+    }
+
+    /// Reads values from file, format is chosen by extension
+    fn read_data_file(path: &Path, max_len: usize) -> Result<Vec<i32>, String> {
+        let bytes = fs::read(path).map_err(|_err| t!("error.file.cant_open"))?;
+        data_file::parse(&bytes, DataFormat::from_path(path), max_len)
+            .map_err(|err| err.to_string())
+    }
+
+    fn load_rom(&mut self, tab: usize, id: usize, path: &Path) -> Result<(), String> {
+        let data = SvaUI::read_data_file(path, MAX_ROM_SIZE)?;
+        let Some(document) = self.documents.get_mut(tab) else {
+            return Ok(());
+        };
+        document.load_rom(id, data).map_err(|err| err.to_string())
+    }
+
+    /// Replaces values of ram with values from file, cells after them are zeroed
+    fn import_ram(&mut self, tab: usize, id: usize, path: &Path) -> Result<(), String> {
+        let Some(document) = self.documents.get_mut(tab) else {
+            return Ok(());
+        };
+        let Some(size) = document.circuit.ram(id).map(|ram| ram.size()) else {
+            return Ok(());
+        };
+        let mut data = SvaUI::read_data_file(path, size)?;
+        data.resize(size, 0);
+        document.set_ram_values(id, 0, data);
+        Ok(())
+    }
+
+    /// Writes values of ram in range to file, format is chosen by extension
+    fn export_ram(
+        &mut self,
+        tab: usize,
+        id: usize,
+        range: Range<usize>,
+        path: &Path,
+    ) -> Result<(), String> {
+        let Some(ram) = self
+            .documents
+            .get(tab)
+            .and_then(|document| document.circuit.ram(id))
+        else {
+            return Ok(());
+        };
+        let data = ram.get_data();
+        let values = data
+            .get(range)
+            .ok_or_else(|| t!("ram_window.error.bad_range"))?;
+        let bytes = data_file::write(values, DataFormat::from_path(path));
+        fs::write(path, bytes).map_err(|_err| t!("error.file.cant_save"))?;
+        ToastsManager::show_info(t!("ram_window.exported"), 5);
+        Ok(())
+    }
+
     fn show_connection_name_change_modal(&mut self, ctx: &Context) -> Modal {
         let change_conn_name_modal = Modal::new(ctx, "change_conn_name_modal");
         change_conn_name_modal.show(|ui| {
@@ -412,8 +671,12 @@ impl SvaUI {
                     change_conn_name_modal.close();
                 }
                 if ui.button(t!("button.save")).clicked() {
-                    let id = *CURRENT_CONN_ID_FOR_RENAME.lock().unwrap();
-                    ConnectionManager::set_name(id, self.new_connection_name_buffer.clone());
+                    if let Some(id) = self.connection_to_rename.take() {
+                        let name = self.new_connection_name_buffer.clone();
+                        if let Err(err) = self.doc_mut().rename(NameTarget::Connection(id), name) {
+                            ToastsManager::show_err(err.to_string(), 10);
+                        }
+                    }
 
                     self.change_conn_name_modal_open = false;
                     change_conn_name_modal.close();
@@ -423,34 +686,30 @@ impl SvaUI {
         change_conn_name_modal
     }
 
-    fn set_ram_value(&mut self, value: i32, index: usize) {
-        let ram_id = *RAM_ID.lock().unwrap();
-        if ram_id.clone().is_none() {
-            return;
+    fn set_ram_value(&mut self, value: i32) {
+        if let Some((ram_id, index)) = self.ram_value_target.take() {
+            self.doc_mut().set_ram_values(ram_id, index, vec![value]);
         }
-        let ram_id = ram_id.unwrap();
-        let ram = self.rams.iter_mut().find(|ram| ram.get_id() == ram_id);
+    }
 
-        if let Some(r) = ram {
-            r.set_value_at_index(index, value);
+    fn change_ram_name(&mut self, id: usize, name: String) {
+        let target = NameTarget::Component(ComponentKind::Ram, id);
+        if self.doc_mut().rename(target, name).is_err() {
+            ToastsManager::show_err(t!("error.cant_change_ram_name"), 10);
         }
     }
 
-    fn change_ram_name(&mut self, id: usize, name: String) {
-        let res = self.rams.iter_mut().find(|ram| ram.get_id() == id);
-        match res {
-            Some(ram) => ram.set_name(name),
-            None => ToastsManager::show_err(t!("error.cant_change_ram_name"), 10),
+    fn change_device_name(&mut self, id: usize, name: String) {
+        let target = NameTarget::Component(ComponentKind::Device, id);
+        if let Err(err) = self.doc_mut().rename(target, name) {
+            ToastsManager::show_err(err.to_string(), 10);
         }
     }
 
     fn change_vm_name(&mut self, id: usize, name: String) {
-        let res = self.vms.iter_mut().find(|vm| vm.get_id() == id);
-        match res {
-            Some(vm) => {
-                vm.set_name(name);
-            }
-            None => ToastsManager::show_err(t!("error.cant_change_vm_name"), 10),
+        let target = NameTarget::Component(ComponentKind::Vm, id);
+        if self.doc_mut().rename(target, name).is_err() {
+            ToastsManager::show_err(t!("error.cant_change_vm_name"), 10);
         }
     }
 
@@ -458,26 +717,25 @@ impl SvaUI {
         let change_component_name_modal = Modal::new(ctx, "change_component_name_modal");
         change_component_name_modal.show(|ui| {
             change_component_name_modal.title(ui, t!("modal.change_component_name.title"));
-            let buffer = &mut *MODAL_TEXT_EDIT_BUFFER.lock().unwrap();
-            ui.text_edit_singleline(buffer);
+            ui.text_edit_singleline(&mut self.component_change_name_buffer);
             ui.horizontal(|ui| {
                 if ui.button(t!("button.cancel")).clicked() {
                     change_component_name_modal.close();
                     ModalManager::unset_current_modal();
                 }
                 if ui.button(t!("button.save")).clicked() {
-                    if self.component_change_name_is_ram == Some(true) {
-                        self.change_ram_name(
-                            self.component_change_name_id.unwrap(),
-                            buffer.to_string(),
-                        );
-                    } else if self.component_change_name_is_ram == Some(false) {
-                        self.change_vm_name(
-                            self.component_change_name_id.unwrap(),
-                            buffer.to_string(),
-                        );
+                    let name = self.component_change_name_buffer.clone();
+                    if let (Some(kind), Some(id)) = (
+                        self.component_change_name_kind,
+                        self.component_change_name_id,
+                    ) {
+                        match kind {
+                            ComponentKind::Vm => self.change_vm_name(id, name),
+                            ComponentKind::Ram => self.change_ram_name(id, name),
+                            ComponentKind::Device => self.change_device_name(id, name),
+                        }
                     }
-                    self.component_change_name_is_ram = None;
+                    self.component_change_name_kind = None;
                     self.component_change_name_id = None;
                     change_component_name_modal.close();
                     ModalManager::unset_current_modal();
@@ -491,15 +749,10 @@ impl SvaUI {
     fn create_ram_value_setter_modal(&mut self, ctx: &Context) {
         let set_ram_value_modal = Modal::new(ctx, "set_ram_value_modal");
 
-        {
-            *MODAL_BUFFER_VALUE_I32.lock().unwrap() = None;
-        }
-
         set_ram_value_modal.show(|ui| {
             set_ram_value_modal.title(ui, t!("modal.set_ram_value.title"));
-            let buffer = &mut *MODAL_TEXT_EDIT_BUFFER.lock().unwrap();
-
-            ui.text_edit_singleline(buffer);
+            ui.text_edit_singleline(&mut self.ram_value_buffer);
+            let buffer = self.ram_value_buffer.as_str();
 
             let mut can_save = false;
             let mut value: Option<i32> = None;
@@ -534,21 +787,19 @@ impl SvaUI {
                 }
                 _ => {}
             };
-            if let Some(value) = value {
-                *MODAL_BUFFER_VALUE_I32.lock().unwrap() = Some(value);
-            }
 
             ui.horizontal(|ui| {
                 if ui.button(t!("button.cancel")).clicked() {
                     set_ram_value_modal.close();
                     ModalManager::unset_current_modal();
+                    self.ram_value_target = None;
                 }
                 if can_save && ui.button(t!("button.save")).clicked() {
                     set_ram_value_modal.close();
                     ModalManager::unset_current_modal();
-                    let x = MODAL_BUFFER_VALUE_I32.lock().unwrap().unwrap();
-                    let y = MODAL_INDEX_BUFFER.lock().unwrap().unwrap();
-                    self.set_ram_value(x, y);
+                    if let Some(value) = value {
+                        self.set_ram_value(value);
+                    }
                 }
             });
         });
@@ -556,14 +807,77 @@ impl SvaUI {
         ModalManager::add_modal(1, set_ram_value_modal);
     }
 
+    /// Modal with settings of added vm, or of existing vm when target is set
+    fn create_vm_settings_modal(&mut self, ctx: &Context) {
+        let vm_settings_modal = Modal::new(ctx, "vm_settings_modal");
+        vm_settings_modal.show(|ui| {
+            match self.vm_settings_target {
+                Some(_) => vm_settings_modal.title(ui, t!("modal.vm_settings.title")),
+                None => vm_settings_modal.title(ui, t!("modal.add_vm.title")),
+            }
+            self.vm_settings_buffer.show(ui);
+            ui.horizontal(|ui| {
+                if ui.button(t!("button.cancel")).clicked() {
+                    vm_settings_modal.close();
+                    ModalManager::unset_current_modal();
+                    self.vm_settings_target = None;
+                }
+                let save_text = match self.vm_settings_target {
+                    Some(_) => t!("button.save"),
+                    None => t!("button.add"),
+                };
+                if ui.button(save_text).clicked() {
+                    vm_settings_modal.close();
+                    ModalManager::unset_current_modal();
+                    let settings = self.vm_settings_buffer.clone();
+                    match self.vm_settings_target.take() {
+                        Some(id) => {
+                            if let Err(err) = self.doc_mut().apply_vm_settings(id, &settings) {
+                                ToastsManager::show_err(err.to_string(), 10);
+                            }
+                        }
+                        None => {
+                            self.doc_mut().add_vm(&settings);
+                        }
+                    }
+                }
+            });
+        });
+
+        ModalManager::add_modal(4, vm_settings_modal);
+    }
+
+    fn create_recovery_modal(&mut self, ctx: &Context) {
+        let recovery_modal = Modal::new(ctx, "recovery_modal");
+        recovery_modal.show(|ui| {
+            recovery_modal.title(ui, t!("modal.recovery.title"));
+            ui.label(t!("modal.recovery.text"));
+            ui.horizontal(|ui| {
+                if ui.button(t!("button.discard")).clicked() {
+                    recovery_modal.close();
+                    ModalManager::unset_current_modal();
+                    self.recovery_snapshot = None;
+                    RecoveryManager::discard_snapshot();
+                }
+                if ui.button(t!("button.restore")).clicked() {
+                    recovery_modal.close();
+                    ModalManager::unset_current_modal();
+                    self.restore_snapshot();
+                }
+            });
+        });
+
+        ModalManager::add_modal(5, recovery_modal);
+        if self.recovery_snapshot.is_some() && !ModalManager::should_display_modal() {
+            ModalManager::set_modal(5);
+        }
+    }
+
     fn create_are_you_sure_modal(&mut self, ctx: &Context) {
         let are_yot_sure_modal = Modal::new(ctx, "are you sure modal");
-        let conn_id: Option<usize>;
-        {
-            conn_id = *ANOTHER_ID_BUFFER.lock().unwrap();
-        }
-        if conn_id.is_some() {
-            let conn_name = ConnectionManager::get_name(conn_id.unwrap());
+        let conn_id = self.connection_to_remove;
+        if let Some(id) = conn_id {
+            let conn_name = self.doc().circuit.connection_name(id);
             self.are_you_sure_modal_text = format!(
                 "{}:{}",
                 t!("modal.are_you_sure.remove_connection_heading"),
@@ -576,12 +890,14 @@ impl SvaUI {
                 if ui.button(t!("button.no")).clicked() {
                     are_yot_sure_modal.close();
                     ModalManager::unset_current_modal();
-                    *ANOTHER_ID_BUFFER.lock().unwrap() = None;
+                    self.connection_to_remove = None;
+                    self.tab_to_close = None;
                 }
                 if ui.button(t!("button.yes")).clicked() {
-                    if conn_id.is_some() {
-                        ConnectionManager::remove_connection(conn_id);
-                        *ANOTHER_ID_BUFFER.lock().unwrap() = None;
+                    if let Some(id) = self.connection_to_remove.take() {
+                        if let Err(err) = self.doc_mut().remove_connection(id) {
+                            ToastsManager::show_err(err.to_string(), 10);
+                        }
                     }
 
                     match self.are_you_sure_modal_action {
@@ -593,6 +909,16 @@ impl SvaUI {
                         AreYouSureModalAction::RemoveRam => {
                             self.remove_ram(self.component_change_name_id)
                         }
+                        AreYouSureModalAction::RemoveDevice => {
+                            if let Some(id) = self.component_change_name_id {
+                                self.doc_mut().remove_device(id);
+                            }
+                        }
+                        AreYouSureModalAction::CloseTab => {
+                            if let Some(index) = self.tab_to_close.take() {
+                                self.close_tab(index);
+                            }
+                        }
                     }
                     are_yot_sure_modal.close();
                     ModalManager::unset_current_modal();
@@ -604,31 +930,23 @@ impl SvaUI {
 
     pub fn remove_vm(&mut self, id: Option<usize>) {
         if let Some(id) = id {
-            if let Some(vm) = self.vms.iter_mut().find(|vm| vm.get_id() == id) {
-                vm.halt_vm()
-            }
-            self.vms.retain(|vm| vm.get_id() != id);
+            self.doc_mut().remove_vm(id);
         }
     }
 
     pub fn remove_ram(&mut self, id: Option<usize>) {
         if let Some(id) = id {
-            self.rams.retain(|ram| ram.get_id() != id);
+            self.doc_mut().remove_ram(id);
         }
     }
 
+    /// Clears shown tab
     pub fn clear_file(&mut self) {
-        self.vms.iter_mut().for_each(|vm| vm.halt_vm());
-        self.vms.clear();
-        self.rams.clear();
-        self.conn_names_copies.clear();
-        ConnectionManager::clear_connection_names();
-        ConnectionManager::clear_connections();
+        self.doc_mut().clear();
+        self.view_mut().canvas.clear();
         self.new_connection_name_buffer.clear();
-        {
-            let buffer = &mut *MODAL_TEXT_EDIT_BUFFER.lock().unwrap();
-            buffer.clear();
-        }
+        self.component_change_name_buffer.clear();
+        self.ram_value_buffer.clear();
     }
 
     fn show_file_menu(&mut self, ui: &mut Ui) {
@@ -640,22 +958,138 @@ impl SvaUI {
 
                 ModalManager::set_modal(3);
             }
-            // import button
+            // new tab button
+            if ui.button(t!("menu.file.new_tab")).clicked() {
+                self.new_tab();
+            }
+            // import button, web pages can't open file dialogs
+            #[cfg(not(target_arch = "wasm32"))]
             if ui.button(t!("menu.file.import")).clicked() {
-                let mut dialog = FileDialog::open_file(self.opened_file.clone());
+                let mut dialog = FileDialog::open_file(self.doc().path.clone());
                 dialog.open();
                 self.open_file_dialog = Some(dialog);
             }
+            // save button
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .add(
+                    Button::new(t!("menu.file.save"))
+                        .shortcut_text(ui.ctx().format_shortcut(&SAVE_SHORTCUT)),
+                )
+                .on_hover_text(t!("menu.file.save.on_hover_text"))
+                .clicked()
+            {
+                self.save_file();
+                ui.close_menu();
+            }
             // export button
+            #[cfg(not(target_arch = "wasm32"))]
             if ui.button(t!("menu.file.export")).clicked() {
-                let mut dialog = FileDialog::save_file(self.opened_file.clone());
-                dialog.open();
-                self.save_file_dialog = Some(dialog);
+                self.open_save_file_dialog(self.doc().path.clone());
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            self.show_recent_files_menu(ui);
+        });
+        ui.add_space(16.0);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn show_recent_files_menu(&mut self, ui: &mut Ui) {
+        ui.add_enabled_ui(!self.recent_files.is_empty(), |ui| {
+            ui.menu_button(t!("menu.file.recent"), |ui| {
+                let mut to_open = None;
+                for path in self.recent_files.iter() {
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string());
+                    if ui
+                        .button(name)
+                        .on_hover_text(path.display().to_string())
+                        .clicked()
+                    {
+                        to_open = Some(path.clone());
+                    }
+                }
+                ui.separator();
+                if ui.button(t!("menu.file.recent.clear")).clicked() {
+                    self.recent_files.clear();
+                    ui.close_menu();
+                }
+                if let Some(path) = to_open {
+                    self.import_file(path);
+                    ui.close_menu();
+                }
+            });
+        });
+    }
+
+    fn show_edit_menu(&mut self, ui: &mut Ui) {
+        ui.menu_button(format!("\u{270F} {}", t!("menu.edit")), |ui| {
+            let ctx = ui.ctx().clone();
+            if ui
+                .add_enabled(
+                    self.doc().can_undo(),
+                    Button::new(t!("menu.edit.undo"))
+                        .shortcut_text(ctx.format_shortcut(&UNDO_SHORTCUT)),
+                )
+                .clicked()
+            {
+                self.undo();
+                ui.close_menu();
+            }
+            if ui
+                .add_enabled(
+                    self.doc().can_redo(),
+                    Button::new(t!("menu.edit.redo"))
+                        .shortcut_text(ctx.format_shortcut(&REDO_SHORTCUT)),
+                )
+                .clicked()
+            {
+                self.redo();
+                ui.close_menu();
             }
         });
         ui.add_space(16.0);
     }
 
+    /// Save, undo and redo shortcuts, undo and redo are left to text fields while one of them
+    /// is focused
+    fn handle_shortcuts(&mut self, ctx: &Context) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if ctx.input_mut(|i| i.consume_shortcut(&SAVE_SHORTCUT)) {
+            self.save_file();
+        }
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&UNDO_SHORTCUT)) {
+            self.undo();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&REDO_SHORTCUT)) {
+            self.redo();
+        }
+    }
+
+    /// Shows file name of shown tab in window title, `*` marks unsaved changes
+    fn update_window_title(&mut self, ctx: &Context) {
+        let document = self.doc();
+        let marker = if document.changed { "*" } else { "" };
+        let title = format!("{}{} - sva_ui", document.title(), marker);
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
+    fn undo(&mut self) {
+        self.doc_mut().undo();
+    }
+
+    fn redo(&mut self) {
+        self.doc_mut().redo();
+    }
+
     fn show_language_select(&mut self, ui: &mut Ui) {
         egui::ComboBox::from_label("")
             .selected_text(format!("{:?}", self.language.string_code()))
@@ -676,24 +1110,214 @@ impl SvaUI {
     }
 
     fn show_component_add_menu(&mut self, ui: &mut Ui) {
-        let max_height = 400.0 * (2.25 / self.ui_scale);
         ui.menu_button(t!("button.add"), |ui| {
-            // vm with stack
+            // vm, settings are chosen in modal
             if ui.button(t!("button.add_vm")).clicked() {
-                let id = self.vms.last().map_or(0, |last| last.get_id() + 1);
-                let x = SVAWindow::new(id, true, max_height);
-                self.active_vms.insert(id, true);
-                self.vms.push(x);
+                self.vm_settings_buffer = VmSettings::default();
+                self.vm_settings_target = None;
+                ModalManager::set_modal(4);
+                ui.close_menu();
             }
             // ram module
-            if ui.button(t!("button.add_ram")).clicked() {
-                let id = self.rams.last().map_or(0, |last| last.get_id() + 1);
-                self.active_rams.insert(id, true);
-                self.rams.push(RamWidow::new(id));
+            ui.horizontal(|ui| {
+                if ui.button(t!("button.add_ram")).clicked() {
+                    let size = self.new_ram_size;
+                    self.doc_mut().add_ram(size);
+                }
+                ui.add(
+                    egui::DragValue::new(&mut self.new_ram_size)
+                        .clamp_range(MIN_RAM_SIZE..=MAX_RAM_SIZE)
+                        .speed(16),
+                )
+                .on_hover_text(t!("ram_window.size"));
+            });
+            // text console
+            if ui
+                .button(t!("button.add_console"))
+                .on_hover_text(t!("button.add_console.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::Console(Console::default()));
+            }
+            // pixel display
+            if ui
+                .button(t!("button.add_display"))
+                .on_hover_text(t!("button.add_display.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::Display(PixelDisplay::default()));
+            }
+            // clock
+            if ui
+                .button(t!("button.add_clock"))
+                .on_hover_text(t!("button.add_clock.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::Clock(Clock::default()));
+            }
+            // led bar
+            if ui
+                .button(t!("button.add_led_bar"))
+                .on_hover_text(t!("button.add_led_bar.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::LedBar(LedBar::default()));
+            }
+            // seven segment display
+            if ui
+                .button(t!("button.add_seven_segment"))
+                .on_hover_text(t!("button.add_seven_segment.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::SevenSegment(SevenSegment::default()));
             }
+            // switches
+            if ui
+                .button(t!("button.add_switches"))
+                .on_hover_text(t!("button.add_switches.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::Switches(Switches::default()));
+            }
+            // push buttons
+            if ui
+                .button(t!("button.add_push_buttons"))
+                .on_hover_text(t!("button.add_push_buttons.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::Buttons(PushButtons::default()));
+            }
+            // keypad
+            if ui
+                .button(t!("button.add_keypad"))
+                .on_hover_text(t!("button.add_keypad.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut()
+                    .add_device(DeviceKind::Keypad(Keypad::default()));
+            }
+            // rom
+            if ui
+                .button(t!("button.add_rom"))
+                .on_hover_text(t!("button.add_rom.on_hover_text"))
+                .clicked()
+            {
+                self.doc_mut().add_device(DeviceKind::Rom(Rom::default()));
+            }
+            // component copied from any tab
+            if let Some(copy) = self.clipboard.clone() {
+                ui.separator();
+                if ui
+                    .button(format!("{}: {}", t!("button.paste"), copy.name()))
+                    .clicked()
+                {
+                    self.doc_mut().paste(copy);
+                }
+            }
+        });
+    }
+
+    fn show_scheduler_menu(&mut self, ui: &mut Ui) {
+        let document = self.doc_mut();
+        ui.menu_button(t!("menu.scheduler"), |ui| {
+            let mut enabled = document.scheduler.is_enabled();
+            if ui
+                .checkbox(&mut enabled, t!("scheduler.lockstep"))
+                .changed()
+            {
+                document.set_lockstep(enabled);
+            }
+            ui.add(
+                egui::Slider::new(document.scheduler.tick_ms_mut(), 0..=5000)
+                    .logarithmic(true)
+                    .text(t!("scheduler.tick_interval")),
+            );
+            ui.separator();
+            ui.add_enabled_ui(document.scheduler.is_enabled(), |ui| {
+                ui.horizontal(|ui| {
+                    if document.scheduler.is_running() {
+                        if ui.button(t!("scheduler.button.pause")).clicked() {
+                            document.scheduler.pause();
+                        }
+                    } else if ui.button(t!("sva_shell.button.start")).clicked() {
+                        document.scheduler.start();
+                    }
+                    if ui.button(t!("sva_shell.button.step")).clicked() {
+                        document.scheduler.pause();
+                        document.scheduler_step(true);
+                    }
+                    if ui.button(t!("scheduler.button.stop")).clicked() {
+                        document.scheduler_stop();
+                    }
+                });
+            });
+            let status = match document.scheduler.get_status() {
+                SchedulerStatus::Stopped => t!("sva.vm_status.initial"),
+                SchedulerStatus::Running => t!("sva.vm_status.running"),
+                SchedulerStatus::Paused => t!("sva.vm_status.stopped"),
+            };
+            ui.label(format!(
+                "{}: {} ({})",
+                t!("scheduler.tick"),
+                document.scheduler.get_tick(),
+                status
+            ));
         });
     }
 
+    /// Buttons controlling all vms and rams at once
+    fn show_global_controls(&mut self, ui: &mut Ui) {
+        if ui
+            .button(format!("\u{25B6} {}", t!("toolbar.run_all")))
+            .on_hover_text(t!("toolbar.run_all.on_hover_text"))
+            .clicked()
+        {
+            self.doc_mut().run_all();
+        }
+        if ui
+            .button(format!("\u{23F8} {}", t!("toolbar.stop_all")))
+            .on_hover_text(t!("toolbar.stop_all.on_hover_text"))
+            .clicked()
+        {
+            self.doc_mut().stop_all();
+        }
+        if ui
+            .button(format!("\u{23F9} {}", t!("toolbar.halt_all")))
+            .on_hover_text(t!("toolbar.halt_all.on_hover_text"))
+            .clicked()
+        {
+            self.doc_mut().halt_all();
+        }
+        if ui
+            .button(format!("\u{23ED} {}", t!("toolbar.step_all")))
+            .on_hover_text(t!("toolbar.step_all.on_hover_text"))
+            .clicked()
+        {
+            self.doc_mut().step_all_vms();
+        }
+        if ui
+            .button(format!("\u{21BA} {}", t!("toolbar.reset_all")))
+            .on_hover_text(t!("toolbar.reset_all.on_hover_text"))
+            .clicked()
+        {
+            self.doc_mut().reset_all();
+        }
+        if ui
+            .button(t!("toolbar.zero_rams"))
+            .on_hover_text(t!("toolbar.zero_rams.on_hover_text"))
+            .clicked()
+        {
+            self.doc_mut().zero_all_rams();
+        }
+    }
+
     fn show_components_side_panel(&mut self, ctx: &Context) {
         egui::SidePanel::right("components_panel")
             .resizable(true)
@@ -705,16 +1329,17 @@ impl SvaUI {
 
                 ui.collapsing(t!("side_panel.components.collapsing_vms"), |ui| {
                     ScrollArea::new(true).show(ui, |ui| {
-                        for vm in &self.vms {
+                        let document = self.doc();
+                        for vm in document.circuit.vms() {
                             let id = vm.get_id();
-                            let is_active = *self.active_vms.get(&id).unwrap_or(&false);
+                            let is_active = *document.active_vms.get(&id).unwrap_or(&false);
                             actions.push(
                                 ComponentListWidget::new(
                                     id,
                                     vm.get_name(),
                                     is_active,
-                                    Some(vm.get_status()),
-                                    true,
+                                    Some(vm.read_status()),
+                                    ComponentKind::Vm,
                                 )
                                 .show(ctx, ui),
                             );
@@ -723,16 +1348,36 @@ impl SvaUI {
                 });
                 ui.collapsing(t!("side_panel.components.collapsing_rams"), |ui| {
                     ScrollArea::new(true).show(ui, |ui| {
-                        for ram in &self.rams {
+                        let document = self.doc();
+                        for ram in document.circuit.rams() {
                             let id = ram.get_id();
-                            let is_active = *self.active_rams.get(&id).unwrap_or(&false);
+                            let is_active = *document.active_rams.get(&id).unwrap_or(&false);
                             actions.push(
                                 ComponentListWidget::new(
                                     id,
                                     ram.get_name(),
                                     is_active,
                                     None,
-                                    false,
+                                    ComponentKind::Ram,
+                                )
+                                .show(ctx, ui),
+                            );
+                        }
+                    });
+                });
+                ui.collapsing(t!("side_panel.components.collapsing_devices"), |ui| {
+                    ScrollArea::new(true).show(ui, |ui| {
+                        let document = self.doc();
+                        for device in document.circuit.devices() {
+                            let id = device.get_id();
+                            let is_active = *document.active_devices.get(&id).unwrap_or(&false);
+                            actions.push(
+                                ComponentListWidget::new(
+                                    id,
+                                    device.get_name(),
+                                    is_active,
+                                    None,
+                                    ComponentKind::Device,
                                 )
                                 .show(ctx, ui),
                             );
@@ -744,20 +1389,26 @@ impl SvaUI {
                     match action {
                         ComponentAction::DoNothing => {}
                         ComponentAction::ToggleVmVisibility(id) => {
-                            if let Some(value) = self.active_vms.get_mut(&id) {
+                            if let Some(value) = self.doc_mut().active_vms.get_mut(&id) {
                                 *value = !*value; // Toggle the boolean value
                             }
                         }
                         ComponentAction::RenameVm(id) => {
-                            self.component_change_name_is_ram = Some(false);
+                            self.component_change_name_kind = Some(ComponentKind::Vm);
                             self.component_change_name_id = Some(id);
+                            self.component_change_name_buffer = self
+                                .doc()
+                                .circuit
+                                .vm(id)
+                                .map(|vm| vm.get_name())
+                                .unwrap_or_default();
                             ModalManager::set_modal(2);
                         }
                         ComponentAction::RemoveVm(id) => {
-                            self.component_change_name_is_ram = Some(false);
+                            self.component_change_name_kind = Some(ComponentKind::Vm);
                             self.component_change_name_id = Some(id);
 
-                            let name = match self.vms.iter().find(|vm| vm.get_id() == id) {
+                            let name = match self.doc().circuit.vm(id) {
                                 Some(vm) => vm.get_name(),
                                 None => "None".to_owned(),
                             };
@@ -766,22 +1417,31 @@ impl SvaUI {
                             self.are_you_sure_modal_action = AreYouSureModalAction::RemoveVm;
                             ModalManager::set_modal(3);
                         }
+                        ComponentAction::CopyVm(id) => {
+                            self.clipboard = self.doc().copy_vm(id);
+                        }
                         ComponentAction::ToggleRamVisibility(id) => {
-                            if let Some(value) = self.active_rams.get_mut(&id) {
+                            if let Some(value) = self.doc_mut().active_rams.get_mut(&id) {
                                 *value = !*value; // Toggle the boolean value
                             }
                         }
                         ComponentAction::RenameRam(id) => {
-                            self.component_change_name_is_ram = Some(true);
+                            self.component_change_name_kind = Some(ComponentKind::Ram);
                             self.component_change_name_id = Some(id);
+                            self.component_change_name_buffer = self
+                                .doc()
+                                .circuit
+                                .ram(id)
+                                .map(|ram| ram.get_name())
+                                .unwrap_or_default();
                             ModalManager::set_modal(2);
                         }
                         ComponentAction::RemoveRam(id) => {
-                            self.component_change_name_is_ram = Some(true);
+                            self.component_change_name_kind = Some(ComponentKind::Ram);
                             self.component_change_name_id = Some(id);
 
-                            let name = match self.rams.iter().find(|ram| ram.get_id() == id) {
-                                Some(vm) => vm.get_name(),
+                            let name = match self.doc().circuit.ram(id) {
+                                Some(ram) => ram.get_name(),
                                 None => "None".to_owned(),
                             };
 
@@ -793,45 +1453,181 @@ impl SvaUI {
                             self.are_you_sure_modal_action = AreYouSureModalAction::RemoveRam;
                             ModalManager::set_modal(3);
                         }
+                        ComponentAction::CopyRam(id) => {
+                            self.clipboard = self.doc().copy_ram(id);
+                        }
+                        ComponentAction::ToggleDeviceVisibility(id) => {
+                            if let Some(value) = self.doc_mut().active_devices.get_mut(&id) {
+                                *value = !*value;
+                            }
+                        }
+                        ComponentAction::RenameDevice(id) => {
+                            self.component_change_name_kind = Some(ComponentKind::Device);
+                            self.component_change_name_id = Some(id);
+                            self.component_change_name_buffer = self
+                                .doc()
+                                .circuit
+                                .device(id)
+                                .map(|device| device.get_name())
+                                .unwrap_or_default();
+                            ModalManager::set_modal(2);
+                        }
+                        ComponentAction::RemoveDevice(id) => {
+                            self.component_change_name_kind = Some(ComponentKind::Device);
+                            self.component_change_name_id = Some(id);
+
+                            let name = match self.doc().circuit.device(id) {
+                                Some(device) => device.get_name(),
+                                None => "None".to_owned(),
+                            };
+
+                            self.are_you_sure_modal_text = format!(
+                                "{}: {}",
+                                t!("modal.are_you_sure.remove_device_heading"),
+                                name
+                            );
+                            self.are_you_sure_modal_action = AreYouSureModalAction::RemoveDevice;
+                            ModalManager::set_modal(3);
+                        }
+                        ComponentAction::CopyDevice(id) => {
+                            self.clipboard = self.doc().copy_device(id);
+                        }
                     }
                 }
             });
     }
 
+    /// Bar with tab of every opened project
+    fn show_tabs(&mut self, ctx: &Context) {
+        egui::TopBottomPanel::top("tabs_panel").show(ctx, |ui| {
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let mut to_close = None;
+                    for (index, document) in self.documents.iter().enumerate() {
+                        ui.separator();
+                        if ui
+                            .selectable_label(index == self.current_tab, document.tab_label())
+                            .on_hover_text(
+                                document
+                                    .path
+                                    .as_ref()
+                                    .map(|path| path.display().to_string())
+                                    .unwrap_or_default(),
+                            )
+                            .clicked()
+                        {
+                            self.current_tab = index;
+                        }
+                        if ui
+                            .small_button("\u{2716}")
+                            .on_hover_text(t!("tab.close"))
+                            .clicked()
+                        {
+                            to_close = Some(index);
+                        }
+                    }
+                    ui.separator();
+                    if ui
+                        .button("+")
+                        .on_hover_text(t!("menu.file.new_tab"))
+                        .clicked()
+                    {
+                        self.new_tab();
+                    }
+
+                    if let Some(index) = to_close {
+                        if self.documents[index].is_blank() {
+                            self.close_tab(index);
+                        } else {
+                            self.tab_to_close = Some(index);
+                            self.are_you_sure_modal_text = format!(
+                                "{}: {}",
+                                t!("modal.are_you_sure.close_tab_heading"),
+                                self.documents[index].title()
+                            );
+                            self.are_you_sure_modal_action = AreYouSureModalAction::CloseTab;
+                            ModalManager::set_modal(3);
+                        }
+                    }
+                });
+            });
+        });
+    }
+
     fn show_connections_side_panel(&mut self, ctx: &Context) {
         egui::SidePanel::right("connections_panel")
             .resizable(true)
             .min_width(100.0)
             .max_width(300.0)
             .show(ctx, |ui| {
+                let document = &mut self.documents[self.current_tab];
                 ui.heading(t!("side_panel.connections.heading"));
                 ui.vertical(|ui| {
                     if ui.button(t!("button.add")).clicked() {
-                        ConnectionManager::create_connection();
+                        document.add_connection();
                     }
                     let mut disconnect_button_text = t!("button.disconnect.disconnect");
-                    if ConnectionManager::in_disconnect_mode() {
+                    if document.wiring.is_disconnecting() {
                         disconnect_button_text = t!("button.disconnect.stop_disconnecting");
                     }
                     if ui.button(disconnect_button_text).clicked() {
-                        ConnectionManager::toggle_disconnect_mode();
+                        document.wiring = if document.wiring.is_disconnecting() {
+                            WiringMode::Idle
+                        } else {
+                            WiringMode::Disconnecting
+                        };
                     }
 
                     if ui
                         .button(t!("button.stop_connecting_disconnecting"))
                         .clicked()
                     {
-                        ConnectionManager::set_current_id(None);
+                        document.wiring = WiringMode::Idle;
                     }
                 });
 
+                let mut actions = Vec::new();
                 egui::ScrollArea::vertical().show(ui, |ui| {
-                    let conns = ConnectionManager::get_connections().lock().unwrap().clone();
-                    for c in conns {
-                        ConnectionWidget::new(c, &mut self.change_conn_name_modal_open)
-                            .show(ctx, ui);
+                    for conn in document.circuit.connections() {
+                        let Some(id) = conn.get_id() else {
+                            continue;
+                        };
+                        let name = document.circuit.connection_name(id).unwrap_or_default();
+                        let ports = PortRef::from_connection(conn)
+                            .iter()
+                            .map(|port| document.circuit.port_label(port))
+                            .collect();
+                        let history = document.connection_history.changes(id);
+                        actions.push(
+                            ConnectionWidget::new(id, name, document.wiring, ports, history)
+                                .show(ctx, ui),
+                        );
                     }
                 });
+
+                for action in actions {
+                    match action {
+                        ConnectionAction::DoNothing => {}
+                        ConnectionAction::ToggleConnecting(id) => {
+                            document.wiring = if document.wiring == WiringMode::Connecting(id) {
+                                WiringMode::Idle
+                            } else {
+                                WiringMode::Connecting(id)
+                            };
+                        }
+                        ConnectionAction::Rename(id) => {
+                            self.connection_to_rename = Some(id);
+                            self.new_connection_name_buffer =
+                                document.circuit.connection_name(id).unwrap_or_default();
+                            self.change_conn_name_modal_open = true;
+                        }
+                        ConnectionAction::Remove(id) => {
+                            self.connection_to_remove = Some(id);
+                            self.are_you_sure_modal_action = AreYouSureModalAction::DoNothing;
+                            ModalManager::set_modal(3);
+                        }
+                    }
+                }
             });
     }
 }
@@ -840,61 +1636,54 @@ impl eframe::App for SvaUI {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         CustomLogger::log("auto saving");
-        self.copy_connections_and_their_names();
-        self.disconnect_vm_ports();
-        self.disconnect_ram_ports();
-        let mut to_be_restated: Vec<usize> = Vec::new();
-        self.vms.iter_mut().for_each(|vm| {
-            if vm.get_status() == VmStatus::Running {
-                vm.stop_vm();
-                to_be_restated.push(vm.get_id())
-            }
-        });
-        eframe::set_value(storage, eframe::APP_KEY, self);
-        self.reconnect_ram_ports();
-        self.reconnect_vm_ports();
-        self.set_connections_and_their_names();
-        self.vms
+        let to_be_restarted: Vec<Vec<usize>> = self
+            .documents
             .iter_mut()
-            .filter(|vm| to_be_restated.contains(&vm.get_id()))
-            .for_each(|vm| vm.resume_vm());
+            .map(|document| document.suspend_for_save())
+            .collect();
+        eframe::set_value(storage, eframe::APP_KEY, self);
+        for (document, ids) in self.documents.iter_mut().zip(to_be_restarted.iter()) {
+            document.resume_after_save(ids);
+        }
+    }
+
+    /// Snapshot is removed on clean exit, so it's only offered after crash
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        RecoveryManager::discard_snapshot();
     }
 
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
-        //refreshing ram
-        self.rams.iter_mut().for_each(|ram| ram.refresh());
+        // rams, scheduler, breakpoints and watchpoints of every tab
+        let time = ctx.input(|i| i.time);
+        for document in self.documents.iter_mut() {
+            if let Some(after) = document.update(time) {
+                ctx.request_repaint_after(after);
+            }
+        }
 
         ctx.set_pixels_per_point(self.ui_scale);
 
-        // reconnect connection after removal
-        {
-            let mut done_reconnecting = false;
-            if *RELOAD_CONNECTION.lock().unwrap() {
-                self.disconnect_ram_ports();
-                self.disconnect_vm_ports();
-                self.reconnect_ram_ports();
-                self.reconnect_vm_ports();
-                done_reconnecting = true;
-            }
-            if done_reconnecting {
-                *RELOAD_CONNECTION.lock().unwrap() = true;
-            }
-        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.autosave(ctx);
+
+        self.handle_shortcuts(ctx);
+        self.update_window_title(ctx);
 
         // creating modals
 
         self.create_ram_value_setter_modal(ctx);
         self.crate_component_change_name_modal(ctx);
         self.create_are_you_sure_modal(ctx);
+        self.create_vm_settings_modal(ctx);
+        self.create_recovery_modal(ctx);
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::ScrollArea::horizontal().show(ui, |ui| {
                 egui::menu::bar(ui, |ui| {
-                    #[cfg(not(target_arch = "wasm32"))] // no File->Quit on web pages!
-                    {
-                        self.show_file_menu(ui);
-                    }
+                    self.show_file_menu(ui);
+                    self.show_edit_menu(ui);
 
                     egui::widgets::global_dark_light_mode_switch(ui);
 
@@ -902,24 +1691,38 @@ impl eframe::App for SvaUI {
                     self.show_language_select(ui);
                     ui.separator();
                     // ui scale slider
-                    if ui
-                        .add(
-                            egui::Slider::new(&mut self.ui_scale, 0.75..=2.0)
-                                .step_by(0.05)
-                                .text(t!("slider.ui.scale")),
-                        )
-                        .changed()
-                    {
-                        let max_height = 450.0 * (2.0 / self.ui_scale);
-                        for vm in self.vms.iter_mut() {
-                            vm.set_max_height(max_height);
-                        }
-                    }
+                    ui.add(
+                        egui::Slider::new(&mut self.ui_scale, 0.75..=2.0)
+                            .step_by(0.05)
+                            .text(t!("slider.ui.scale")),
+                    );
 
                     ui.separator();
 
                     self.show_component_add_menu(ui);
 
+                    self.show_scheduler_menu(ui);
+
+                    ui.separator();
+                    self.show_global_controls(ui);
+                    ui.separator();
+
+                    if ui
+                        .button(t!("button.open_watch_window"))
+                        .on_hover_text(t!("button.open_watch_window.on_hover_text"))
+                        .clicked()
+                    {
+                        self.view_mut().watch_window.toggle_open_close();
+                    }
+
+                    if ui
+                        .button(t!("button.open_wiring_canvas"))
+                        .on_hover_text(t!("button.open_wiring_canvas.on_hover_text"))
+                        .clicked()
+                    {
+                        self.view_mut().canvas.toggle_open_close();
+                    }
+
                     if ui.button(t!("button.open_help_window")).clicked() {
                         self.help_widow.toggle_open_close();
                     }
@@ -945,6 +1748,8 @@ impl eframe::App for SvaUI {
             });
         });
 
+        self.show_tabs(ctx);
+
         if self.components_panel_visible {
             self.show_components_side_panel(ctx);
         }
@@ -957,24 +1762,166 @@ impl eframe::App for SvaUI {
         egui::CentralPanel::default().show(ctx, |ui| {
             // The central panel the region left after adding TopPanel's and SidePanel's
 
+            // files chosen for vms, roms and rams, dialog is opened after handling actions
+            let mut component_file_targets = Vec::new();
             // vms
-            for index in 0..self.vms.len() {
-                let vm = &mut self.vms[index];
-
-                let active = *self.active_vms.get(&vm.get_id()).unwrap_or(&false);
+            let max_height = self.max_vm_height();
+            let document = &mut self.documents[self.current_tab];
+            let view = &mut self.views[self.current_tab];
+            view.retain_existing(&document.circuit);
+            let names = document.circuit.connection_names().clone();
+            let wiring = document.wiring;
+            let mut vm_actions = Vec::new();
+            for vm in document.circuit.vms_mut().iter_mut() {
+                let active = *document.active_vms.get(&vm.get_id()).unwrap_or(&false);
 
                 if active {
-                    vm.show(ctx, ui);
+                    let window = view.vm_windows.entry(vm.get_id()).or_default();
+                    vm_actions.push(window.show(vm, ctx, ui, wiring, &names, max_height));
+                }
+            }
+            for action in vm_actions {
+                let result = match action {
+                    VmAction::DoNothing => Ok(()),
+                    VmAction::Step(id) => document.step_vm(id),
+                    VmAction::StepBack(id) => document.circuit.step_back_vm(id),
+                    VmAction::Watch(target) => {
+                        document.watchpoints.add(target);
+                        view.watch_window.is_open = true;
+                        Ok(())
+                    }
+                    VmAction::PortClicked(port) => {
+                        document.port_clicked(port);
+                        Ok(())
+                    }
+                    VmAction::OpenSettings(id) => {
+                        if let Some(vm) = document.circuit.vm(id) {
+                            self.vm_settings_buffer = vm.settings();
+                            self.vm_settings_target = Some(id);
+                            ModalManager::set_modal(4);
+                        }
+                        Ok(())
+                    }
+                    VmAction::SetDelay(id, delay_ms) => document.set_vm_delay(id, delay_ms),
+                    VmAction::ToggleBreakpoint(id, line) => {
+                        document.toggle_breakpoint(id, line);
+                        Ok(())
+                    }
+                    VmAction::RemoveBreakpoint(id, index) => {
+                        document.remove_breakpoint(id, index);
+                        Ok(())
+                    }
+                    VmAction::SetBreakpointCondition(id, index, condition) => {
+                        document.set_breakpoint_condition(id, index, condition);
+                        Ok(())
+                    }
+                    VmAction::SetReloadSource(id, reload) => {
+                        document.set_reload_source(id, reload);
+                        Ok(())
+                    }
+                    VmAction::UnlinkSource(id) => {
+                        document.unlink_source(id);
+                        Ok(())
+                    }
+                    VmAction::CodeEdited(id, old_code) => {
+                        document.record_code_edit(id, old_code);
+                        Ok(())
+                    }
+                    VmAction::OpenSource(id) => {
+                        component_file_targets.push(ComponentFileTarget::OpenSource {
+                            tab: self.current_tab,
+                            id,
+                        });
+                        Ok(())
+                    }
+                    VmAction::SaveSource(id) => {
+                        component_file_targets.push(ComponentFileTarget::SaveSource {
+                            tab: self.current_tab,
+                            id,
+                        });
+                        Ok(())
+                    }
+                };
+                if let Err(err) = result {
+                    ToastsManager::show_err(err.to_string(), 10);
                 }
             }
             // rams
-            for index in 0..self.rams.len() {
-                let ram = &mut self.rams[index];
-                let active = *self.active_rams.get(&ram.get_id()).unwrap_or(&false);
+            let mut ram_actions = Vec::new();
+            for ram in document.circuit.rams_mut().iter_mut() {
+                let active = *document.active_rams.get(&ram.get_id()).unwrap_or(&false);
                 if active {
-                    ram.show(ctx, ui);
+                    let window = view.ram_windows.entry(ram.get_id()).or_default();
+                    ram_actions.push((ram.get_id(), window.show(ram, ctx, ui, wiring, &names)));
                 }
             }
+            for (ram_id, action) in ram_actions {
+                match action {
+                    RamAction::DoNothing => {}
+                    RamAction::PortClicked(port) => document.port_clicked(port),
+                    RamAction::SetValue(index) => {
+                        self.ram_value_target = Some((ram_id, index));
+                        self.ram_value_buffer.clear();
+                        ModalManager::set_modal(1);
+                    }
+                    RamAction::Zero => document.zero_ram(ram_id),
+                    RamAction::Watch(target) => {
+                        document.watchpoints.add(target);
+                        view.watch_window.is_open = true;
+                    }
+                    RamAction::Import => {
+                        component_file_targets.push(ComponentFileTarget::ImportRam {
+                            tab: self.current_tab,
+                            id: ram_id,
+                        })
+                    }
+                    RamAction::Export(range) => {
+                        component_file_targets.push(ComponentFileTarget::ExportRam {
+                            tab: self.current_tab,
+                            id: ram_id,
+                            range,
+                        })
+                    }
+                    RamAction::Resize(size) => {
+                        if let Err(err) = document.resize_ram(ram_id, size) {
+                            ToastsManager::show_err(err.to_string(), 10);
+                        }
+                    }
+                }
+            }
+            // devices
+            let mut device_actions = Vec::new();
+            for device in document.circuit.devices_mut().iter_mut() {
+                let active = *document
+                    .active_devices
+                    .get(&device.get_id())
+                    .unwrap_or(&false);
+                if active {
+                    device_actions.push(device.show(ctx, wiring, &names));
+                }
+            }
+            for action in device_actions {
+                match action {
+                    DeviceAction::DoNothing => {}
+                    DeviceAction::PortClicked(port) => document.port_clicked(port),
+                    DeviceAction::LoadRom(id) => {
+                        component_file_targets.push(ComponentFileTarget::LoadRom {
+                            tab: self.current_tab,
+                            id,
+                        })
+                    }
+                }
+            }
+
+            // watch window
+            view.show_watch_window(ctx, document);
+
+            // wiring canvas
+            view.show_canvas(ctx, document);
+
+            if let Some(target) = component_file_targets.pop() {
+                self.open_component_file_dialog(target);
+            }
 
             // powered by
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
@@ -987,6 +1934,8 @@ impl eframe::App for SvaUI {
 
             self.show_import_file_dialog(ctx, ui);
 
+            self.show_component_file_dialog(ctx);
+
             //help window
             self.help_widow.show(ctx, ui);
 
@@ -1026,3 +1975,33 @@ fn powered_by_egui_and_eframe(ui: &mut egui::Ui) {
         ui.label(".");
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_files_are_capped_without_duplicates() {
+        let path = |index: usize| PathBuf::from(format!("project_{}.json", index));
+        let mut sva_ui = SvaUI::default();
+        for index in 0..RECENT_FILES_CAPACITY + 2 {
+            sva_ui.remember_file(&path(index));
+        }
+        assert_eq!(sva_ui.recent_files.len(), RECENT_FILES_CAPACITY);
+        assert_eq!(sva_ui.recent_files[0], path(RECENT_FILES_CAPACITY + 1));
+        assert!(!sva_ui.recent_files.contains(&path(1)));
+
+        // opening file again moves it to the top
+        sva_ui.remember_file(&path(5));
+        assert_eq!(sva_ui.recent_files.len(), RECENT_FILES_CAPACITY);
+        assert_eq!(sva_ui.recent_files[0], path(5));
+        assert_eq!(
+            sva_ui
+                .recent_files
+                .iter()
+                .filter(|recent| **recent == path(5))
+                .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use egui::Ui;
+
+use crate::model::clock::{Clock, ClockMode, ClockUnit};
+
+impl Clock {
+    pub fn show(&mut self, ui: &mut Ui, id: usize) {
+        ui.horizontal(|ui| {
+            let button_text = if self.running {
+                t!("clock.button.stop")
+            } else {
+                t!("clock.button.start")
+            };
+            if ui.button(button_text).clicked() {
+                self.running = !self.running;
+            }
+            if ui.button(t!("clock.button.reset")).clicked() {
+                self.reset();
+            }
+            ui.label(format!("{}: {}", t!("clock.count"), self.count()));
+        });
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source(("clock_mode", id))
+                .selected_text(match self.mode {
+                    ClockMode::Pulse => t!("clock.mode.pulse"),
+                    ClockMode::Counter => t!("clock.mode.counter"),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.mode, ClockMode::Pulse, t!("clock.mode.pulse"));
+                    ui.selectable_value(
+                        &mut self.mode,
+                        ClockMode::Counter,
+                        t!("clock.mode.counter"),
+                    );
+                });
+            ui.label(t!("clock.period"));
+            ui.add(egui::DragValue::new(&mut self.period).clamp_range(1..=60_000));
+            let mut unit = self.unit;
+            egui::ComboBox::from_id_source(("clock_unit", id))
+                .selected_text(match self.unit {
+                    ClockUnit::Ms => "ms".to_string(),
+                    ClockUnit::Ticks => t!("scheduler.tick"),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut unit, ClockUnit::Ms, "ms");
+                    ui.selectable_value(&mut unit, ClockUnit::Ticks, t!("scheduler.tick"));
+                });
+            self.set_unit(unit);
+        });
+    }
+}
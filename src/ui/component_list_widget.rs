@@ -1,15 +1,23 @@
 use egui::{Context, Ui};
 use simple_virtual_assembler::vm::virtual_machine::VmStatus;
 
+use crate::project::document::ComponentKind;
+
 #[derive(Debug, PartialEq)]
 pub enum ComponentAction {
     DoNothing,
     ToggleVmVisibility(usize),
     RenameVm(usize),
     RemoveVm(usize),
+    CopyVm(usize),
     ToggleRamVisibility(usize),
     RenameRam(usize),
     RemoveRam(usize),
+    CopyRam(usize),
+    ToggleDeviceVisibility(usize),
+    RenameDevice(usize),
+    RemoveDevice(usize),
+    CopyDevice(usize),
 }
 
 pub struct ComponentListWidget {
@@ -17,7 +25,7 @@ pub struct ComponentListWidget {
     name: String,
     is_active: bool,
     status: Option<VmStatus>,
-    is_vm: bool,
+    kind: ComponentKind,
 }
 
 impl ComponentListWidget {
@@ -26,14 +34,14 @@ impl ComponentListWidget {
         name: String,
         is_active: bool,
         status: Option<VmStatus>,
-        is_vm: bool,
+        kind: ComponentKind,
     ) -> Self {
         ComponentListWidget {
             id,
             name,
             is_active,
             status,
-            is_vm,
+            kind,
         }
     }
 
@@ -62,24 +70,35 @@ impl ComponentListWidget {
                 t!("button.show")
             };
             if ui.button(show_hide_button_text).clicked() {
-                action = if self.is_vm {
-                    ComponentAction::ToggleVmVisibility(self.id)
-                } else {
-                    ComponentAction::ToggleRamVisibility(self.id)
+                action = match self.kind {
+                    ComponentKind::Vm => ComponentAction::ToggleVmVisibility(self.id),
+                    ComponentKind::Ram => ComponentAction::ToggleRamVisibility(self.id),
+                    ComponentKind::Device => ComponentAction::ToggleDeviceVisibility(self.id),
                 }
             }
             if ui.button(t!("button.rename")).clicked() {
-                action = if self.is_vm {
-                    ComponentAction::RenameVm(self.id)
-                } else {
-                    ComponentAction::RenameRam(self.id)
+                action = match self.kind {
+                    ComponentKind::Vm => ComponentAction::RenameVm(self.id),
+                    ComponentKind::Ram => ComponentAction::RenameRam(self.id),
+                    ComponentKind::Device => ComponentAction::RenameDevice(self.id),
+                }
+            }
+            if ui
+                .button(t!("button.copy"))
+                .on_hover_text(t!("button.copy.on_hover_text"))
+                .clicked()
+            {
+                action = match self.kind {
+                    ComponentKind::Vm => ComponentAction::CopyVm(self.id),
+                    ComponentKind::Ram => ComponentAction::CopyRam(self.id),
+                    ComponentKind::Device => ComponentAction::CopyDevice(self.id),
                 }
             }
             if ui.button(t!("button.remove")).clicked() {
-                action = if self.is_vm {
-                    ComponentAction::RemoveVm(self.id)
-                } else {
-                    ComponentAction::RemoveRam(self.id)
+                action = match self.kind {
+                    ComponentKind::Vm => ComponentAction::RemoveVm(self.id),
+                    ComponentKind::Ram => ComponentAction::RemoveRam(self.id),
+                    ComponentKind::Device => ComponentAction::RemoveDevice(self.id),
                 }
             }
         });
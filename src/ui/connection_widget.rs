@@ -1,41 +1,52 @@
-use egui::{Color32, Context, RichText, Ui};
+use egui::{CollapsingHeader, Color32, Context, RichText, ScrollArea, Ui};
 
-use simple_virtual_assembler::components::connection::Connection;
+use crate::model::connection_history::ValueChange;
+use crate::project::document::WiringMode;
 
-use crate::storage::{
-    connections_manager::{ConnectionManager, ANOTHER_ID_BUFFER, CURRENT_CONN_ID_FOR_RENAME},
-    custom_logger::CustomLogger,
-    modals_manager::ModalManager,
-};
+#[derive(Debug, PartialEq)]
+pub enum ConnectionAction {
+    DoNothing,
+    /// Start or stop connecting ports to connection
+    ToggleConnecting(usize),
+    Rename(usize),
+    Remove(usize),
+}
 
-pub struct ConnectionWidget<'a> {
-    conn: Connection,
-    change_conn_name_modal_open: &'a mut bool,
+pub struct ConnectionWidget {
+    id: usize,
+    name: String,
+    wiring: WiringMode,
+    /// Labels of ports joined by connection
+    ports: Vec<String>,
+    /// Recent values, newest first
+    history: Vec<ValueChange>,
 }
 
-impl<'a> ConnectionWidget<'a> {
-    pub fn new(conn: Connection, change_conn_name_modal_open: &'a mut bool) -> Self {
+impl ConnectionWidget {
+    pub fn new(
+        id: usize,
+        name: String,
+        wiring: WiringMode,
+        ports: Vec<String>,
+        history: Vec<ValueChange>,
+    ) -> Self {
         Self {
-            conn,
-            change_conn_name_modal_open,
+            id,
+            name,
+            wiring,
+            ports,
+            history,
         }
     }
 
-    pub fn show(&mut self, _ctx: &Context, ui: &mut Ui) {
-        let id = self.conn.get_id();
-
-        let mut name = "".to_owned();
-        if let Some(id_c) = id {
-            if let Some(res) = ConnectionManager::get_name(id_c) {
-                name = res;
-            }
-        }
+    pub fn show(&mut self, _ctx: &Context, ui: &mut Ui) -> ConnectionAction {
+        let mut action = ConnectionAction::DoNothing;
 
         ui.separator();
 
         let mut button_text = t!("button.connect.connect");
         let mut color = Color32::GRAY;
-        if ConnectionManager::get_current_id() == self.conn.get_id() {
+        if self.wiring == WiringMode::Connecting(self.id) {
             button_text = t!("button.connect.stop_connecting");
             let in_dark_mode = ui.style().visuals.dark_mode;
 
@@ -48,34 +59,61 @@ impl<'a> ConnectionWidget<'a> {
 
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
-                //ui.label(format!("{:?}", id));
                 ui.separator();
                 ui.vertical(|ui| {
-                    ui.heading(RichText::new(name).color(color));
+                    ui.heading(RichText::new(&self.name).color(color));
                 });
             });
 
-            // it's an index
+            let value = match self.history.first() {
+                Some(change) => change.value.to_string(),
+                None => "-".to_string(),
+            };
+            ui.label(
+                RichText::new(format!("{}: {}", t!("label.connection_value"), value)).monospace(),
+            );
 
-            let current_index = ConnectionManager::get_current_id();
-            if ui.button(button_text).clicked() {
-                CustomLogger::log(&format!("CONN IDS: {:?} {:?}", current_index, id));
-                if current_index == id {
-                    ConnectionManager::set_current_id(None);
-                } else {
-                    ConnectionManager::set_current_id(id);
+            CollapsingHeader::new(format!(
+                "{} ({})",
+                t!("label.connection_ports"),
+                self.ports.len()
+            ))
+            .id_source(("connection_ports", self.id))
+            .show(ui, |ui| {
+                if self.ports.is_empty() {
+                    ui.label(t!("label.connection_no_ports"));
+                }
+                for port in &self.ports {
+                    ui.label(port);
                 }
+            });
+
+            CollapsingHeader::new(t!("label.connection_history"))
+                .id_source(("connection_history", self.id))
+                .show(ui, |ui| {
+                    ScrollArea::vertical()
+                        .id_source(("connection_history_scroll", self.id))
+                        .max_height(120.0)
+                        .show(ui, |ui| {
+                            for change in &self.history {
+                                ui.label(
+                                    RichText::new(format!("{}: {}", change.when(), change.value))
+                                        .monospace(),
+                                );
+                            }
+                        });
+                });
+
+            if ui.button(button_text).clicked() {
+                action = ConnectionAction::ToggleConnecting(self.id);
             }
             if ui.button(t!("button.rename")).clicked() {
-                *CURRENT_CONN_ID_FOR_RENAME.lock().unwrap() = id;
-                *self.change_conn_name_modal_open = true;
+                action = ConnectionAction::Rename(self.id);
             }
             if ui.button(t!("button.remove")).clicked() {
-                *ANOTHER_ID_BUFFER.lock().unwrap() = id;
-                ModalManager::set_modal(3);
-                //ConnectionManager::remove_connection(id);
+                action = ConnectionAction::Remove(self.id);
             }
         });
-        let _collapsing_id = ui.make_persistent_id(self.conn.get_id());
+        action
     }
 }
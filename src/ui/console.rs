@@ -0,0 +1,47 @@
+use egui::{Label, RichText, ScrollArea, Ui};
+
+use crate::model::console::Console;
+use crate::model::value_format::ValueFormat;
+use crate::storage::toasts::ToastsManager;
+
+impl Console {
+    pub fn show(&mut self, ui: &mut Ui, id: usize) {
+        ui.horizontal(|ui| {
+            ValueFormat::select(ui, ("console_format", id), &mut self.format);
+            ui.label(t!("common.format"));
+            if ui.button(t!("console.button.clear")).clicked() {
+                self.clear_output();
+            }
+        });
+        ui.separator();
+        ScrollArea::vertical()
+            .id_source(("console_output", id))
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                ui.add(Label::new(RichText::new(self.output_text()).monospace()).wrap(true));
+            });
+        ui.separator();
+        // text typed but not sent yet is kept by egui
+        let buffer_id = ui.make_persistent_id(("console_input", id));
+        let mut buffer: String = ui.data_mut(|d| d.get_temp(buffer_id)).unwrap_or_default();
+        ui.horizontal(|ui| {
+            let response = ui.text_edit_singleline(&mut buffer);
+            let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button(t!("console.button.send")).clicked() || entered {
+                let text = std::mem::take(&mut buffer);
+                if let Err(word) = self.queue_input(&text) {
+                    buffer = text;
+                    ToastsManager::show_err(
+                        format!("{}: {}", t!("console.error.bad_value"), word),
+                        10,
+                    );
+                }
+                response.request_focus();
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(buffer_id, buffer));
+        ui.label(format!("{}: {}", t!("console.queued"), self.queued_input()));
+    }
+}
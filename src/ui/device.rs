@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use egui::{Color32, Context, Ui};
+
+use crate::model::device::{Device, DeviceKind};
+use crate::project::document::WiringMode;
+use crate::project::schema::PortRef;
+
+use super::wiring::port_button;
+
+/// Actions that need access to other components, handled by SvaUI
+#[derive(Debug, PartialEq, Clone)]
+pub enum DeviceAction {
+    DoNothing,
+    /// Port was clicked while connecting or disconnecting
+    PortClicked(PortRef),
+    /// Rom with id wants its contents loaded from file
+    LoadRom(usize),
+}
+
+impl DeviceKind {
+    fn show(&mut self, ui: &mut Ui, id: usize) -> DeviceAction {
+        match self {
+            DeviceKind::Console(console) => console.show(ui, id),
+            DeviceKind::Display(display) => display.show(ui, id),
+            DeviceKind::Clock(clock) => clock.show(ui, id),
+            DeviceKind::LedBar(leds) => leds.show(ui, id),
+            DeviceKind::SevenSegment(segments) => segments.show(ui, id),
+            DeviceKind::Switches(switches) => switches.show(ui, id),
+            DeviceKind::Buttons(buttons) => buttons.show(ui, id),
+            DeviceKind::Keypad(keypad) => keypad.show(ui, id),
+            DeviceKind::Rom(rom) => {
+                if rom.show(ui, id) {
+                    return DeviceAction::LoadRom(id);
+                }
+            }
+        }
+        DeviceAction::DoNothing
+    }
+}
+
+impl Device {
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        wiring: WiringMode,
+        names: &HashMap<usize, String>,
+    ) -> DeviceAction {
+        let mut action = DeviceAction::DoNothing;
+        let id = self.get_id();
+        egui::Window::new(self.get_name())
+            .id(egui::Id::new(format!("device:{}", id)))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, name) in self.kind.port_names().iter().enumerate() {
+                        ui.separator();
+                        ui.label(*name);
+                        let port = self.port(index);
+                        if port_button(ui, &port, wiring, names, Color32::LIGHT_GRAY).clicked() {
+                            action = DeviceAction::PortClicked(PortRef::Device {
+                                device: id,
+                                port: index,
+                            });
+                        }
+                    }
+                });
+                ui.separator();
+                let kind_action = self.kind.show(ui, id);
+                if kind_action != DeviceAction::DoNothing {
+                    action = kind_action;
+                }
+            });
+        action
+    }
+}
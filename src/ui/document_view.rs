@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use egui::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::project::circuit::Circuit;
+use crate::project::document::Document;
+
+use super::ram_window::RamWidow;
+use super::sva_window::SVAWindow;
+use super::watch_window::WatchWindow;
+use super::wiring_canvas::{CanvasAction, WiringCanvas};
+
+/// Windows of one tab, kept next to its document
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DocumentView {
+    /// Watchpoints of vms and rams of this project
+    pub watch_window: WatchWindow,
+
+    /// Node view of vms, rams and connections between them
+    pub canvas: WiringCanvas,
+
+    /// Windows of vms by vm id, created when vm is shown first time
+    #[serde(skip)]
+    pub vm_windows: HashMap<usize, SVAWindow>,
+
+    /// Windows of rams by ram id, created when ram is shown first time
+    #[serde(skip)]
+    pub ram_windows: HashMap<usize, RamWidow>,
+}
+
+impl DocumentView {
+    /// Forgets windows and canvas nodes of components removed from circuit
+    pub fn retain_existing(&mut self, circuit: &Circuit) {
+        let vm_ids: Vec<usize> = circuit.vms().iter().map(|vm| vm.get_id()).collect();
+        let ram_ids: Vec<usize> = circuit.rams().iter().map(|ram| ram.get_id()).collect();
+        let device_ids: Vec<usize> = circuit
+            .devices()
+            .iter()
+            .map(|device| device.get_id())
+            .collect();
+        self.vm_windows.retain(|id, _| vm_ids.contains(id));
+        self.ram_windows.retain(|id, _| ram_ids.contains(id));
+        self.canvas.retain(&vm_ids, &ram_ids, &device_ids);
+    }
+
+    /// Shows watch window with current values of watched targets
+    pub fn show_watch_window(&mut self, ctx: &Context, document: &mut Document) {
+        let labels = document.watch_labels();
+        self.watch_window
+            .show(ctx, &mut document.watchpoints, &labels);
+    }
+
+    /// Shows wiring canvas and applies wires dragged or removed on it
+    pub fn show_canvas(&mut self, ctx: &Context, document: &mut Document) {
+        if !self.canvas.is_open {
+            return;
+        }
+        match self.canvas.show(ctx, &document.circuit) {
+            CanvasAction::DoNothing => {}
+            CanvasAction::Wire(from, to) => document.wire(from, to),
+            CanvasAction::Disconnect(port) => document.disconnect_port(port),
+        }
+    }
+}
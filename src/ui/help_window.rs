@@ -165,10 +165,17 @@ impl HelpWindow {
             It can be started, stopped, halted, reset, and steeped through
             Pressing on registers except flag will change their display format 
             
-            Stack stores 32 values, pushing to full stack overrides it's top,
+            Vm is added with dialog choosing its name, stack, stack depth and delay,
+            which can be changed later with settings button in its window
+            Stack stores 32 values unless changed, pushing to full stack overrides it's top,
             pop'ing from empty stack pops 0
 
-            Ram stores 512 values, that can be written and read
+            Code can be opened from and saved to .sva text files with buttons under editor
+            Vm stays linked to the file, and with reloading turned on its code is
+            replaced whenever the file changes on disk
+
+            Ram stores values, that can be written and read, 512 unless changed
+            Its size, from 16 to 65536 values, is chosen in add menu and can be changed in its window
             Ram refreshes every frame
             Refresh copies values between data port nad ram value 
             designated by the value in index port
@@ -180,6 +187,46 @@ impl HelpWindow {
             mode        determines wether ram is in read or wite mode,
                         0 - write mode, anything else - read mode
 
+            Values can be imported from file and exported to file, whole ram or range
+            Format is chosen by extension: .bin or .dat raw i32 little endian,
+            .csv comma separated, .hex Intel HEX, any other one value per line
+
+            Console prints values and lets user type input
+            Port holding 0 is empty, whoever takes value from port writes 0 back to it
+
+            It has 2 ports:
+            output      value written here is printed and the port is set to 0
+            input       when empty, console puts next typed value here,
+                        program should write 0 after reading it
+            In unicode format typed text is sent as characters followed by new line (10)
+
+            Display shows pixels, 32x32 unless changed in its window
+
+            It has 4 ports:
+            x, y        position of pixel
+            color       colour of pixel as 0xRRGGBB, 0 is black
+            write       non zero value sets pixel at x, y to color,
+                        display then sets this port to 0
+
+            Clock writes to its out port every period, set in ms
+            or in ticks of lockstep scheduler
+            pulse       writes 1, program should write 0 after noticing it
+            counter     writes number of periods since clock was reset
+
+            Leds show bits of value on their in port, lowest bit on the right
+            Seven segment display shows value on its in port as 1 to 4 digits
+            in base from 2 to 16, both only read the port and leave value on it
+
+            Switches write their state to out port whenever it changes,
+            each switch is one bit, switch 0 is least significant
+            Push buttons write bits of buttons held down to out port
+            Keypad feeds pressed keys to empty key port as characters, like console input
+
+            Rom has ports index, data and mode like ram, but its values are loaded
+            from file and can't be changed by program
+            In read mode it puts value at index on data port, in write mode it does nothing
+            Files use the same formats as ram import
+
         "#;
         let vm_explanation_pl = r#"
             Maszyna wirtualna wykonuje instrukcje modyfikujące jej stan
@@ -194,10 +241,17 @@ impl HelpWindow {
             Można go uruchomić, zatrzymać, zatrzymać, zresetować i przesiąknąć
             Naciśnięcie na rejestry z wyjątkiem flagi spowoduje zmianę ich formatu wyświetlania
         
-            Stos przechowuje 32 wartości, pchanie do pełnego stosu zastępuje jego szczyt,
+            Maszyna jest dodawana oknem wyboru nazwy, stosu, jego głębokości i opóźnienia,
+            które można później zmienić przyciskiem ustawień w jej oknie
+            Stos przechowuje 32 wartości o ile nie zmieniono, pchanie do pełnego stosu zastępuje jego szczyt,
             zdejmowanie z pustego stosu zdejmuje 0
 
-            Ram przechowuje 512 wartości, które można zapisywać i odczytywać
+            Kod można otworzyć z pliku tekstowego .sva i zapisać do niego przyciskami pod edytorem
+            Maszyna pozostaje połączona z plikiem, a przy włączonym ponownym wczytywaniu
+            jej kod jest zastępowany po każdej zmianie pliku na dysku
+
+            Ram przechowuje wartości, które można zapisywać i odczytywać, 512 o ile nie zmieniono
+            Jego rozmiar, od 16 do 65536 wartości, wybiera się w menu dodawania i można go zmienić w jego oknie
             Ram odświeża każdą klatkę
             Odśwież kopiuje wartości pomiędzy portem danych a wartością pamięci RAM
             oznaczony przez wartość w porcie indeksu
@@ -208,6 +262,46 @@ impl HelpWindow {
                             lub od siebie do barana
             tryb określa    czy pamięć RAM jest w trybie odczytu czy zapisu,
                             0 - tryb zapisu, cokolwiek innego - tryb odczytu
+
+            Wartości można importować z pliku i eksportować do pliku, cały ram lub zakres
+            Format zależy od rozszerzenia: .bin lub .dat surowe i32 little endian,
+            .csv wartości oddzielone przecinkami, .hex Intel HEX, każde inne jedna wartość w linii
+
+            Konsola wypisuje wartości i pozwala użytkownikowi wpisywać dane
+            Port zawierający 0 jest pusty, kto pobiera wartość z portu zapisuje do niego 0
+
+            Posiada 2 porty:
+            output          zapisana tu wartość jest wypisywana, a port ustawiany na 0
+            input           gdy jest pusty, konsola umieszcza w nim kolejną wpisaną wartość,
+                            program powinien zapisać 0 po jej odczytaniu
+            W formacie unicode wpisany tekst jest wysyłany jako znaki zakończone nową linią (10)
+
+            Wyświetlacz pokazuje piksele, 32x32 o ile nie zmieniono tego w jego oknie
+
+            Posiada 4 porty:
+            x, y            pozycja piksela
+            color           kolor piksela jako 0xRRGGBB, 0 to czarny
+            write           niezerowa wartość ustawia piksel x, y na kolor color,
+                            po czym wyświetlacz ustawia ten port na 0
+
+            Zegar zapisuje do portu out co okres, ustawiany w ms
+            lub w taktach harmonogramu synchronicznego
+            impuls          zapisuje 1, program powinien zapisać 0 po jej zauważeniu
+            licznik         zapisuje liczbę okresów od zresetowania zegara
+
+            Diody pokazują bity wartości na porcie in, najniższy bit po prawej
+            Wyświetlacz siedmiosegmentowy pokazuje wartość na porcie in jako od 1 do 4 cyfr
+            w podstawie od 2 do 16, oba tylko odczytują port i zostawiają na nim wartość
+
+            Przełączniki zapisują swój stan do portu out przy każdej zmianie,
+            każdy przełącznik to jeden bit, przełącznik 0 jest najmniej znaczący
+            Przyciski zapisują do portu out bity wciśniętych przycisków
+            Klawiatura podaje wciśnięte klawisze do pustego portu key jako znaki, jak wejście konsoli
+
+            Rom ma porty index, data i mode jak ram, ale jego wartości są wczytywane
+            z pliku i program nie może ich zmienić
+            W trybie odczytu umieszcza wartość spod indeksu na porcie data, w trybie zapisu nic nie robi
+            Pliki mają te same formaty co import ramu
         "#;
 
         let vm_explanation = match self.language {
@@ -1,13 +1,35 @@
 use egui::{Context, Ui};
-use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
-pub enum ValueFormat {
-    #[default]
-    Dec,
-    Bin,
-    Hex,
-    Unicode,
+use crate::model::value_format::ValueFormat;
+
+impl ValueFormat {
+    /// Combo box choosing format, shared by rams and devices
+    pub fn select(ui: &mut Ui, id_source: impl std::hash::Hash, format: &mut ValueFormat) {
+        egui::ComboBox::from_id_source(id_source)
+            .selected_text(format!("{:?}", format))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(
+                    format,
+                    ValueFormat::Dec,
+                    t!("ram_window.selectable_value.format.decimal"),
+                );
+                ui.selectable_value(
+                    format,
+                    ValueFormat::Bin,
+                    t!("ram_window.selectable_value.format.binary"),
+                );
+                ui.selectable_value(
+                    format,
+                    ValueFormat::Hex,
+                    t!("ram_window.selectable_value.format.hexadecimal"),
+                );
+                ui.selectable_value(
+                    format,
+                    ValueFormat::Unicode,
+                    t!("ram_window.selectable_value.format.unicode"),
+                );
+            });
+    }
 }
 
 #[derive(Default, Clone)]
@@ -37,12 +59,22 @@ impl IndicatorWidget {
         self
     }
 
-    pub fn show(&mut self, _ctx: &Context, ui: &mut Ui) {
+    /// Returns true if watching value was requested from context menu
+    pub fn show(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut watch = false;
         let formatted_value = self.write_in_format();
         ui.label(&self.label);
-        if ui.button(formatted_value).clicked() {
+        let response = ui.button(formatted_value);
+        if response.clicked() {
             self.switch_format();
         }
+        response.context_menu(|ui| {
+            if ui.button(t!("watch.add")).clicked() {
+                watch = true;
+                ui.close_menu();
+            }
+        });
+        watch
     }
 
     fn write_in_format(&mut self) -> String {
@@ -0,0 +1,29 @@
+use egui::Ui;
+
+use crate::model::keypad::Keypad;
+
+/// Keys in order they are shown in
+const KEYS: [char; 12] = ['1', '2', '3', '4', '5', '6', '7', '8', '9', '*', '0', '#'];
+
+impl Keypad {
+    pub fn show(&mut self, ui: &mut Ui, id: usize) {
+        egui::Grid::new(("keypad", id)).show(ui, |ui| {
+            for row in KEYS.chunks(3) {
+                for key in row {
+                    let button =
+                        egui::Button::new(key.to_string()).min_size(egui::vec2(32.0, 32.0));
+                    if ui.add(button).clicked() {
+                        self.press(*key);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(format!("{}: {}", t!("keypad.queued"), self.queued_keys()));
+            if ui.button(t!("keypad.button.clear")).clicked() {
+                self.clear();
+            }
+        });
+    }
+}
@@ -0,0 +1,48 @@
+use egui::{Color32, Pos2, Sense, Stroke, Ui, Vec2};
+
+use crate::model::led_bar::LedBar;
+
+const LED_SIZE: f32 = 16.0;
+
+impl LedBar {
+    pub fn show(&mut self, ui: &mut Ui, _id: usize) {
+        ui.horizontal(|ui| {
+            ui.label(t!("led_bar.bits"));
+            let mut bits = self.bits();
+            if ui
+                .add(egui::DragValue::new(&mut bits).clamp_range(1..=32))
+                .changed()
+            {
+                self.set_bits(bits);
+            }
+            ui.label(format!("= {}", self.value()));
+        });
+        ui.separator();
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(self.bits() as f32 * (LED_SIZE + 4.0), LED_SIZE + 14.0),
+            Sense::hover(),
+        );
+        let text_color = ui.style().visuals.text_color();
+        for index in 0..self.bits() {
+            let bit = self.bits() - 1 - index;
+            let center = response.rect.min
+                + Vec2::new(
+                    index as f32 * (LED_SIZE + 4.0) + LED_SIZE / 2.0 + 2.0,
+                    LED_SIZE / 2.0,
+                );
+            if self.is_lit(bit) {
+                painter.circle_filled(center, LED_SIZE / 2.0, Color32::RED);
+            } else {
+                painter.circle_filled(center, LED_SIZE / 2.0, Color32::from_rgb(60, 0, 0));
+            }
+            painter.circle_stroke(center, LED_SIZE / 2.0, Stroke::new(1.0, text_color));
+            painter.text(
+                Pos2::new(center.x, response.rect.max.y),
+                egui::Align2::CENTER_BOTTOM,
+                bit.to_string(),
+                egui::FontId::proportional(9.0),
+                text_color,
+            );
+        }
+    }
+}
@@ -1,8 +1,22 @@
 pub mod app;
+pub mod clock;
 pub mod component_list_widget;
 pub mod connection_widget;
+pub mod console;
+pub mod device;
+pub mod document_view;
 pub mod help_window;
 pub mod indicator_widget;
+pub mod keypad;
+pub mod led_bar;
+pub mod pixel_display;
+pub mod push_buttons;
 pub mod ram_window;
+pub mod rom;
+pub mod seven_segment;
 pub mod sva_window;
+pub mod switches;
 pub mod syntax;
+pub mod watch_window;
+pub mod wiring;
+pub mod wiring_canvas;
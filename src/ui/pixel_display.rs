@@ -0,0 +1,69 @@
+use egui::{Color32, Rect, Sense, Ui, Vec2};
+
+use crate::model::pixel_display::{PixelDisplay, MAX_DISPLAY_SIZE};
+
+/// Size of pixel on screen of display that wasn't scaled yet
+const DEFAULT_SCALE: f32 = 8.0;
+
+impl PixelDisplay {
+    pub fn show(&mut self, ui: &mut Ui, id: usize) {
+        let (mut width, mut height) = (self.width(), self.height());
+        // size of pixel on screen is kept by egui
+        let scale_id = ui.make_persistent_id(("display_scale", id));
+        let mut scale = ui
+            .data_mut(|d| d.get_persisted(scale_id))
+            .unwrap_or(DEFAULT_SCALE);
+        ui.horizontal(|ui| {
+            ui.label(t!("display.size"));
+            let width_changed = ui
+                .add(egui::DragValue::new(&mut width).clamp_range(1..=MAX_DISPLAY_SIZE))
+                .changed();
+            ui.label("x");
+            let height_changed = ui
+                .add(egui::DragValue::new(&mut height).clamp_range(1..=MAX_DISPLAY_SIZE))
+                .changed();
+            if width_changed || height_changed {
+                self.resize(width, height);
+            }
+            ui.separator();
+            ui.add(egui::Slider::new(&mut scale, 2.0..=16.0).text(t!("display.scale")));
+            if ui.button(t!("display.button.clear")).clicked() {
+                self.clear();
+            }
+        });
+        ui.separator();
+        ui.push_id(("display", id), |ui| {
+            let size = Vec2::new(self.width() as f32, self.height() as f32) * scale;
+            let (response, painter) = ui.allocate_painter(size, Sense::hover());
+            painter.rect_filled(response.rect, 0.0, Color32::BLACK);
+            for y in 0..self.height() {
+                for x in 0..self.width() {
+                    let color = self.pixel(x, y).unwrap_or(0);
+                    if color == 0 {
+                        continue;
+                    }
+                    let min = response.rect.min + Vec2::new(x as f32, y as f32) * scale;
+                    painter.rect_filled(
+                        Rect::from_min_size(min, Vec2::splat(scale)),
+                        0.0,
+                        to_color(color),
+                    );
+                }
+            }
+            if let Some(pos) = response.hover_pos() {
+                let x = ((pos.x - response.rect.min.x) / scale) as usize;
+                let y = ((pos.y - response.rect.min.y) / scale) as usize;
+                if let Some(color) = self.pixel(x, y) {
+                    response.on_hover_text(format!("x: {}, y: {}, 0x{:06X}", x, y, color));
+                }
+            }
+        });
+        ui.data_mut(|d| d.insert_persisted(scale_id, scale));
+    }
+}
+
+/// Colour written as `0xRRGGBB`
+fn to_color(value: i32) -> Color32 {
+    let [_, r, g, b] = value.to_be_bytes();
+    Color32::from_rgb(r, g, b)
+}
@@ -0,0 +1,30 @@
+use egui::{Sense, Ui};
+
+use crate::model::push_buttons::PushButtons;
+
+impl PushButtons {
+    pub fn show(&mut self, ui: &mut Ui, _id: usize) {
+        ui.horizontal(|ui| {
+            ui.label(t!("push_buttons.count"));
+            let mut count = self.count();
+            if ui
+                .add(egui::DragValue::new(&mut count).clamp_range(1..=16))
+                .changed()
+            {
+                self.set_count(count);
+            }
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            for button in (0..self.count()).rev() {
+                let response = ui.add(
+                    egui::Button::new(button.to_string())
+                        .selected(self.is_pressed(button))
+                        .sense(Sense::drag()),
+                );
+                // held only while pointer is down on it
+                self.set_pressed(button, response.is_pointer_button_down_on());
+            }
+        });
+    }
+}
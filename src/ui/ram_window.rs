@@ -1,321 +1,186 @@
-use egui::{Button, Color32, Context, Stroke, Ui};
-use serde::{Deserialize, Serialize};
-use simple_virtual_assembler::components::ram::Ram;
-
-use crate::storage::{
-    connections_manager::{ConnectionManager, CONNECTIONS},
-    modals_manager::{ModalManager, MODAL_INDEX_BUFFER, RAM_ID},
-    toasts::ToastsManager,
-};
-
-use super::indicator_widget::ValueFormat;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use egui::{Color32, Context, Ui};
+
+use crate::model::memory::{Memory, MAX_RAM_SIZE, MIN_RAM_SIZE};
+use crate::model::value_format::ValueFormat;
+use crate::model::watchpoint::WatchTarget;
+use crate::project::document::WiringMode;
+use crate::project::schema::{PortRef, RamPort};
+
+use super::wiring::port_button;
+
+/// Cells in one row of grid
+const ROW_LEN: usize = 8;
+
+/// Actions that need access to other components, handled by SvaUI
+#[derive(Debug, PartialEq, Clone)]
+pub enum RamAction {
+    DoNothing,
+    /// Port was clicked while connecting or disconnecting
+    PortClicked(PortRef),
+    /// Cell at index was clicked to set its value
+    SetValue(usize),
+    /// Every cell should be set to 0
+    Zero,
+    Watch(WatchTarget),
+    /// Ram should get new size, done by circuit so ports are connected again
+    Resize(usize),
+    /// Values should be loaded from file
+    Import,
+    /// Values in range should be written to file
+    Export(Range<usize>),
+}
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Window showing ram, holds only state of window itself
+#[derive(Default)]
 pub struct RamWidow {
-    id: usize,
-    name: String,
-    /// Is widow open
-    pub is_open: bool,
-    pub ram: Ram,
     format: ValueFormat,
+    /// Size typed in window, not applied yet
+    size_input: Option<usize>,
+    /// Cells written by export, None to export whole ram
+    export_range: Option<Range<usize>>,
 }
 
 impl RamWidow {
-    pub fn new(id: usize) -> Self {
-        Self {
-            is_open: true,
-            ram: Ram::new().with_id(id).with_size(512),
-            id,
-            name: format!("ram:{}", id),
-            format: Default::default(),
-        }
-    }
-    pub fn get_id(&self) -> usize {
-        self.id
-    }
-
-    pub fn get_name(&self) -> String {
-        self.name.clone()
-    }
-    pub fn set_name(&mut self, name: String) {
-        self.name = name;
-    }
-
-    pub fn format_value(&mut self, value: i32) -> String {
-        match self.format {
-            ValueFormat::Dec => format!("{}", value),
-            ValueFormat::Hex => format!("0x{:X}", value),
-            ValueFormat::Bin => format!("0b{:b}", value),
-            ValueFormat::Unicode => {
-                if value < 0 {
-                    t!("common.invalid_char")
-                } else {
-                    if let Some(char) = char::from_u32(value as u32) {
-                        return format!("\'{}\'", char);
-                    }
-                    t!("common.invalid_char")
-                }
-            }
+    fn show_port(
+        ram: &Memory,
+        ui: &mut Ui,
+        port: RamPort,
+        wiring: WiringMode,
+        names: &HashMap<usize, String>,
+    ) -> RamAction {
+        ui.separator();
+        ui.label(port.name());
+        let ram_port = match port {
+            RamPort::Index => ram.ram.get_index_port(),
+            RamPort::Data => ram.ram.get_data_port(),
+            RamPort::Mode => ram.ram.get_mode_port(),
+        };
+        if port_button(ui, &ram_port, wiring, names, Color32::LIGHT_GRAY).clicked() {
+            return RamAction::PortClicked(PortRef::Ram {
+                ram: ram.get_id(),
+                port,
+            });
         }
+        RamAction::DoNothing
     }
 
-    pub fn refresh(&mut self) {
-        self.ram.refresh();
-    }
-
-    pub fn show(&mut self, ctx: &Context, _ui: &mut Ui) {
-        egui::Window::new(&self.name)
-            .id(egui::Id::new(format!("ram:{}", self.id)))
+    pub fn show(
+        &mut self,
+        ram: &Memory,
+        ctx: &Context,
+        _ui: &mut Ui,
+        wiring: WiringMode,
+        names: &HashMap<usize, String>,
+    ) -> RamAction {
+        let mut action = RamAction::DoNothing;
+        let id = ram.get_id();
+        let size = ram.size();
+        egui::Window::new(ram.get_name())
+            .id(egui::Id::new(format!("ram:{}", id)))
             .show(ctx, |ui| {
-                let values = self.ram.get_data_ref().clone();
                 ui.horizontal(|ui| {
-                    ui.separator();
-                    ui.label("index");
-
-                    let already_connected = match self.ram.get_index_port() {
-                        simple_virtual_assembler::components::port::Port::Connected(_, _) => true,
-                        simple_virtual_assembler::components::port::Port::Disconnected(_) => false,
-                    };
-
-                    let mut port_color = Color32::LIGHT_GRAY;
-
-                    if ConnectionManager::get_current_id_index().is_some() && !already_connected {
-                        let in_dark_mode = ui.style().visuals.dark_mode;
-
-                        port_color = if in_dark_mode {
-                            Color32::YELLOW
-                        } else {
-                            Color32::BLUE
-                        }
-                    } else if ConnectionManager::in_disconnect_mode() && already_connected {
-                        port_color = Color32::DARK_RED;
-                    }
-
-                    let index_port_button = Button::new(format!("{}", self.ram.get_index_port()))
-                        .stroke(Stroke::new(1.0, port_color));
-
-                    if ui.add_enabled(true, index_port_button).clicked() {
-                        if let Some(conn_index) = ConnectionManager::get_current_id_index() {
-                            if let Some(conn) = ConnectionManager::get_connections()
-                                .lock()
-                                .unwrap()
-                                .get_mut(conn_index)
-                            {
-                                if !already_connected {
-                                    let id = format!("R{}:index", self.get_id().clone());
-
-                                    self.ram.connect_index_port(conn);
-                                    conn.add_port_id(id);
-                                } else {
-                                    ToastsManager::show_info(
-                                        t!("toast_info.can_connect_connected_port"),
-                                        10,
-                                    )
-                                }
-                            }
-                        } else if ConnectionManager::in_disconnect_mode() {
-                            let conn_id = self.ram.get_index_port().get_conn_id();
-                            let conn_index = ConnectionManager::get_connection_index_by_id(conn_id);
-                            if let Some(conn_i) = conn_index {
-                                let mut conns_lock = CONNECTIONS.lock().unwrap();
-                                let conn = conns_lock.get_mut(conn_i);
-                                if let Some(conn_ref) = conn {
-                                    self.ram.disconnect_index_port();
-                                    let id = format!("R{}:index", self.get_id().clone());
-                                    conn_ref.remove_port_id(id);
-                                }
-                            }
-                        }
-                    }
-                    if let Some(id) = self.ram.get_index_port().get_conn_id() {
-                        if let Some(conn_name) = ConnectionManager::get_name(id) {
-                            ui.label(conn_name);
-                        }
-                    }
-
-                    // ---------------------------------------------------------------------------------------------------------------------
-                    ui.separator();
-
-                    let already_connected = match self.ram.get_data_port() {
-                        simple_virtual_assembler::components::port::Port::Connected(_, _) => true,
-                        simple_virtual_assembler::components::port::Port::Disconnected(_) => false,
-                    };
-
-                    let mut port_color = Color32::GRAY;
-
-                    if ConnectionManager::get_current_id_index().is_some() && !already_connected {
-                        let in_dark_mode = ui.style().visuals.dark_mode;
-
-                        port_color = if in_dark_mode {
-                            Color32::YELLOW
-                        } else {
-                            Color32::BLUE
+                    for port in [RamPort::Index, RamPort::Data, RamPort::Mode] {
+                        let port_action = RamWidow::show_port(ram, ui, port, wiring, names);
+                        if port_action != RamAction::DoNothing {
+                            action = port_action;
                         }
-                    } else if ConnectionManager::in_disconnect_mode() && already_connected {
-                        port_color = Color32::DARK_RED;
                     }
-
-                    let data_port_button = Button::new(format!("{}", self.ram.get_data_port()))
-                        .stroke(Stroke::new(1.0, port_color));
-
-                    ui.label("data");
-                    if ui.add_enabled(true, data_port_button).clicked() {
-                        if let Some(conn_index) = ConnectionManager::get_current_id_index() {
-                            if let Some(conn) = ConnectionManager::get_connections()
-                                .lock()
-                                .unwrap()
-                                .get_mut(conn_index)
-                            {
-                                if !already_connected {
-                                    let id = format!("R{}:data", self.get_id().clone());
-
-                                    //self.ram.get_index_port().connect(conn);
-                                    self.ram.connect_data_port(conn);
-                                    conn.add_port_id(id);
-                                } else {
-                                    ToastsManager::show_info(
-                                        t!("toast_info.can_connect_connected_port"),
-                                        10,
-                                    )
-                                }
-                            }
-                        } else if ConnectionManager::in_disconnect_mode() {
-                            let conn_id = self.ram.get_data_port().get_conn_id();
-                            let conn_index = ConnectionManager::get_connection_index_by_id(conn_id);
-                            if let Some(conn_i) = conn_index {
-                                let mut conns_lock = CONNECTIONS.lock().unwrap();
-                                let conn = conns_lock.get_mut(conn_i);
-                                if let Some(conn_ref) = conn {
-                                    self.ram.disconnect_data_port();
-                                    let id = format!("R{}:data", self.get_id().clone());
-                                    conn_ref.remove_port_id(id);
-                                }
-                            }
-                        }
-                    }
-                    if let Some(id) = self.ram.get_data_port().get_conn_id() {
-                        if let Some(conn_name) = ConnectionManager::get_name(id) {
-                            ui.label(conn_name);
-                        }
-                    }
-                    // ---------------------------------------------------------------------------------------------------------------------
-
-                    ui.separator();
-                    ui.label("mode");
-
-                    let already_connected = match self.ram.get_mode_port() {
-                        simple_virtual_assembler::components::port::Port::Connected(_, _) => true,
-                        simple_virtual_assembler::components::port::Port::Disconnected(_) => false,
-                    };
-
-                    let mut port_color = Color32::LIGHT_GRAY;
-
-                    if ConnectionManager::get_current_id_index().is_some() && !already_connected {
-                        let in_dark_mode = ui.style().visuals.dark_mode;
-
-                        port_color = if in_dark_mode {
-                            Color32::YELLOW
-                        } else {
-                            Color32::BLUE
-                        };
-                    } else if ConnectionManager::in_disconnect_mode() && already_connected {
-                        port_color = Color32::DARK_RED;
-                    }
-
-                    let data_mode_button = Button::new(format!("{}", self.ram.get_mode_port()))
-                        .stroke(Stroke::new(1.0, port_color));
-
-                    if ui.add_enabled(true, data_mode_button).clicked() {
-                        if let Some(conn_index) = ConnectionManager::get_current_id_index() {
-                            if let Some(conn) = ConnectionManager::get_connections()
-                                .lock()
-                                .unwrap()
-                                .get_mut(conn_index)
-                            {
-                                if !already_connected {
-                                    let id = format!("R{}:mode", self.get_id().clone());
-
-                                    //self.ram.get_index_port().connect(conn);
-                                    self.ram.connect_mode_port(conn);
-                                    conn.add_port_id(id);
-                                } else {
-                                    ToastsManager::show_info(
-                                        t!("toast_info.can_connect_connected_port"),
-                                        10,
-                                    )
-                                }
-                            }
-                        } else if ConnectionManager::in_disconnect_mode() {
-                            let conn_id = self.ram.get_mode_port().get_conn_id();
-                            let conn_index = ConnectionManager::get_connection_index_by_id(conn_id);
-                            if let Some(conn_i) = conn_index {
-                                let mut conns_lock = CONNECTIONS.lock().unwrap();
-                                let conn = conns_lock.get_mut(conn_i);
-                                if let Some(conn_ref) = conn {
-                                    self.ram.disconnect_mode_port();
-                                    let id = format!("R{}:mode", self.get_id().clone());
-                                    conn_ref.remove_port_id(id);
-                                }
-                            }
-                        }
-                    }
-                    if let Some(id) = self.ram.get_mode_port().get_conn_id() {
-                        if let Some(conn_name) = ConnectionManager::get_name(id) {
-                            ui.label(conn_name);
-                        }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(t!("ram_window.size"));
+                    let input = self.size_input.get_or_insert(size);
+                    ui.add(
+                        egui::DragValue::new(input)
+                            .clamp_range(MIN_RAM_SIZE..=MAX_RAM_SIZE)
+                            .speed(16),
+                    );
+                    if *input != size && ui.button(t!("button.apply")).clicked() {
+                        action = RamAction::Resize(*input);
+                        self.size_input = None;
                     }
                 });
                 ui.separator();
                 ui.collapsing(t!("ram_window.collapsing.values"), |ui| {
                     ui.horizontal(|ui| {
                         if ui.button(t!("button.zero_values")).clicked() {
-                            self.ram.zero_data();
+                            action = RamAction::Zero;
                         }
-                        egui::ComboBox::from_label("format")
-                            .selected_text(format!("{:?}", self.format))
-                            .show_ui(ui, |ui| {
-                                ui.selectable_value(
-                                    &mut self.format,
-                                    ValueFormat::Dec,
-                                    t!("ram_window.selectable_value.format.decimal"),
-                                );
-                                ui.selectable_value(
-                                    &mut self.format,
-                                    ValueFormat::Bin,
-                                    t!("ram_window.selectable_value.format.binary"),
-                                );
-                                ui.selectable_value(
-                                    &mut self.format,
-                                    ValueFormat::Hex,
-                                    t!("ram_window.selectable_value.format.hexadecimal"),
-                                );
-                                ui.selectable_value(
-                                    &mut self.format,
-                                    ValueFormat::Unicode,
-                                    t!("ram_window.selectable_value.format.unicode"),
-                                );
-                            });
+                        ValueFormat::select(ui, ("ram_format", id), &mut self.format);
+                        ui.label(t!("common.format"));
                     });
-                    ui.separator();
-                    egui::ScrollArea::new(true).show(ui, |ui| {
-                        for i in 0..(512 / 8) {
-                            ui.horizontal(|ui| {
-                                for j in 0..8 {
-                                    let index = i * 8 + j;
-                                    if ui.button(self.format_value(values[index])).clicked() {
-                                        ModalManager::set_modal(1);
-                                        *RAM_ID.lock().unwrap() = Some(self.get_id());
-                                        *MODAL_INDEX_BUFFER.lock().unwrap() = Some(index);
-                                    };
-                                }
-                            });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(t!("ram_window.button.import"))
+                            .on_hover_text(t!("ram_window.file_formats"))
+                            .clicked()
+                        {
+                            action = RamAction::Import;
+                        }
+                        if ui
+                            .button(t!("ram_window.button.export"))
+                            .on_hover_text(t!("ram_window.file_formats"))
+                            .clicked()
+                        {
+                            let range = self.export_range.clone().unwrap_or(0..size);
+                            action = RamAction::Export(range);
+                        }
+                        let mut use_range = self.export_range.is_some();
+                        if ui
+                            .checkbox(&mut use_range, t!("ram_window.export_range"))
+                            .changed()
+                        {
+                            self.export_range = use_range.then_some(0..size);
+                        }
+                        if let Some(range) = &mut self.export_range {
+                            // ram could have been resized since range was chosen
+                            range.end = range.end.min(size);
+                            ui.add(egui::DragValue::new(&mut range.start).clamp_range(0..=size));
+                            ui.label("..");
+                            ui.add(egui::DragValue::new(&mut range.end).clamp_range(0..=size));
+                            range.start = range.start.min(range.end);
                         }
                     });
+                    ui.separator();
+                    // only visible rows are shown, so big rams stay responsive
+                    let row_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y;
+                    let rows = (size + ROW_LEN - 1) / ROW_LEN;
+                    egui::ScrollArea::new(true).max_height(400.0).show_rows(
+                        ui,
+                        row_height,
+                        rows,
+                        |ui, row_range| {
+                            for i in row_range {
+                                ui.horizontal(|ui| {
+                                    ui.weak(format!("{:>5}", i * ROW_LEN));
+                                    for j in 0..ROW_LEN {
+                                        let index = i * ROW_LEN + j;
+                                        let Some(value) = ram.get_value(index) else {
+                                            break;
+                                        };
+                                        let response = ui.button(self.format.format_value(value));
+                                        if response.clicked() {
+                                            action = RamAction::SetValue(index);
+                                        };
+                                        response.context_menu(|ui| {
+                                            if ui.button(t!("watch.add")).clicked() {
+                                                action = RamAction::Watch(WatchTarget::RamCell {
+                                                    ram_id: id,
+                                                    index,
+                                                });
+                                                ui.close_menu();
+                                            }
+                                        });
+                                    }
+                                });
+                            }
+                        },
+                    );
                 });
             });
-    }
-
-    pub fn set_value_at_index(&mut self, index: usize, value: i32) {
-        self.ram.set_value(index, value);
+        action
     }
 }
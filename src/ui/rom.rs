@@ -0,0 +1,36 @@
+use egui::{ScrollArea, Ui};
+
+use crate::model::rom::Rom;
+use crate::model::value_format::ValueFormat;
+
+impl Rom {
+    /// Shows values, returns true when user wants to load file
+    pub fn show(&mut self, ui: &mut Ui, id: usize) -> bool {
+        let mut load = false;
+        ui.horizontal(|ui| {
+            if ui.button(t!("rom.button.load")).clicked() {
+                load = true;
+            }
+            ValueFormat::select(ui, ("rom_format", id), &mut self.format);
+            ui.label(t!("common.format"));
+            ui.label(format!("{}: {}", t!("rom.size"), self.data().len()));
+        });
+        ui.separator();
+        let row_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y;
+        let rows = (self.data().len() + 7) / 8;
+        ScrollArea::vertical()
+            .id_source(("rom_values", id))
+            .max_height(300.0)
+            .show_rows(ui, row_height, rows, |ui, row_range| {
+                for row in row_range {
+                    ui.horizontal(|ui| {
+                        ui.weak(format!("{:>5}", row * 8));
+                        for value in self.data().iter().skip(row * 8).take(8) {
+                            ui.monospace(self.format.format_value(*value));
+                        }
+                    });
+                }
+            });
+        load
+    }
+}
@@ -0,0 +1,84 @@
+use egui::{Color32, Painter, Pos2, Rect, Rounding, Sense, Ui, Vec2};
+
+use crate::model::seven_segment::SevenSegment;
+
+const DIGIT_WIDTH: f32 = 32.0;
+const DIGIT_HEIGHT: f32 = 56.0;
+const SEGMENT_WIDTH: f32 = 5.0;
+
+/// Lit segments of digits 0-F, bits are segments a (top) to g (middle)
+const SEGMENTS: [u8; 16] = [
+    0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, 0x7F, 0x6F, 0x77, 0x7C, 0x39, 0x5E, 0x79, 0x71,
+];
+
+impl SevenSegment {
+    pub fn show(&mut self, ui: &mut Ui, _id: usize) {
+        ui.horizontal(|ui| {
+            ui.label(t!("seven_segment.digits"));
+            let mut digits = self.digit_count();
+            if ui
+                .add(egui::DragValue::new(&mut digits).clamp_range(1..=4))
+                .changed()
+            {
+                self.set_digit_count(digits);
+            }
+            ui.label(t!("seven_segment.base"));
+            let mut base = self.base();
+            if ui
+                .add(egui::DragValue::new(&mut base).clamp_range(2..=16))
+                .changed()
+            {
+                self.set_base(base);
+            }
+        });
+        ui.separator();
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(
+                self.digit_count() as f32 * (DIGIT_WIDTH + 8.0),
+                DIGIT_HEIGHT,
+            ),
+            Sense::hover(),
+        );
+        painter.rect_filled(response.rect, 2.0, Color32::BLACK);
+        for (index, digit) in self.digits().into_iter().enumerate() {
+            let min = response.rect.min + Vec2::new(index as f32 * (DIGIT_WIDTH + 8.0) + 4.0, 0.0);
+            draw_digit(&painter, min, SEGMENTS[digit as usize]);
+        }
+        response.on_hover_text(format!("{}", self.value()));
+    }
+}
+
+fn draw_digit(painter: &Painter, min: Pos2, segments: u8) {
+    let (w, h, s) = (DIGIT_WIDTH, DIGIT_HEIGHT, SEGMENT_WIDTH);
+    let half = h / 2.0;
+    // a, b, c, d, e, f, g
+    let rects = [
+        Rect::from_min_size(min + Vec2::new(s, 2.0), Vec2::new(w - 2.0 * s, s)),
+        Rect::from_min_size(
+            min + Vec2::new(w - s, s + 2.0),
+            Vec2::new(s, half - s - 2.0),
+        ),
+        Rect::from_min_size(
+            min + Vec2::new(w - s, half + 1.0),
+            Vec2::new(s, half - s - 2.0),
+        ),
+        Rect::from_min_size(min + Vec2::new(s, h - s - 2.0), Vec2::new(w - 2.0 * s, s)),
+        Rect::from_min_size(
+            min + Vec2::new(0.0, half + 1.0),
+            Vec2::new(s, half - s - 2.0),
+        ),
+        Rect::from_min_size(min + Vec2::new(0.0, s + 2.0), Vec2::new(s, half - s - 2.0)),
+        Rect::from_min_size(
+            min + Vec2::new(s, half - s / 2.0),
+            Vec2::new(w - 2.0 * s, s),
+        ),
+    ];
+    for (segment, rect) in rects.into_iter().enumerate() {
+        let color = if (segments >> segment) & 1 == 1 {
+            Color32::RED
+        } else {
+            Color32::from_rgb(40, 0, 0)
+        };
+        painter.rect_filled(rect, Rounding::same(1.0), color);
+    }
+}
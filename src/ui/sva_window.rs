@@ -1,400 +1,193 @@
+use egui::text_edit::TextEditOutput;
 use egui::Button;
 use egui::Color32;
+use egui::Rect;
+use egui::Sense;
 use egui::Stroke;
 use simple_virtual_assembler::vm::flag::Flag;
 
-use std::panic;
-
-use std::sync::Arc;
-use std::sync::Mutex;
-
-use std::thread::JoinHandle;
+use std::collections::HashMap;
 use std::time::Duration;
 
 use egui::Context;
 use egui::Ui;
 
-use simple_virtual_assembler::assembler::parsing_err::ParsingError;
-
-use simple_virtual_assembler::components::port::Port;
-use simple_virtual_assembler::vm::instruction::Instruction;
-use simple_virtual_assembler::vm::virtual_machine::{VirtualMachine, VmStatus};
+use simple_virtual_assembler::vm::virtual_machine::VmStatus;
 
 use egui_code_editor::{CodeEditor, ColorTheme};
 
-use simple_virtual_assembler::assembler::assembler::Assembler;
-
-use simple_virtual_assembler::language::Language;
-
-use crate::storage::connections_manager::ConnectionManager;
-use crate::storage::connections_manager::CONNECTIONS;
-use crate::storage::custom_logger::CustomLogger;
-use crate::storage::toasts::ToastsManager;
+use crate::model::vm::{Vm, VmSettings, MAX_STACK_SIZE};
+use crate::model::watchpoint::WatchTarget;
+use crate::project::document::WiringMode;
+use crate::project::schema::PortRef;
 
 use super::indicator_widget::IndicatorWidget;
 use super::syntax::sva_syntax;
-
-#[derive(serde::Deserialize, serde::Serialize)]
-pub struct SVAWindow {
-    /// Id
-    id: usize,
-    /// Tile
-    name: String,
-
-    /// Simple virtual machine
-    pub vm: Arc<Mutex<VirtualMachine>>,
-    /// Assembler for simple virtual machine
-    assembler: Assembler,
-    /// Code before assembly
-    code: String,
-    /// ( Currently useless ) Program to be executed by vm
-    program: Vec<Instruction>,
-    /// Error assembling code to program
-    parsing_error: Option<ParsingError>,
-    /// ( Currently useless ) Parsing error message
-    parsing_error_msg: String,
-    /// Language
-    language: Language,
-    /// 'Start' or 'Stop' text for button
-    control_button_text: String,
-
-    //  --------------------------------------RCs--------------------------------------
-    /// Connecting in progress
-
-    /// Connections
-
-    /// Disconnect port on clikc
-
-    //  -------------------------------------------------------------------------------
-    ///Delay ms
-    delay_ms: u64,
-
-    vm_state: (i32, usize, Flag, [i32; 4], [i32; 6], VmStatus, u32),
-
-    #[serde(skip)]
-    indicators: [IndicatorWidget; 13],
-
-    conn_ids: [Option<usize>; 4],
-
-    stack_present: bool,
-
-    stack_data: Vec<i32>,
-
-    max_hight: f32,
-    /// determine if window is open, closed windows get deleted
-    active: bool,
-
-    ports_collapsed: bool,
-
-    #[serde(skip)]
-    vm_join_handle: Option<JoinHandle<()>>,
-
-    vm_status: VmStatus,
-}
-
-impl Default for SVAWindow {
-    fn default() -> Self {
-        Self {
-            id: 0,
-            name: format!("{}", 0).to_owned(),
-            vm: Arc::new(Mutex::new(VirtualMachine::new())),
-            assembler: Assembler::new(),
-            code: String::new(),
-            program: Vec::new(),
-
-            parsing_error_msg: String::new(),
-            parsing_error: None,
-            language: Language::En,
-            control_button_text: "Start".to_owned(),
-
-            delay_ms: 1000,
-
-            vm_state: (0, 0, Flag::EQUAL, [0; 4], [0; 6], VmStatus::Initial, 0),
-
-            indicators: [
-                IndicatorWidget::new("acc".to_owned()),
-                IndicatorWidget::new("pc".to_owned()),
-                IndicatorWidget::new("flag".to_owned()),
-                IndicatorWidget::new("r0".to_owned()),
-                IndicatorWidget::new("r1".to_owned()),
-                IndicatorWidget::new("r2".to_owned()),
-                IndicatorWidget::new("r3".to_owned()),
-                IndicatorWidget::new("p0".to_owned()),
-                IndicatorWidget::new("p1".to_owned()),
-                IndicatorWidget::new("p2".to_owned()),
-                IndicatorWidget::new("p3".to_owned()),
-                IndicatorWidget::new("status".to_owned()),
-                IndicatorWidget::new(t!("sva_shell.indicator.delay").to_owned()),
-            ],
-            conn_ids: [None; 4],
-            stack_present: false,
-            stack_data: Vec::new(),
-            max_hight: 1000.0,
-            active: true,
-            ports_collapsed: false,
-            vm_join_handle: None,
-            vm_status: VmStatus::Initial,
-        }
-    }
+use super::wiring::port_button;
+
+/// Actions that need access to other components, handled by SvaUI
+#[derive(Debug, PartialEq, Clone)]
+pub enum VmAction {
+    DoNothing,
+    Step(usize),
+    StepBack(usize),
+    Watch(WatchTarget),
+    /// Port was clicked while connecting or disconnecting
+    PortClicked(PortRef),
+    /// Settings window of vm with id should be opened
+    OpenSettings(usize),
+    /// File dialog for opening source of vm with id should be opened
+    OpenSource(usize),
+    /// File dialog for saving source of vm with id should be opened
+    SaveSource(usize),
+    /// Code of vm with id was edited, with code before the edit
+    CodeEdited(usize, String),
+    /// Delay of vm with id was changed on slider
+    SetDelay(usize, u64),
+    /// Breakpoint at line of vm with id was clicked in gutter
+    ToggleBreakpoint(usize, usize),
+    /// Breakpoint at index of vm with id should be removed
+    RemoveBreakpoint(usize, usize),
+    /// Condition of breakpoint at index of vm with id was edited
+    SetBreakpointCondition(usize, usize, String),
+    /// Reloading of linked source of vm with id was turned on or off
+    SetReloadSource(usize, bool),
+    /// Vm with id should forget its source file
+    UnlinkSource(usize),
 }
 
-impl SVAWindow {
-    pub fn new(id: usize, stack_present: bool, max_hight: f32) -> SVAWindow {
-        let mut s = SVAWindow {
-            id,
-            name: format!("vm:{}", id),
-            vm: Arc::new(Mutex::new(VirtualMachine::new())),
-            assembler: Assembler::new(),
-            code: String::new(),
-            program: Vec::new(),
-
-            parsing_error_msg: String::new(),
-            parsing_error: None,
-            language: Language::En,
-            control_button_text: "Start".to_owned(),
-
-            delay_ms: 1000,
-
-            vm_state: (0, 0, Flag::EQUAL, [0; 4], [0; 6], VmStatus::Initial, 0),
-
-            indicators: Default::default(),
-            conn_ids: [None; 4],
-            stack_present,
-            stack_data: Vec::new(),
-            max_hight,
-            active: true,
-            ports_collapsed: false,
-            vm_join_handle: None,
-            vm_status: VmStatus::Initial,
-        };
-        if stack_present {
-            s.assembler = Assembler::new().with_stack();
-            s.vm = Arc::new(Mutex::new(VirtualMachine::new().with_stack(32)))
-        }
-        s.vm.lock().unwrap().set_delay(1000);
-        s
-    }
-
-    pub fn handle_poison_error(&mut self) {
-        ToastsManager::show_info(format!("{} {} ", t!("error.restarting_vm"), self.id), 10);
-        if self.stack_present {
-            self.vm = Arc::new(Mutex::new(VirtualMachine::new()));
-            self.try_assemble_and_load();
-        } else {
-            self.vm = Arc::new(Mutex::new(VirtualMachine::new().with_stack(32)));
-            self.try_assemble_and_load();
-        }
-    }
-
-    pub fn reset_vm(&mut self) {
-        let mut poison_err = false;
-        {
-            let vm_lock = self.vm.lock();
-            match vm_lock {
-                Ok(mut vm) => vm.clear_registers(),
-                Err(_err) => poison_err = true,
-            }
-        }
-        if poison_err {
-            self.handle_poison_error();
-        }
-    }
-
-    pub fn join_vm(&mut self) {
-        if let Some(join_handle) = self.vm_join_handle.take() {
-            join_handle.join().unwrap();
-        }
-    }
-
-    pub fn get_status(&self) -> VmStatus {
-        self.vm_status
-    }
-
-    pub fn halt_vm(&mut self) {
-        VirtualMachine::halt(self.vm.clone());
-    }
+impl VmSettings {
+    pub fn show(&mut self, ui: &mut Ui) {
+        egui::Grid::new("vm_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(t!("vm_settings.name"));
+                ui.text_edit_singleline(&mut self.name);
+                ui.end_row();
+
+                ui.label(t!("vm_settings.stack"));
+                ui.checkbox(&mut self.stack, t!("button.add_vm_stack"));
+                ui.end_row();
+
+                ui.label(t!("vm_settings.stack_size"));
+                ui.add_enabled(
+                    self.stack,
+                    egui::DragValue::new(&mut self.stack_size).clamp_range(1..=MAX_STACK_SIZE),
+                );
+                ui.end_row();
 
-    pub fn stop_vm(&mut self) {
-        VirtualMachine::stop(self.vm.clone());
+                ui.label(t!("sva_shell.indicator.delay"));
+                ui.add(egui::Slider::new(&mut self.delay_ms, 0..=5000).logarithmic(true));
+                ui.end_row();
+            });
     }
+}
 
-    pub fn resume_vm(&mut self) {
-        if self.vm_join_handle.is_some() {
-            VirtualMachine::resume(self.vm.clone());
-        } else {
-            self.vm_join_handle = Some(VirtualMachine::start(self.vm.clone()));
+/// Margin of text inside code editor, same as egui's default for TextEdit
+const TEXT_EDIT_MARGIN: egui::Vec2 = egui::vec2(4.0, 2.0);
+/// Width of clickable area left of code used to toggle breakpoints
+const BREAKPOINT_GUTTER_WIDTH: f32 = 12.0;
+
+/// Rects of source lines in code editor in screen coordinates, wrapped rows are merged
+fn line_rects(output: &TextEditOutput) -> Vec<Rect> {
+    let origin = (output.response.rect.min + TEXT_EDIT_MARGIN).to_vec2();
+    let mut rects: Vec<Rect> = Vec::new();
+    let mut starts_new_line = true;
+    for row in output.galley.rows.iter() {
+        let rect = row.rect.translate(origin);
+        match rects.last_mut() {
+            Some(last) if !starts_new_line => *last = last.union(rect),
+            _ => rects.push(rect),
         }
+        starts_new_line = row.ends_with_newline;
     }
+    rects
+}
 
-    pub fn has_stack(&self) -> bool {
-        self.stack_present
-    }
-
-    pub fn get_id(&self) -> usize {
-        self.id
-    }
+/// Window showing vm, holds only state of window itself
+#[derive(Default)]
+pub struct SVAWindow {
+    indicators: [IndicatorWidget; 13],
 
-    pub fn get_name(&self) -> String {
-        self.name.clone()
-    }
+    ports_collapsed: bool,
 
-    pub fn set_name(&mut self, name: String) {
-        self.name = name;
-    }
+    /// Line of code with currently executed instruction
+    highlighted_line: Option<usize>,
 
-    pub fn set_max_height(&mut self, height: f32) {
-        self.max_hight = height;
-    }
+    /// Rect of highlighted line from previous frame, code editor gets scrolled to it
+    scroll_to_line: Option<Rect>,
 
-    pub fn set_language(&mut self, language: Language) {
-        self.assembler.set_language(language);
-    }
+    /// Delay being dragged on slider, applied when slider is released
+    delay_input: Option<u64>,
+}
 
-    pub fn show_stack(&mut self, _ctx: &Context, ui: &mut Ui) {
-        if !self.stack_present {
-            return;
+impl SVAWindow {
+    /// Returns true if watching stack depth was requested
+    fn show_stack(vm: &mut Vm, ui: &mut Ui, max_height: f32) -> bool {
+        let mut watch = false;
+        if !vm.has_stack() {
+            return watch;
         }
+        let stack_data = vm.stack_values();
         ui.collapsing(t!("sva_shell.collapsing_stack"), |ui| {
+            if ui.button(t!("watch.stack_depth")).clicked() {
+                watch = true;
+            }
             egui::ScrollArea::horizontal()
-                .max_height(self.max_hight * 0.20)
+                .max_height(max_height * 0.20)
                 .enable_scrolling(true)
                 .show(ui, |ui| {
                     ui.separator();
                     ui.horizontal(|ui| {
-                        for item in self.stack_data.iter().rev() {
+                        for item in stack_data.iter().rev() {
                             let _ = ui.button(&item.to_string());
                         }
                     });
                     ui.add_space(10.0);
                 });
         });
+        watch
     }
 
-    fn show_ports(&mut self, ui: &mut Ui) {
+    /// Returns action for clicked port or port which value should be watched
+    fn show_ports(
+        &mut self,
+        vm: &mut Vm,
+        ui: &mut Ui,
+        wiring: WiringMode,
+        names: &HashMap<usize, String>,
+    ) -> VmAction {
+        let mut action = VmAction::DoNothing;
+        let id = vm.get_id();
         self.ports_collapsed = ui
             .collapsing(t!("sva_shell.collapsing_ports"), |ui| {
-                let mut poison_error = false;
                 ui.vertical(|ui| {
-                    let mut ports = [
-                        Port::new(0),
-                        Port::new(0),
-                        Port::new(0),
-                        Port::new(0),
-                        Port::new(0),
-                        Port::new(0),
-                    ];
-
-                    {
-                        match self.vm.lock() {
-                            Ok(vm) => {
-                                ports = vm.get_ports();
-                            }
-                            Err(_err) => {
-                                poison_error = true;
-                            }
-                        }
-                    }
-                    if poison_error {
-                        self.handle_poison_error();
-                    }
-                    let mut index = 0;
-                    for mut p in ports {
-                        let port_is_connected = match p.clone() {
-                            Port::Connected(_, _) => true,
-                            Port::Disconnected(_) => false,
-                        };
-
-                        let mut port_color = Color32::GRAY;
-
-                        if ConnectionManager::get_current_id_index().is_some() && !port_is_connected
-                        {
-                            let in_dark_mode = ui.style().visuals.dark_mode;
-
-                            port_color = if in_dark_mode {
-                                Color32::YELLOW
-                            } else {
-                                Color32::BLUE
-                            }
-                        } else if ConnectionManager::in_disconnect_mode() && port_is_connected {
-                            port_color = Color32::DARK_RED;
-                        }
-
-                        let port_button =
-                            Button::new(format!("{}", p)).stroke(Stroke::new(1.0, port_color));
-
+                    let ports = vm.ports();
+                    for (index, p) in ports.iter().enumerate() {
                         ui.horizontal(|ui| {
                             ui.label(format!("p:{}", index));
-                            if ui.add_enabled(true, port_button).clicked() {
-                                if let Some(conn_index) = ConnectionManager::get_current_id_index()
-                                {
-                                    if let Some(conn) = ConnectionManager::get_connections()
-                                        .lock()
-                                        .unwrap()
-                                        .get_mut(conn_index)
-                                    {
-                                        if !port_is_connected {
-                                            let id = self.id.to_string() + "P" + &index.to_string();
-
-                                            {
-                                                let lock = self.vm.lock();
-                                                match lock {
-                                                    Ok(mut vm) => {
-                                                        vm.connect_with_id(index, conn, id)
-                                                    }
-                                                    Err(_err) => poison_error = true,
-                                                }
-                                            }
-                                            if poison_error {
-                                                self.handle_poison_error();
-                                            }
-                                        } else {
-                                            ToastsManager::show_info(
-                                                t!("toast_info.can_connect_connected_port"),
-                                                10,
-                                            )
-                                        }
-                                    }
-                                } else if ConnectionManager::in_disconnect_mode() {
-                                    let conn_id = p.get_conn_id();
-                                    let conn_index =
-                                        ConnectionManager::get_connection_index_by_id(conn_id);
-                                    if let Some(conn_i) = conn_index {
-                                        let mut conns_lock = CONNECTIONS.lock().unwrap();
-                                        let conn = conns_lock.get_mut(conn_i);
-                                        if let Some(conn_ref) = conn {
-                                            {
-                                                let lock = self.vm.lock();
-                                                match lock {
-                                                    Ok(mut vm) => vm.disconnect(index),
-                                                    Err(_err) => poison_error = true,
-                                                }
-                                                let p_id =
-                                                    self.id.to_string() + "P" + &index.to_string();
-                                                CustomLogger::log(&p_id);
-                                                conn_ref.remove_port_id(p_id);
-                                            }
-                                            if poison_error {
-                                                self.handle_poison_error();
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-
-                            if index < 5 {
-                                index += 1;
-                            }
-
-                            if let Some(id) = p.get_conn_id() {
-                                if let Some(conn_name) = ConnectionManager::get_name(id) {
-                                    ui.label(conn_name);
+                            let response = port_button(ui, p, wiring, names, Color32::GRAY);
+                            response.context_menu(|ui| {
+                                if ui.button(t!("watch.add")).clicked() {
+                                    action = VmAction::Watch(WatchTarget::VmRegister {
+                                        vm_id: id,
+                                        name: format!("p{}", index),
+                                    });
+                                    ui.close_menu();
                                 }
+                            });
+                            if response.clicked() {
+                                action = VmAction::PortClicked(PortRef::Vm {
+                                    vm: id,
+                                    port: index,
+                                });
                             }
                         });
                     }
                 });
             })
             .fully_open();
+        action
     }
 
     fn show_registers(
@@ -405,27 +198,43 @@ impl SVAWindow {
         pc: usize,
         flag: Flag,
         r: [i32; 4],
-    ) {
+    ) -> Option<&'static str> {
         let _labels = ["acc", "pc", "flag", "r:0-3", "p:0-3", "status", "delay"];
+        let mut watch = None;
 
         ui.horizontal(|ui| {
-            self.indicators[0].set(acc, "acc").show(ctx, ui);
-            self.indicators[1]
+            if self.indicators[0].set(acc, "acc").show(ctx, ui) {
+                watch = Some("acc");
+            }
+            if self.indicators[1]
                 .set(pc.try_into().unwrap_or(0), "pc")
-                .show(ctx, ui);
+                .show(ctx, ui)
+            {
+                watch = Some("pc");
+            }
 
             // flag
             ui.label("flag");
             let _ = ui.button(flag.to_string());
 
-            self.indicators[2].set(r[0], "r0").show(ctx, ui);
-            self.indicators[2].set(r[1], "r1").show(ctx, ui);
-            self.indicators[2].set(r[2], "r2").show(ctx, ui);
-            self.indicators[2].set(r[3], "r3").show(ctx, ui);
+            for (name, value) in ["r0", "r1", "r2", "r3"].into_iter().zip(r) {
+                if self.indicators[2].set(value, name).show(ctx, ui) {
+                    watch = Some(name);
+                }
+            }
         });
+        watch
     }
 
-    fn show_code_editor(&mut self, ui: &mut Ui) {
+    /// Shows code editor, returns code from before the edit when code was edited
+    fn show_code_editor(
+        &mut self,
+        vm: &mut Vm,
+        ui: &mut Ui,
+        current_line: Option<usize>,
+        max_height: f32,
+    ) -> VmAction {
+        let mut action = VmAction::DoNothing;
         let in_dark_mode = ui.style().visuals.dark_mode;
         let editor_them = if in_dark_mode {
             ColorTheme::GITHUB_DARK
@@ -438,14 +247,20 @@ impl SVAWindow {
                 ui.label(t!("sva_shell.code_block"));
             })
             .body(|ui| {
-                let max_height = if self.stack_present {
-                    self.max_hight * 0.30 * (1.0 + !self.ports_collapsed as i32 as f32)
+                let max_height = if vm.has_stack() {
+                    max_height * 0.30 * (1.0 + !self.ports_collapsed as i32 as f32)
                 } else {
-                    self.max_hight * 0.4 * (1.0 + !self.ports_collapsed as i32 as f32)
+                    max_height * 0.4 * (1.0 + !self.ports_collapsed as i32 as f32)
                 };
                 egui::ScrollArea::neither()
                     .max_height(max_height)
                     .show(ui, |ui| {
+                        // scrolling to line highlighted in previous frame
+                        if let Some(rect) = self.scroll_to_line.take() {
+                            ui.scroll_to_rect(rect, None);
+                        }
+                        let code_before_edit = vm.get_code();
+                        let mut code = code_before_edit.clone();
                         let code_editor = CodeEditor::default()
                             .id_source("code editor")
                             .with_rows(12)
@@ -453,217 +268,317 @@ impl SVAWindow {
                             .with_theme(editor_them)
                             .with_syntax(sva_syntax())
                             .with_numlines(true)
-                            .show(ui, &mut self.code);
+                            .show(ui, &mut code);
                         if code_editor.response.changed() {
-                            self.try_assemble_and_load();
+                            action = VmAction::CodeEdited(vm.get_id(), code_before_edit);
+                            vm.set_code(code);
+                        }
+                        self.show_current_line(ui, &code_editor, current_line);
+                        if let Some(gutter_action) =
+                            Self::show_breakpoint_gutter(vm, ui, &code_editor)
+                        {
+                            action = gutter_action;
                         }
                     });
+                if let Some(list_action) = Self::show_breakpoints_list(vm, ui) {
+                    action = list_action;
+                }
             });
+        action
     }
 
-    fn show_vm_control_buttons(&mut self, ui: &mut Ui, vm_status: VmStatus) {
-        let next_instruction = self.vm.lock().unwrap().get_next_instruction();
+    /// Highlights line with next instruction, when it changes editor will follow it
+    fn show_current_line(&mut self, ui: &mut Ui, output: &TextEditOutput, line: Option<usize>) {
+        if let Some(line) = line {
+            if let Some(line_rect) = line_rects(output).get(line) {
+                let rect =
+                    Rect::from_x_y_ranges(output.response.rect.x_range(), line_rect.y_range());
+                ui.painter_at(output.text_clip_rect).rect_filled(
+                    rect,
+                    0.0,
+                    Color32::from_rgba_unmultiplied(255, 100, 0, 40),
+                );
+                if self.highlighted_line != Some(line) {
+                    self.scroll_to_line = Some(rect);
+                }
+            }
+        }
+        self.highlighted_line = line;
+    }
 
-        if let Some(instruction) = next_instruction {
+    /// Draws breakpoints left of code, clicked line toggles its breakpoint
+    fn show_breakpoint_gutter(vm: &Vm, ui: &mut Ui, output: &TextEditOutput) -> Option<VmAction> {
+        let mut action = None;
+        let clip_rect = output.text_clip_rect;
+        let left = output.response.rect.left();
+        let painter = ui.painter_at(Rect::from_x_y_ranges(
+            (left - BREAKPOINT_GUTTER_WIDTH)..=clip_rect.right(),
+            clip_rect.y_range(),
+        ));
+        for (line, line_rect) in line_rects(output).into_iter().enumerate() {
+            if !clip_rect.intersects(line_rect) {
+                continue;
+            }
+            let gutter_rect =
+                Rect::from_x_y_ranges((left - BREAKPOINT_GUTTER_WIDTH)..=left, line_rect.y_range());
+            let response = ui
+                .interact(
+                    gutter_rect,
+                    ui.id().with(("breakpoint", vm.get_id(), line)),
+                    Sense::click(),
+                )
+                .on_hover_text(t!("sva_shell.breakpoint.on_hover_text"));
+            if response.clicked() {
+                action = Some(VmAction::ToggleBreakpoint(vm.get_id(), line));
+            }
+            let radius = gutter_rect.height().min(BREAKPOINT_GUTTER_WIDTH) * 0.3;
+            if vm.has_breakpoint(line) {
+                painter.circle_filled(gutter_rect.center(), radius, Color32::RED);
+            } else if response.hovered() {
+                painter.circle_stroke(gutter_rect.center(), radius, Stroke::new(1.0, Color32::RED));
+            }
+        }
+        action
+    }
+
+    /// List of breakpoints with their conditions
+    fn show_breakpoints_list(vm: &mut Vm, ui: &mut Ui) -> Option<VmAction> {
+        if !vm.has_breakpoints() {
+            return None;
+        }
+        let id = vm.get_id();
+        let mut action = None;
+        ui.collapsing(t!("sva_shell.collapsing_breakpoints"), |ui| {
+            for (index, breakpoint) in vm.breakpoints_mut().iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}: {}", t!("sva_shell.line"), breakpoint.line + 1));
+                    let mut condition = breakpoint.condition().to_owned();
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut condition)
+                                .hint_text(t!("sva_shell.breakpoint.condition_hint"))
+                                .desired_width(120.0),
+                        )
+                        .changed()
+                    {
+                        action = Some(VmAction::SetBreakpointCondition(id, index, condition));
+                    }
+                    if ui.button(t!("button.remove")).clicked() {
+                        action = Some(VmAction::RemoveBreakpoint(id, index));
+                    }
+                });
+                if let Some(err) = breakpoint.condition_error() {
+                    ui.label(egui::RichText::new(err).color(egui::Color32::from_rgb(255, 0, 0)));
+                }
+            }
+        });
+        action
+    }
+
+    /// Buttons for opening and saving source file and linked file info
+    fn show_source_file(vm: &Vm, ui: &mut Ui) -> Option<VmAction> {
+        let mut action = None;
+        ui.horizontal_wrapped(|ui| {
+            if ui.button(t!("sva_shell.button.open_source")).clicked() {
+                action = Some(VmAction::OpenSource(vm.get_id()));
+            }
+            if ui.button(t!("sva_shell.button.save_source")).clicked() {
+                action = Some(VmAction::SaveSource(vm.get_id()));
+            }
+            let Some(path) = vm.source_path() else {
+                return;
+            };
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            ui.label(file_name)
+                .on_hover_text(path.display().to_string());
+            let mut reload_source = vm.reloads_source();
+            if ui
+                .checkbox(&mut reload_source, t!("sva_shell.reload_source"))
+                .on_hover_text(t!("sva_shell.reload_source.on_hover_text"))
+                .changed()
+            {
+                action = Some(VmAction::SetReloadSource(vm.get_id(), reload_source));
+            }
+            if ui
+                .button(t!("sva_shell.button.unlink_source"))
+                .on_hover_text(t!("sva_shell.button.unlink_source.on_hover_text"))
+                .clicked()
+            {
+                action = Some(VmAction::UnlinkSource(vm.get_id()));
+            }
+        });
+        action
+    }
+
+    fn show_vm_control_buttons(
+        &mut self,
+        vm: &mut Vm,
+        ui: &mut Ui,
+        vm_status: VmStatus,
+    ) -> VmAction {
+        let mut action = VmAction::DoNothing;
+        let id = vm.get_id();
+
+        if let Some(instruction) = vm.next_instruction() {
             ui.separator();
             ui.horizontal(|ui| {
                 ui.label(t!("sva_shell.next_instruction") + ":");
                 ui.label(
-                    egui::RichText::new(instruction.to_string())
-                        .color(egui::Color32::from_rgb(255, 100, 000)),
+                    egui::RichText::new(instruction).color(egui::Color32::from_rgb(255, 100, 000)),
                 );
             });
         }
 
-        let mut poison_err = false;
         ui.separator();
 
-        if let Some(parsing_error) = &self.parsing_error {
+        if let Some(parsing_error) = vm.parsing_error() {
             ui.label(
                 egui::RichText::new(parsing_error.to_string())
                     .color(egui::Color32::from_rgb(255, 0, 0)),
             );
         } else {
             ui.horizontal(|ui| {
-                if vm_status == VmStatus::Running {
-                    self.control_button_text = t!("sva_shell.button.stop").to_owned();
-                }
-
-                match vm_status {
-                    VmStatus::Initial => {
-                        self.control_button_text = t!("sva_shell.button.start").to_owned()
-                    }
-                    VmStatus::Running => {
-                        self.control_button_text = t!("sva_shell.button.stop").to_owned()
-                    }
-                    VmStatus::Stopped => {
-                        self.control_button_text = t!("sva_shell.button.resume").to_owned()
-                    }
-                    VmStatus::Finished => {
-                        self.control_button_text = t!("sva_shell.button.start").to_owned()
-                    }
-                }
+                let control_button_text = match vm_status {
+                    VmStatus::Initial => t!("sva_shell.button.start"),
+                    VmStatus::Running => t!("sva_shell.button.stop"),
+                    VmStatus::Stopped => t!("sva_shell.button.resume"),
+                    VmStatus::Finished => t!("sva_shell.button.start"),
+                };
 
                 if (vm_status == VmStatus::Running || vm_status == VmStatus::Stopped)
                     && ui.button(t!("sva_shell.button.halt")).clicked()
                 {
-                    VirtualMachine::halt(self.vm.clone());
+                    vm.halt_vm();
                 }
 
-                if ui.button(&self.control_button_text).clicked() {
-                    {
-                        {
-                            let vm_lock = self.vm.lock();
-                            match vm_lock {
-                                Ok(mut vm) => {
-                                    vm.set_delay(self.delay_ms.try_into().unwrap());
-                                }
-                                Err(_) => poison_err = true,
-                            }
-                        }
-                        if poison_err {
-                            self.handle_poison_error();
-                        }
-                    }
-                    match vm_status {
-                        VmStatus::Initial => {
-                            self.vm_join_handle = Some(VirtualMachine::start(self.vm.clone()));
-                        }
-                        VmStatus::Running => {
-                            VirtualMachine::stop(self.vm.clone());
-                        }
-                        VmStatus::Stopped => {
-                            if self.vm_join_handle.is_some() {
-                                VirtualMachine::resume(self.vm.clone());
-                            } else {
-                                self.vm_join_handle = Some(VirtualMachine::start(self.vm.clone()));
-                            }
-                        }
-                        VmStatus::Finished => {
-                            VirtualMachine::start(self.vm.clone());
-                            //self.reset_vm();
-                            //VirtualMachine::resume(self.vm.clone());
-                        }
+                if vm.is_lockstep() {
+                    ui.label(t!("sva_shell.controlled_by_scheduler"));
+                } else if ui.button(control_button_text).clicked() {
+                    if vm_status == VmStatus::Running {
+                        vm.stop_vm();
+                    } else {
+                        vm.start_vm();
                     }
                 }
 
-                if ui.button(t!("sva_shell.button.step")).clicked() {
-                    self.step();
+                if ui
+                    .add_enabled(
+                        vm.history_len() > 0 && vm_status != VmStatus::Running,
+                        Button::new(t!("sva_shell.button.step_back")),
+                    )
+                    .on_hover_text(format!(
+                        "{}: {}",
+                        t!("sva_shell.button.step_back.on_hover_text"),
+                        vm.history_len()
+                    ))
+                    .clicked()
+                {
+                    action = VmAction::StepBack(id);
+                }
+                // in lockstep mode vms are stepped together by scheduler
+                if ui
+                    .add_enabled(!vm.is_lockstep(), Button::new(t!("sva_shell.button.step")))
+                    .on_disabled_hover_text(t!("sva_shell.controlled_by_scheduler"))
+                    .clicked()
+                {
+                    action = VmAction::Step(id);
                 }
                 if ui.button(t!("sva_shell.button.reset")).clicked() {
-                    self.reset_vm();
+                    vm.reset_vm();
+                }
+                if ui.button(t!("sva_shell.button.settings")).clicked() {
+                    action = VmAction::OpenSettings(id);
                 }
                 ui.separator();
-                if ui
-                    .add(
-                        egui::Slider::new(&mut self.delay_ms, 0..=5000)
-                            .logarithmic(true)
-                            .text(t!("sva_shell.indicator.delay")),
-                    )
-                    .changed()
-                {
-                    self.vm
-                        .lock()
-                        .unwrap()
-                        .set_delay(self.delay_ms.try_into().unwrap());
+                let delay_ms = self.delay_input.get_or_insert(vm.get_delay_ms());
+                let response = ui.add(
+                    egui::Slider::new(delay_ms, 0..=5000)
+                        .logarithmic(true)
+                        .text(t!("sva_shell.indicator.delay")),
+                );
+                if response.drag_released() || (response.changed() && !response.dragged()) {
+                    action = VmAction::SetDelay(id, *delay_ms);
+                }
+                if !response.dragged() {
+                    self.delay_input = None;
                 }
             });
         }
         ui.separator();
+        action
     }
 
-    /// Tries Assembles code to instructions and loads them to vm
-    pub fn try_assemble_and_load(&mut self) {
-        let res = self.assembler.parse(&self.code);
-
-        match res {
-            Ok(program) => {
-                {
-                    match self.vm.lock() {
-                        Ok(mut vm) => {
-                            //CustomLogger::log("Loading program");
-                            vm.load_program(program);
-                        }
-                        Err(err) => ToastsManager::show_err(format!("{:?}", err), 10),
-                    }
-                }
-                self.parsing_error = None
-            }
-            Err(err) => self.parsing_error = Some(err),
-        }
-    }
-    /// Execute one instruction FIXME:
-    fn step(&mut self) {
-        let mut poison_err = false;
-        {
-            let vm_lock = self.vm.lock();
-
-            match vm_lock {
-                Ok(mut vm) => {
-                    if vm.get_pc() >= vm.get_program().len() {
-                        vm.clear_registers();
-                    }
+    pub fn show(
+        &mut self,
+        vm: &mut Vm,
+        ctx: &Context,
+        _ui: &mut Ui,
+        wiring: WiringMode,
+        names: &HashMap<usize, String>,
+        max_height: f32,
+    ) -> VmAction {
+        let mut action = VmAction::DoNothing;
+        let id = vm.get_id();
+        let vm_state = vm.state();
+        let (acc, pc, flag, r, _p, vm_status, _delay) = vm_state;
+        let vm_status = if vm.is_checked_run() {
+            VmStatus::Running
+        } else {
+            vm_status
+        };
+        let current_line = vm.current_line(&vm_state);
+        // window
+        egui::Window::new(vm.get_name())
+            .id(egui::Id::new(format!("vm:{}", id)))
+            .max_height(max_height)
+            .max_width(500.0)
+            .show(ctx, |ui| {
+                let code_action = self.show_code_editor(vm, ui, current_line, max_height);
+                if code_action != VmAction::DoNothing {
+                    action = code_action;
                 }
-                Err(_err) => poison_err = true,
-            }
-        }
-        if poison_err {
-            self.handle_poison_error();
-        }
-        panic::catch_unwind(|| {
-            self.vm.lock().unwrap().execute();
-        })
-        .unwrap_or_else(|_err| {
-            self.handle_poison_error();
-        });
-    }
 
-    pub fn show(&mut self, ctx: &Context, _ui: &mut Ui) {
-        let mut poison_error = false;
-        {
-            match self.vm.lock() {
-                Ok(vm) => {
-                    self.vm_state = vm.get_state_for_display();
-                    if self.stack_present {
-                        self.stack_data = vm.get_stack();
-                    }
+                if let Some(source_action) = Self::show_source_file(vm, ui) {
+                    action = source_action;
                 }
-                Err(err) => {
-                    poison_error = true;
 
-                    ToastsManager::show_err(format!("{}", err), 10);
+                let control_action = self.show_vm_control_buttons(vm, ui, vm_status);
+                if control_action != VmAction::DoNothing {
+                    action = control_action;
                 }
-            }
-        }
-        if poison_error {
-            self.handle_poison_error();
-        }
-        let (acc, pc, flag, r, _p, vm_status, _delay) = self.vm_state;
-        self.vm_status = vm_status;
-        // window
-        egui::Window::new(&self.name)
-            .id(egui::Id::new(format!("vm:{}", self.id)))
-            .max_height(self.max_hight)
-            .max_width(500.0)
-            .show(ctx, |ui| {
-                self.show_code_editor(ui);
 
-                self.show_vm_control_buttons(ui, vm_status);
-
-                self.show_registers(ui, acc, ctx, pc, flag, r);
+                if let Some(name) = self.show_registers(ui, acc, ctx, pc, flag, r) {
+                    action = VmAction::Watch(WatchTarget::VmRegister {
+                        vm_id: id,
+                        name: name.to_string(),
+                    });
+                }
 
-                self.show_ports(ui);
+                let port_action = self.show_ports(vm, ui, wiring, names);
+                if port_action != VmAction::DoNothing {
+                    action = port_action;
+                }
 
-                self.show_stack(ctx, ui);
+                if Self::show_stack(vm, ui, max_height) {
+                    action = VmAction::Watch(WatchTarget::VmStackDepth { vm_id: id });
+                }
 
                 // uncomment for debugging
-                //ui.label(self.vm.lock().unwrap().to_string());
-
-                if self.delay_ms > 10 {
-                    ctx.request_repaint_after(Duration::from_millis(self.delay_ms));
-                } else {
-                    ctx.request_repaint_after(Duration::from_millis(10));
+                //ui.label(vm.vm.lock().unwrap().to_string());
+
+                // in lockstep mode repainting is requested by scheduler
+                if !vm.is_lockstep() {
+                    let delay_ms = vm.get_delay_ms();
+                    if delay_ms > 10 {
+                        ctx.request_repaint_after(Duration::from_millis(delay_ms));
+                    } else {
+                        ctx.request_repaint_after(Duration::from_millis(10));
+                    }
                 }
             });
+        action
     }
 }
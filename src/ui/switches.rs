@@ -0,0 +1,28 @@
+use egui::Ui;
+
+use crate::model::switches::Switches;
+
+impl Switches {
+    pub fn show(&mut self, ui: &mut Ui, _id: usize) {
+        ui.horizontal(|ui| {
+            ui.label(t!("switches.count"));
+            let mut count = self.count();
+            if ui
+                .add(egui::DragValue::new(&mut count).clamp_range(1..=32))
+                .changed()
+            {
+                self.set_count(count);
+            }
+            ui.label(format!("= {}", self.state() as i32));
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            for switch in (0..self.count()).rev() {
+                let mut on = self.is_on(switch);
+                if ui.toggle_value(&mut on, switch.to_string()).changed() {
+                    self.toggle(switch);
+                }
+            }
+        });
+    }
+}
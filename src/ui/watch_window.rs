@@ -0,0 +1,73 @@
+use egui::{Context, RichText, ScrollArea};
+use serde::{Deserialize, Serialize};
+
+use crate::model::watchpoint::WatchList;
+
+/// Window listing watchpoints of all vms and rams
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WatchWindow {
+    /// Is widow open
+    pub is_open: bool,
+}
+
+impl WatchWindow {
+    pub fn toggle_open_close(&mut self) {
+        self.is_open = !self.is_open;
+    }
+
+    /// `labels` contains name and current value of each target, in order of `targets`
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        watchpoints: &mut WatchList,
+        labels: &[(String, Option<i32>)],
+    ) {
+        let mut to_remove = None;
+        egui::Window::new(t!("window.watch"))
+            .open(&mut self.is_open)
+            .show(ctx, |ui| {
+                if watchpoints.is_empty() {
+                    ui.label(t!("watch_window.empty"));
+                    return;
+                }
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (index, (wp, (name, value))) in
+                        watchpoints.iter_mut().zip(labels.iter()).enumerate()
+                    {
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut wp.enabled, "");
+                            ui.label(RichText::new(name).strong());
+                            match value {
+                                Some(value) => ui.label(format!("= {}", value)),
+                                None => ui.label(t!("common.none")),
+                            };
+                        });
+                        ui.horizontal(|ui| {
+                            let mut condition = wp.condition().to_owned();
+                            if ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut condition)
+                                        .hint_text(t!("watch_window.condition_hint"))
+                                        .desired_width(160.0),
+                                )
+                                .changed()
+                            {
+                                wp.set_condition(condition);
+                            }
+                            if ui.button(t!("button.remove")).clicked() {
+                                to_remove = Some(index);
+                            }
+                        });
+                        if let Some(err) = wp.condition_error() {
+                            ui.label(RichText::new(err).color(egui::Color32::from_rgb(255, 0, 0)));
+                        }
+                    }
+                });
+            });
+        if let Some(index) = to_remove {
+            watchpoints.remove(index);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use egui::{Button, Color32, Response, Stroke, Ui};
+use simple_virtual_assembler::components::port::Port;
+
+use crate::project::document::WiringMode;
+
+/// Outline of port, highlights ports that can be clicked in current mode
+pub fn port_color(ui: &Ui, mode: WiringMode, port_is_connected: bool, default: Color32) -> Color32 {
+    if mode.is_connecting() && !port_is_connected {
+        if ui.style().visuals.dark_mode {
+            Color32::YELLOW
+        } else {
+            Color32::BLUE
+        }
+    } else if mode.is_disconnecting() && port_is_connected {
+        Color32::DARK_RED
+    } else {
+        default
+    }
+}
+
+/// Shows port as button followed by name of its connection
+pub fn port_button(
+    ui: &mut Ui,
+    port: &Port,
+    mode: WiringMode,
+    names: &HashMap<usize, String>,
+    default_color: Color32,
+) -> Response {
+    let port_is_connected = matches!(port, Port::Connected(_, _));
+    let color = port_color(ui, mode, port_is_connected, default_color);
+    let response = ui.add(Button::new(format!("{}", port)).stroke(Stroke::new(1.0, color)));
+    if let Some(name) = port.get_conn_id().and_then(|id| names.get(&id)) {
+        ui.label(name);
+    }
+    response
+}
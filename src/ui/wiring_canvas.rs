@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+
+use egui::{Align2, Color32, Context, FontId, Painter, Pos2, Rect, Rounding, Sense, Stroke, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::project::circuit::Circuit;
+use crate::project::schema::{PortRef, RamPort, VM_PORTS};
+
+const NODE_WIDTH: f32 = 120.0;
+const HEADER_HEIGHT: f32 = 22.0;
+const PORT_HEIGHT: f32 = 18.0;
+const PORT_RADIUS: f32 = 5.0;
+/// How close to wire right click has to be to hit it
+const WIRE_HIT_DISTANCE: f32 = 6.0;
+
+/// Actions that change connections, handled by document
+#[derive(Debug, PartialEq, Clone)]
+pub enum CanvasAction {
+    DoNothing,
+    /// Wire was dragged between ports
+    Wire(PortRef, PortRef),
+    /// Wire of port was right clicked
+    Disconnect(PortRef),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NodeId {
+    Vm(usize),
+    Ram(usize),
+    Device(usize),
+}
+
+/// What is being dragged on canvas
+#[derive(Debug, Clone)]
+enum Drag {
+    Canvas,
+    Node(NodeId),
+    Wire(PortRef),
+}
+
+/// Vm, ram or device drawn as node
+struct Node {
+    id: NodeId,
+    /// Index of node in its row, used for default position
+    slot: usize,
+    name: String,
+    /// Port, its label and connection it is connected to
+    ports: Vec<(PortRef, String, Option<usize>)>,
+}
+
+/// View of circuit where vms, rams and devices are nodes and connections are wires between their ports
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WiringCanvas {
+    /// Is widow open
+    pub is_open: bool,
+    /// Positions of nodes relative to canvas origin
+    vm_positions: HashMap<usize, Pos2>,
+    ram_positions: HashMap<usize, Pos2>,
+    device_positions: HashMap<usize, Pos2>,
+    pan: Vec2,
+    #[serde(skip)]
+    drag: Option<Drag>,
+}
+
+impl WiringCanvas {
+    pub fn toggle_open_close(&mut self) {
+        self.is_open = !self.is_open;
+    }
+
+    /// Forgets positions of removed vms, rams and devices
+    pub fn retain(&mut self, vm_ids: &[usize], ram_ids: &[usize], device_ids: &[usize]) {
+        self.vm_positions.retain(|id, _| vm_ids.contains(id));
+        self.ram_positions.retain(|id, _| ram_ids.contains(id));
+        self.device_positions
+            .retain(|id, _| device_ids.contains(id));
+    }
+
+    pub fn clear(&mut self) {
+        self.vm_positions.clear();
+        self.ram_positions.clear();
+        self.device_positions.clear();
+        self.pan = Vec2::ZERO;
+    }
+
+    pub fn show(&mut self, ctx: &Context, circuit: &Circuit) -> CanvasAction {
+        let mut action = CanvasAction::DoNothing;
+        let mut is_open = self.is_open;
+        egui::Window::new(t!("window.wiring_canvas"))
+            .open(&mut is_open)
+            .default_size([640.0, 420.0])
+            .show(ctx, |ui| {
+                ui.label(t!("wiring_canvas.hint"));
+                ui.separator();
+                action = self.show_canvas(ui, circuit);
+            });
+        self.is_open = is_open;
+        action
+    }
+
+    fn show_canvas(&mut self, ui: &mut egui::Ui, circuit: &Circuit) -> CanvasAction {
+        let (response, painter) = ui.allocate_painter(
+            ui.available_size().max(Vec2::splat(100.0)),
+            Sense::click_and_drag(),
+        );
+        let origin = response.rect.min + self.pan;
+        let nodes = nodes_of(circuit);
+
+        // node rects and port positions in screen coordinates
+        let mut rects = Vec::new();
+        let mut port_positions: Vec<(PortRef, Pos2, Option<usize>)> = Vec::new();
+        for node in nodes.iter() {
+            let position = origin + self.position(node.id, node.slot).to_vec2();
+            let height = HEADER_HEIGHT + PORT_HEIGHT * node.ports.len() as f32;
+            let rect = Rect::from_min_size(position, Vec2::new(NODE_WIDTH, height));
+            for (port_index, (port, _, conn)) in node.ports.iter().enumerate() {
+                let y = rect.min.y + HEADER_HEIGHT + PORT_HEIGHT * (port_index as f32 + 0.5);
+                port_positions.push((port.clone(), Pos2::new(rect.max.x, y), *conn));
+            }
+            rects.push(rect);
+        }
+        let wires = wires_of(circuit, &port_positions);
+
+        let pointer = response.interact_pointer_pos().or(response.hover_pos());
+        let port_at = |pos: Pos2| {
+            port_positions
+                .iter()
+                .find(|(_, center, _)| center.distance(pos) <= PORT_RADIUS * 2.0)
+                .map(|(port, _, _)| port.clone())
+        };
+
+        let mut action = CanvasAction::DoNothing;
+
+        // dragging
+        if response.drag_started() {
+            // drag starts only after pointer moved a bit, so it is found where it was pressed
+            let press_origin = ui.input(|i| i.pointer.press_origin()).or(pointer);
+            self.drag = match press_origin {
+                Some(pos) => match port_at(pos) {
+                    Some(port) => Some(Drag::Wire(port)),
+                    None => nodes
+                        .iter()
+                        .zip(rects.iter())
+                        .rev()
+                        .find(|(_, rect)| rect.contains(pos))
+                        .map(|(node, _)| Drag::Node(node.id))
+                        .or(Some(Drag::Canvas)),
+                },
+                None => None,
+            };
+        }
+        if response.dragged() {
+            let delta = response.drag_delta();
+            match self.drag.clone() {
+                Some(Drag::Canvas) => self.pan += delta,
+                Some(Drag::Node(id)) => {
+                    let slot = nodes
+                        .iter()
+                        .find(|node| node.id == id)
+                        .map_or(0, |n| n.slot);
+                    let position = self.position(id, slot) + delta;
+                    self.set_position(id, position);
+                }
+                _ => {}
+            }
+        }
+        if response.drag_released() {
+            if let (Some(Drag::Wire(from)), Some(pos)) = (self.drag.take(), pointer) {
+                if let Some(to) = port_at(pos) {
+                    if from != to {
+                        action = CanvasAction::Wire(from, to);
+                    }
+                }
+            }
+            self.drag = None;
+        }
+
+        // disconnecting
+        if response.secondary_clicked() {
+            if let Some(pos) = pointer {
+                let hit = wires.iter().find(|(_, from, to, _)| {
+                    distance_to_segment(pos, *from, *to) <= WIRE_HIT_DISTANCE
+                });
+                if let Some((port, _, _, _)) = hit {
+                    action = CanvasAction::Disconnect(port.clone());
+                }
+            }
+        }
+
+        // drawing
+        let visuals = ui.style().visuals.clone();
+        let painter = painter.with_clip_rect(response.rect);
+        for (node, rect) in nodes.iter().zip(rects.iter()) {
+            draw_node(&painter, node, *rect, &visuals);
+        }
+        let wire_color = if visuals.dark_mode {
+            Color32::YELLOW
+        } else {
+            Color32::BLUE
+        };
+        for (_, from, hub, _) in wires.iter() {
+            painter.line_segment([*from, *hub], Stroke::new(2.0, wire_color));
+        }
+        let mut hubs: Vec<(Pos2, usize)> = Vec::new();
+        for (_, _, hub, conn) in wires.iter() {
+            if !hubs.iter().any(|(_, id)| id == conn) {
+                hubs.push((*hub, *conn));
+            }
+        }
+        for (hub, conn) in hubs {
+            painter.circle_filled(hub, PORT_RADIUS, wire_color);
+            painter.text(
+                hub + Vec2::new(0.0, -PORT_RADIUS - 2.0),
+                Align2::CENTER_BOTTOM,
+                circuit.connection_name(conn).unwrap_or_default(),
+                FontId::proportional(12.0),
+                visuals.text_color(),
+            );
+        }
+        if let (Some(Drag::Wire(from)), Some(pos)) = (&self.drag, pointer) {
+            if let Some((_, start, _)) = port_positions.iter().find(|(port, _, _)| port == from) {
+                painter.line_segment([*start, pos], Stroke::new(2.0, Color32::GRAY));
+            }
+        }
+
+        action
+    }
+
+    /// Position of node, nodes that weren't moved yet are placed in rows, vms above rams above devices
+    fn position(&self, id: NodeId, slot: usize) -> Pos2 {
+        let stored = match id {
+            NodeId::Vm(id) => self.vm_positions.get(&id),
+            NodeId::Ram(id) => self.ram_positions.get(&id),
+            NodeId::Device(id) => self.device_positions.get(&id),
+        };
+        match stored {
+            Some(position) => *position,
+            None => {
+                let row = match id {
+                    NodeId::Vm(_) => 0.0,
+                    NodeId::Ram(_) => 1.0,
+                    NodeId::Device(_) => 2.0,
+                };
+                Pos2::new(
+                    20.0 + slot as f32 * (NODE_WIDTH + 60.0),
+                    20.0 + row * (HEADER_HEIGHT + PORT_HEIGHT * VM_PORTS as f32 + 60.0),
+                )
+            }
+        }
+    }
+
+    fn set_position(&mut self, id: NodeId, position: Pos2) {
+        match id {
+            NodeId::Vm(id) => self.vm_positions.insert(id, position),
+            NodeId::Ram(id) => self.ram_positions.insert(id, position),
+            NodeId::Device(id) => self.device_positions.insert(id, position),
+        };
+    }
+}
+
+/// Vms followed by rams and devices
+fn nodes_of(circuit: &Circuit) -> Vec<Node> {
+    let vms = circuit.vms().iter().enumerate().map(|(slot, vm)| {
+        let ports = (0..VM_PORTS)
+            .map(|port| {
+                let port_ref = PortRef::Vm {
+                    vm: vm.get_id(),
+                    port,
+                };
+                let conn = circuit.port_connection(&port_ref);
+                (port_ref, format!("p{}", port), conn)
+            })
+            .collect();
+        Node {
+            id: NodeId::Vm(vm.get_id()),
+            slot,
+            name: vm.get_name(),
+            ports,
+        }
+    });
+    let rams = circuit.rams().iter().enumerate().map(|(slot, ram)| {
+        let ports = [RamPort::Index, RamPort::Data, RamPort::Mode]
+            .into_iter()
+            .map(|port| {
+                let port_ref = PortRef::Ram {
+                    ram: ram.get_id(),
+                    port,
+                };
+                let conn = circuit.port_connection(&port_ref);
+                (port_ref, port.name().to_string(), conn)
+            })
+            .collect();
+        Node {
+            id: NodeId::Ram(ram.get_id()),
+            slot,
+            name: ram.get_name(),
+            ports,
+        }
+    });
+    let devices = circuit.devices().iter().enumerate().map(|(slot, device)| {
+        let ports = device
+            .kind
+            .port_names()
+            .iter()
+            .enumerate()
+            .map(|(port, name)| {
+                let port_ref = PortRef::Device {
+                    device: device.get_id(),
+                    port,
+                };
+                let conn = circuit.port_connection(&port_ref);
+                (port_ref, name.to_string(), conn)
+            })
+            .collect();
+        Node {
+            id: NodeId::Device(device.get_id()),
+            slot,
+            name: device.get_name(),
+            ports,
+        }
+    });
+    vms.chain(rams).chain(devices).collect()
+}
+
+/// Wire from every connected port to hub of its connection placed in middle of its ports
+fn wires_of(
+    circuit: &Circuit,
+    port_positions: &[(PortRef, Pos2, Option<usize>)],
+) -> Vec<(PortRef, Pos2, Pos2, usize)> {
+    let mut wires = Vec::new();
+    for conn in circuit.connections() {
+        let Some(conn_id) = conn.get_id() else {
+            continue;
+        };
+        let ports: Vec<&(PortRef, Pos2, Option<usize>)> = port_positions
+            .iter()
+            .filter(|(_, _, conn)| *conn == Some(conn_id))
+            .collect();
+        if ports.is_empty() {
+            continue;
+        }
+        let sum = ports
+            .iter()
+            .fold(Vec2::ZERO, |sum, (_, pos, _)| sum + pos.to_vec2());
+        let hub = (sum / ports.len() as f32).to_pos2() + Vec2::new(30.0, 0.0);
+        for (port, pos, _) in ports {
+            wires.push((port.clone(), *pos, hub, conn_id));
+        }
+    }
+    wires
+}
+
+fn draw_node(painter: &Painter, node: &Node, rect: Rect, visuals: &egui::Visuals) {
+    let rounding = Rounding::same(4.0);
+    painter.rect_filled(rect, rounding, visuals.extreme_bg_color);
+    painter.rect_stroke(rect, rounding, visuals.widgets.noninteractive.bg_stroke);
+    painter.text(
+        rect.min + Vec2::new(6.0, HEADER_HEIGHT / 2.0),
+        Align2::LEFT_CENTER,
+        &node.name,
+        FontId::proportional(14.0),
+        visuals.strong_text_color(),
+    );
+    for (index, (_, label, conn)) in node.ports.iter().enumerate() {
+        let y = rect.min.y + HEADER_HEIGHT + PORT_HEIGHT * (index as f32 + 0.5);
+        painter.text(
+            Pos2::new(rect.max.x - PORT_RADIUS * 2.5, y),
+            Align2::RIGHT_CENTER,
+            label,
+            FontId::proportional(12.0),
+            visuals.text_color(),
+        );
+        let center = Pos2::new(rect.max.x, y);
+        if conn.is_some() {
+            painter.circle_filled(center, PORT_RADIUS, visuals.text_color());
+        } else {
+            painter.circle_stroke(center, PORT_RADIUS, Stroke::new(1.0, visuals.text_color()));
+        }
+    }
+}
+
+fn distance_to_segment(point: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let length_sq = ab.length_sq();
+    if length_sq == 0.0 {
+        return point.distance(a);
+    }
+    let t = ((point - a).dot(ab) / length_sq).clamp(0.0, 1.0);
+    point.distance(a + ab * t)
+}